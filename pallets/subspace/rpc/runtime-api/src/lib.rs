@@ -3,11 +3,11 @@
 use parity_scale_codec::{Decode, Encode};
 use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
-use sp_arithmetic::per_things::Percent;
+use sp_arithmetic::per_things::{Perbill, Percent};
 use sp_runtime::{
     sp_std::{collections::btree_map::BTreeMap, prelude::Vec},
     traits::{IdentifyAccount, Verify},
-    MultiSignature,
+    DispatchError, MultiSignature,
 };
 
 type Signature = MultiSignature;
@@ -20,12 +20,41 @@ pub struct ModuleStats {
     /// A map of AccountId to stake amount (in u64) for this module/key.
     /// This includes both direct stakes and delegations.
     pub stake_from: BTreeMap<AccountId, u64>,
+    /// A map of AccountId to the stake amount that is unbonding from this module/key.
+    pub unbonding: BTreeMap<AccountId, u64>,
     pub emission: u64,
     pub incentive: u16,
     pub dividends: u16,
     pub weights: Vec<(u16, u16)>, // Vec of (uid, weight)
 }
 
+/// `ModuleStats` as returned by version 1 of the API, before unbonding stake was reported.
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct ModuleStatsV1 {
+    pub last_update: u64,
+    pub registration_block: u64,
+    pub stake_from: BTreeMap<AccountId, u64>,
+    pub emission: u64,
+    pub incentive: u16,
+    pub dividends: u16,
+    pub weights: Vec<(u16, u16)>,
+}
+
+impl From<ModuleStatsV1> for ModuleStats {
+    fn from(stats: ModuleStatsV1) -> Self {
+        Self {
+            last_update: stats.last_update,
+            registration_block: stats.registration_block,
+            stake_from: stats.stake_from,
+            unbonding: BTreeMap::new(),
+            emission: stats.emission,
+            incentive: stats.incentive,
+            dividends: stats.dividends,
+            weights: stats.weights,
+        }
+    }
+}
+
 #[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
 pub struct ModuleParams {
     pub name: Vec<u8>,
@@ -40,8 +69,154 @@ pub struct ModuleInfo {
     pub stats: ModuleStats,
 }
 
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct ModuleInfoV1 {
+    pub params: ModuleParams,
+    pub stats: ModuleStatsV1,
+}
+
+impl From<ModuleInfoV1> for ModuleInfo {
+    fn from(info: ModuleInfoV1) -> Self {
+        Self {
+            params: info.params,
+            stats: info.stats.into(),
+        }
+    }
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct UnlockChunk {
+    pub netuid: u16,
+    pub module_key: AccountId,
+    pub amount: u64,
+    pub unlock_block: u64,
+}
+
+/// Stake locked by a conviction vote, on a subnet or on the whole network.
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct StakeLock {
+    pub netuid: Option<u16>,
+    pub amount: u64,
+    pub unlock_block: u64,
+}
+
+/// How a treasury stream releases its value.
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub enum StreamSchedule {
+    Linear {
+        duration: u64,
+    },
+    /// `(end, amount)` tranches, `end` counts the blocks since the start of the stream.
+    Tranches(Vec<(u64, u64)>),
+}
+
+/// Funds of the DAO treasury paid out over time. What is not released yet stays reserved.
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct TreasuryStream {
+    pub id: u64,
+    pub dest: AccountId,
+    pub value: u64,
+    pub released: u64,
+    pub start_block: u64,
+    /// The block the whole value is released at.
+    pub end_block: u64,
+    pub schedule: StreamSchedule,
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct EpochRecord {
+    /// The block the epoch ran at.
+    pub block: u64,
+    /// The module every uid belonged to during the epoch.
+    pub keys: Vec<AccountId>,
+    pub incentive: Vec<u16>,
+    pub dividends: Vec<u16>,
+    pub emission: Vec<u64>,
+    pub trust: Vec<u16>,
+    pub rank: Vec<u16>,
+    pub consensus: Vec<u16>,
+}
+
+/// Parameters to use instead of the stored ones when simulating an epoch.
+#[derive(
+    Decode, Encode, PartialEq, Eq, Clone, Debug, Default, TypeInfo, Serialize, Deserialize,
+)]
+#[serde(default)]
+pub struct EpochOverrides {
+    pub emission: Option<u64>,
+    pub kappa: Option<u16>,
+    pub bonds_ma: Option<u64>,
+    pub max_allowed_validators: Option<u16>,
+    pub incentive_ratio: Option<u16>,
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct EpochSimulation {
+    pub active_stake: Vec<u16>,
+    pub consensus: Vec<u16>,
+    pub ranks: Vec<u16>,
+    pub trust: Vec<u16>,
+    pub incentives: Vec<u16>,
+    pub dividends: Vec<u16>,
+    pub bonds: Vec<Vec<(u16, u16)>>, // Vec of (uid, bond) per uid
+    pub pruning_scores: Vec<u16>,
+    /// A map of module key to the amount each account received through it.
+    pub emissions: BTreeMap<AccountId, BTreeMap<AccountId, u64>>,
+}
+
+/// The emission expected at a future block, assuming every block emits in full.
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct EmissionProjection {
+    pub block: u64,
+    pub emission_per_block: u64,
+    pub total_issued: u64,
+}
+
+/// The part of the block emission a subnet gets.
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct SubnetEmissionShare {
+    pub netuid: u16,
+    pub share: Perbill,
+    pub emission_per_block: u64,
+}
+
 sp_api::decl_runtime_apis! {
+    /// Version 2 reports the unbonding stake of modules, and adds every method after
+    /// `get_module_info`.
+    #[api_version(2)]
     pub trait SubspaceRuntimeApi {
+        #[changed_in(2)]
+        fn get_module_info(key: AccountId, netuid: u16) -> ModuleInfoV1;
+
         fn get_module_info(key: AccountId, netuid: u16) -> ModuleInfo;
+
+        #[api_version(2)]
+        fn get_unlocking_chunks(key: AccountId) -> Vec<UnlockChunk>;
+
+        /// The stake locks of the key that did not expire yet.
+        #[api_version(2)]
+        fn get_stake_locks(key: AccountId) -> Vec<StakeLock>;
+
+        /// The streams paying out of the DAO treasury.
+        #[api_version(2)]
+        fn get_treasury_streams() -> Vec<TreasuryStream>;
+
+        /// The consensus outputs of the last epochs of the subnet, oldest first.
+        #[api_version(2)]
+        fn get_epoch_history(netuid: u16) -> Vec<EpochRecord>;
+
+        #[api_version(2)]
+        fn simulate_epoch(
+            netuid: u16,
+            overrides: EpochOverrides,
+        ) -> Result<EpochSimulation, DispatchError>;
+
+        /// The emission schedule every `step` blocks over the next `blocks` blocks.
+        #[api_version(2)]
+        fn get_emission_projection(blocks: u64, step: u64) -> Vec<EmissionProjection>;
+
+        /// The share of the block emission every subnet gets under the current allocation.
+        #[api_version(2)]
+        fn get_subnet_emission_shares() -> Vec<SubnetEmissionShare>;
     }
 }
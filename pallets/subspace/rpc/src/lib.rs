@@ -1,17 +1,17 @@
 use jsonrpsee::{
     core::{ClientError as JsonRpseeError, RpcResult},
     proc_macros::rpc,
-    types::error::ErrorObject,
+    types::error::{ErrorObject, ErrorObjectOwned},
 };
-use sp_api::ProvideRuntimeApi;
+use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_runtime::{
     traits::{Block as BlockT, IdentifyAccount, Verify},
     MultiSignature,
 };
 use std::sync::Arc;
-use subspace_runtime_api::ModuleInfo;
 pub use subspace_runtime_api::SubspaceRuntimeApi;
+use subspace_runtime_api::{EpochOverrides, EpochSimulation, ModuleInfo};
 
 type Signature = MultiSignature;
 type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
@@ -30,6 +30,15 @@ pub trait SubspaceApi<BlockHash> {
         netuid: u16,
         at: Option<BlockHash>,
     ) -> RpcResult<ModuleInfo>;
+
+    /// Runs the next epoch of `netuid` without persisting anything and returns its results.
+    #[method(name = "subspace_simulateEpoch")]
+    fn simulate_epoch(
+        &self,
+        netuid: u16,
+        overrides: Option<EpochOverrides>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<EpochSimulation>;
 }
 
 pub struct SubspacePallet<C, Block> {
@@ -61,9 +70,45 @@ where
         let api = self.client.runtime_api();
         let at = at.unwrap_or_else(|| self.client.info().best_hash);
 
-        let value = api.get_module_info(at, key, netuid).map_err(runtime_error_into_rpc_err);
+        let version = api
+            .api_version::<dyn SubspaceRuntimeApi<Block>>(at)
+            .map_err(runtime_error_into_error_object)?
+            .unwrap_or(1);
+
+        // runtimes before version 2 do not report the unbonding stake
+        #[allow(deprecated)]
+        let value = if version < 2 {
+            api.get_module_info_before_version_2(at, key, netuid).map(Into::into)
+        } else {
+            api.get_module_info(at, key, netuid)
+        };
+        let value = value.map_err(runtime_error_into_rpc_err);
         Ok(value.unwrap())
     }
+
+    fn simulate_epoch(
+        &self,
+        netuid: u16,
+        overrides: Option<EpochOverrides>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<EpochSimulation> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        let version = api
+            .api_version::<dyn SubspaceRuntimeApi<Block>>(at)
+            .map_err(runtime_error_into_error_object)?
+            .unwrap_or(1);
+        if version < 2 {
+            return Err(runtime_error_into_error_object(
+                "simulate_epoch needs version 2 of the runtime API",
+            ));
+        }
+
+        api.simulate_epoch(at, netuid, overrides.unwrap_or_default())
+            .map_err(runtime_error_into_error_object)?
+            .map_err(runtime_error_into_error_object)
+    }
 }
 
 const RUNTIME_ERROR: i32 = 1;
@@ -76,3 +121,8 @@ fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> JsonRpseeError {
         Some(format!("{:?}", err)),
     ))
 }
+
+/// Converts a runtime trap or a failed call into the error object returned to the caller.
+fn runtime_error_into_error_object(err: impl std::fmt::Debug) -> ErrorObjectOwned {
+    ErrorObject::owned(RUNTIME_ERROR, "Runtime error", Some(format!("{:?}", err)))
+}
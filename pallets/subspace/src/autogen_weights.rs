@@ -53,6 +53,11 @@ pub trait WeightInfo {
 	fn add_subnet_update() -> Weight;
 	fn vote_subnet_update() -> Weight;
 	fn accept_subnet_update() -> Weight;
+	fn adjust_registration() -> Weight;
+	fn queue_subnet_emission(s: u32, ) -> Weight;
+	fn linear_epoch(m: u32, d: u32, ) -> Weight;
+	fn yuma_epoch(m: u32, d: u32, ) -> Weight;
+	fn resolve_proposals(p: u32, v: u32, ) -> Weight;
 }
 
 /// Weights for `pallet_subspace` using the Substrate node and recommended hardware.
@@ -430,6 +435,55 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 		Weight::from_parts(14_941_000, 1965)
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 	}
+	// The weights below were not measured yet. They are estimates until the `adjust_registration`,
+	// `queue_subnet_emission`, `linear_epoch`, `yuma_epoch` and `resolve_proposals` benchmarks are
+	// run and this file is generated again.
+	fn adjust_registration() -> Weight {
+		Weight::from_parts(15_000_000, 3_500)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// The range of component `s` is `[1, 256]`.
+	fn queue_subnet_emission(s: u32, ) -> Weight {
+		Weight::from_parts(10_000_000, 3_500)
+			.saturating_add(Weight::from_parts(2_000_000, 2_500).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(s.into())))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// The range of component `m` is `[1, 800]`.
+	/// The range of component `d` is `[0, 4000]`.
+	fn linear_epoch(m: u32, d: u32, ) -> Weight {
+		Weight::from_parts(10_000_000, 10_000)
+			.saturating_add(Weight::from_parts(5_000_000, 2_500).saturating_mul(m.into()))
+			.saturating_add(Weight::from_parts(50_000, 100).saturating_mul(d.into()))
+			.saturating_add(T::DbWeight::get().reads(20_u64))
+			.saturating_add(T::DbWeight::get().reads((5_u64).saturating_mul(m.into())))
+			.saturating_add(T::DbWeight::get().writes(20_u64))
+			.saturating_add(T::DbWeight::get().writes((3_u64).saturating_mul(m.into())))
+	}
+	/// The range of component `m` is `[1, 800]`.
+	/// The range of component `d` is `[0, 4000]`.
+	fn yuma_epoch(m: u32, d: u32, ) -> Weight {
+		Weight::from_parts(10_000_000, 10_000)
+			.saturating_add(Weight::from_parts(6_000_000, 2_500).saturating_mul(m.into()))
+			.saturating_add(Weight::from_parts(50_000, 100).saturating_mul(d.into()))
+			.saturating_add(T::DbWeight::get().reads(20_u64))
+			.saturating_add(T::DbWeight::get().reads((5_u64).saturating_mul(m.into())))
+			.saturating_add(T::DbWeight::get().writes(20_u64))
+			.saturating_add(T::DbWeight::get().writes((3_u64).saturating_mul(m.into())))
+	}
+	/// The range of component `p` is `[0, 100]`.
+	/// The range of component `v` is `[0, 1000]`.
+	fn resolve_proposals(p: u32, v: u32, ) -> Weight {
+		Weight::from_parts(5_000_000, 1_500)
+			.saturating_add(Weight::from_parts(5_000_000, 2_500).saturating_mul(p.into()))
+			.saturating_add(Weight::from_parts(500_000, 100).saturating_mul(v.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(p.into())))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(v.into())))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(p.into())))
+	}
 }
 
 // For backwards compatibility and tests.
@@ -806,4 +860,53 @@ impl WeightInfo for () {
 		Weight::from_parts(14_941_000, 1965)
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 	}
+	// The weights below were not measured yet. They are estimates until the `adjust_registration`,
+	// `queue_subnet_emission`, `linear_epoch`, `yuma_epoch` and `resolve_proposals` benchmarks are
+	// run and this file is generated again.
+	fn adjust_registration() -> Weight {
+		Weight::from_parts(15_000_000, 3_500)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// The range of component `s` is `[1, 256]`.
+	fn queue_subnet_emission(s: u32, ) -> Weight {
+		Weight::from_parts(10_000_000, 3_500)
+			.saturating_add(Weight::from_parts(2_000_000, 2_500).saturating_mul(s.into()))
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(s.into())))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	/// The range of component `m` is `[1, 800]`.
+	/// The range of component `d` is `[0, 4000]`.
+	fn linear_epoch(m: u32, d: u32, ) -> Weight {
+		Weight::from_parts(10_000_000, 10_000)
+			.saturating_add(Weight::from_parts(5_000_000, 2_500).saturating_mul(m.into()))
+			.saturating_add(Weight::from_parts(50_000, 100).saturating_mul(d.into()))
+			.saturating_add(RocksDbWeight::get().reads(20_u64))
+			.saturating_add(RocksDbWeight::get().reads((5_u64).saturating_mul(m.into())))
+			.saturating_add(RocksDbWeight::get().writes(20_u64))
+			.saturating_add(RocksDbWeight::get().writes((3_u64).saturating_mul(m.into())))
+	}
+	/// The range of component `m` is `[1, 800]`.
+	/// The range of component `d` is `[0, 4000]`.
+	fn yuma_epoch(m: u32, d: u32, ) -> Weight {
+		Weight::from_parts(10_000_000, 10_000)
+			.saturating_add(Weight::from_parts(6_000_000, 2_500).saturating_mul(m.into()))
+			.saturating_add(Weight::from_parts(50_000, 100).saturating_mul(d.into()))
+			.saturating_add(RocksDbWeight::get().reads(20_u64))
+			.saturating_add(RocksDbWeight::get().reads((5_u64).saturating_mul(m.into())))
+			.saturating_add(RocksDbWeight::get().writes(20_u64))
+			.saturating_add(RocksDbWeight::get().writes((3_u64).saturating_mul(m.into())))
+	}
+	/// The range of component `p` is `[0, 100]`.
+	/// The range of component `v` is `[0, 1000]`.
+	fn resolve_proposals(p: u32, v: u32, ) -> Weight {
+		Weight::from_parts(5_000_000, 1_500)
+			.saturating_add(Weight::from_parts(5_000_000, 2_500).saturating_mul(p.into()))
+			.saturating_add(Weight::from_parts(500_000, 100).saturating_mul(v.into()))
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(p.into())))
+			.saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(v.into())))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(p.into())))
+	}
 }
\ No newline at end of file
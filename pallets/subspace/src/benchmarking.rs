@@ -1,6 +1,6 @@
 use super::*;
 
-use crate::Pallet;
+use crate::{subnet::ConsensusMode, Pallet};
 use frame_benchmarking::v2::*;
 use frame_system::RawOrigin;
 
@@ -35,6 +35,7 @@ fn register_helper<T: Config>(
     module_key: T::AccountId,
 ) -> u16 {
     set_user_balance::<T>(&module_key);
+    <Pallet<T>>::insert_to_whitelist(module_key.clone(), 1);
 
     <Pallet<T>>::register(
         RawOrigin::Signed(module_key.clone()).into(),
@@ -99,6 +100,54 @@ fn add_stake_multiple_helper<T: Config>(
     (netuid, module_keys, amounts)
 }
 
+fn epoch_helper<T: Config>(modules: u32, delegations: u32) -> u16 {
+    let network: Vec<u8> = b"network".to_vec();
+    let address: Vec<u8> = b"address".to_vec();
+
+    <Pallet<T>>::set_max_registrations_per_block(u16::MAX);
+
+    let mut netuid: u16 = 0;
+    for index in 0..modules {
+        let mut name: Vec<u8> = b"name".to_vec();
+        name.extend(index.to_le_bytes());
+
+        netuid = register_helper::<T>(
+            network.clone(),
+            name,
+            address.clone(),
+            account("key", index, SEED),
+        );
+    }
+
+    let module_keys = <Pallet<T>>::get_keys(netuid);
+    for index in 0..delegations {
+        let delegator: T::AccountId = account("delegator", index, SEED);
+        set_user_balance::<T>(&delegator);
+
+        <Pallet<T>>::add_stake(
+            RawOrigin::Signed(delegator).into(),
+            netuid,
+            module_keys[index as usize % module_keys.len()].clone(),
+            MIN_STAKE,
+        );
+    }
+
+    // every module votes for all the others
+    let uids = <Pallet<T>>::get_uids(netuid);
+    for (uid, module_key) in Keys::<T>::iter_prefix(netuid) {
+        let others: Vec<u16> = uids.iter().copied().filter(|other| *other != uid).collect();
+
+        <Pallet<T>>::set_weights(
+            RawOrigin::Signed(module_key).into(),
+            netuid,
+            others.clone(),
+            vec![1u16; others.len()],
+        );
+    }
+
+    netuid
+}
+
 #[benchmarks]
 mod benchmarks {
     use super::*;
@@ -111,6 +160,7 @@ mod benchmarks {
         let module_key: T::AccountId = account("key", 0, SEED);
 
         set_user_balance::<T>(&module_key);
+        <Pallet<T>>::insert_to_whitelist(module_key.clone(), 1);
 
         #[extrinsic_call]
         register(
@@ -234,6 +284,36 @@ mod benchmarks {
         Ok(())
     }
 
+    #[benchmark]
+    fn transfer_stake_across_subnets() -> Result<(), BenchmarkError> {
+        let (_, _, _, to_module_key, to_netuid) = default_register_helper::<T>();
+
+        let from_network: Vec<u8> = b"from_network".to_vec();
+        let from_name: Vec<u8> = b"from_name".to_vec();
+        let from_address: Vec<u8> = b"from_address".to_vec();
+        let from_module_key: T::AccountId = account("from_key", 0, SEED);
+
+        let from_netuid = add_stake_helper::<T>(
+            from_network.clone(),
+            from_name.clone(),
+            from_address.clone(),
+            from_module_key.clone(),
+            MIN_STAKE,
+        );
+
+        #[extrinsic_call]
+        transfer_stake_across_subnets(
+            RawOrigin::Signed(from_module_key.clone()),
+            from_netuid,
+            from_module_key.clone(),
+            to_netuid,
+            to_module_key.clone(),
+            MIN_STAKE,
+        );
+
+        Ok(())
+    }
+
     #[benchmark]
     fn transfer_multiple() -> Result<(), BenchmarkError> {
         let network: Vec<u8> = b"network".to_vec();
@@ -519,6 +599,106 @@ mod benchmarks {
         Ok(())
     }
 
+    #[benchmark]
+    fn adjust_registration() -> Result<(), BenchmarkError> {
+        let (_, _, _, _, netuid) = default_register_helper::<T>();
+
+        #[block]
+        {
+            <Pallet<T>>::adjust_registration(netuid, 0, 1, 1, 1);
+        }
+
+        Ok(())
+    }
+
+    #[benchmark]
+    fn queue_subnet_emission(s: Linear<1, 256>) -> Result<(), BenchmarkError> {
+        let address: Vec<u8> = b"address".to_vec();
+
+        let mut netuid: u16 = 0;
+        for index in 0..s {
+            let mut network: Vec<u8> = b"network".to_vec();
+            network.extend(index.to_le_bytes());
+
+            netuid = register_helper::<T>(
+                network,
+                b"name".to_vec(),
+                address.clone(),
+                account("key", index, SEED),
+            );
+        }
+
+        let threshold = SubnetStakeThreshold::<T>::get();
+
+        #[block]
+        {
+            let emission = <Pallet<T>>::calculate_network_emission(netuid, threshold);
+            PendingEmission::<T>::mutate(netuid, |queued| *queued += emission);
+        }
+
+        Ok(())
+    }
+
+    #[benchmark]
+    fn linear_epoch(m: Linear<1, 800>, d: Linear<0, 4000>) -> Result<(), BenchmarkError> {
+        let netuid = epoch_helper::<T>(m, d);
+        ConsensusModeSubnet::<T>::insert(netuid, ConsensusMode::Linear);
+
+        #[block]
+        {
+            <Pallet<T>>::linear_epoch(netuid, MIN_STAKE);
+        }
+
+        Ok(())
+    }
+
+    #[benchmark]
+    fn yuma_epoch(m: Linear<1, 800>, d: Linear<0, 4000>) -> Result<(), BenchmarkError> {
+        let netuid = epoch_helper::<T>(m, d);
+        ConsensusModeSubnet::<T>::insert(netuid, ConsensusMode::Yuma);
+
+        #[block]
+        {
+            let _ = crate::step::yuma::YumaCalc::<T>::new(netuid, MIN_STAKE).run();
+        }
+
+        Ok(())
+    }
+
+    #[benchmark]
+    fn resolve_proposals(p: Linear<0, 100>, v: Linear<0, 1000>) -> Result<(), BenchmarkError> {
+        for index in 0..p {
+            let proposer: T::AccountId = account("proposer", index, SEED);
+            set_user_balance::<T>(&proposer);
+
+            <Pallet<T>>::do_add_custom_proposal(
+                RawOrigin::Signed(proposer).into(),
+                b"data".to_vec(),
+            )?;
+        }
+
+        let proposal_ids: Vec<u64> = Proposals::<T>::iter_keys().collect();
+        if !proposal_ids.is_empty() {
+            for index in 0..v {
+                let voter: T::AccountId = account("voter", index, SEED);
+                let proposal_id = proposal_ids[index as usize % proposal_ids.len()];
+
+                Proposals::<T>::mutate(proposal_id, |proposal| {
+                    if let Some(proposal) = proposal {
+                        proposal.votes_for.insert(voter, 1);
+                    }
+                });
+            }
+        }
+
+        #[block]
+        {
+            <Pallet<T>>::resolve_proposals(<Pallet<T>>::get_current_block_number());
+        }
+
+        Ok(())
+    }
+
     impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
 }
 
@@ -0,0 +1,169 @@
+use super::*;
+use frame_support::pallet_prelude::{DispatchResult, Weight};
+use sp_runtime::traits::Hash;
+
+/// How many blocks a curator motion waits for approvals before it is dropped.
+pub const CURATOR_MOTION_DURATION: u64 = 75_600; // Aprox 7 days
+
+/// An action of the curator council, taken once enough members approve it.
+#[derive(Clone, Debug, PartialEq, Eq, TypeInfo, Decode, Encode)]
+#[scale_info(skip_type_params(T))]
+pub enum CuratorAction<T: Config> {
+    AddToWhitelist {
+        module_key: T::AccountId,
+        recommended_weight: u8,
+    },
+    RemoveFromWhitelist {
+        module_key: T::AccountId,
+    },
+    RefuseDaoApplication {
+        application_id: u64,
+    },
+    FastTrackProposal {
+        proposal_id: u64,
+    },
+}
+
+/// A curator action and the council members that approved it so far.
+#[derive(Clone, Debug, PartialEq, Eq, TypeInfo, Decode, Encode)]
+#[scale_info(skip_type_params(T))]
+pub struct CuratorMotion<T: Config> {
+    pub action: CuratorAction<T>,
+    pub approvals: BTreeSet<T::AccountId>,
+    pub expiration_block: u64,
+}
+
+impl<T: Config> Pallet<T> {
+    /// Whether the key is a member of the curator council.
+    pub fn is_curator(key: &T::AccountId) -> bool {
+        CuratorCouncil::<T>::get().contains(key)
+    }
+
+    /// Records the approval of a council member for the action, and takes the action once
+    /// `CuratorCouncilThreshold` members approved it. Members approve an action by submitting the
+    /// same call.
+    pub(crate) fn approve_curator_action(
+        key: T::AccountId,
+        action: CuratorAction<T>,
+    ) -> DispatchResult {
+        // --- 1. Ensure that the key is a member of the curator council.
+        ensure!(Self::is_curator(&key), Error::<T>::NotCurator);
+
+        // --- 2. Make sure the action can still be taken, so motions that can not pass are not
+        // kept around.
+        Self::check_curator_action(&action)?;
+
+        // --- 3. Record the approval of the member. Approvals of an expired motion do not count
+        // anymore, the motion starts over.
+        let block_number = Self::get_current_block_number();
+        let motion_hash = T::Hashing::hash_of(&action);
+        let mut motion = CuratorMotions::<T>::get(motion_hash)
+            .filter(|motion| motion.expiration_block > block_number)
+            .unwrap_or(CuratorMotion {
+                action,
+                approvals: BTreeSet::new(),
+                expiration_block: block_number.saturating_add(CURATOR_MOTION_DURATION),
+            });
+        ensure!(
+            motion.approvals.insert(key.clone()),
+            Error::<T>::AlreadyApprovedMotion
+        );
+        Self::deposit_event(Event::<T>::CuratorMotionApproved(motion_hash, key));
+
+        // --- 4. Take the action once the council agrees on it.
+        if motion.approvals.len() < CuratorCouncilThreshold::<T>::get() as usize {
+            CuratorMotions::<T>::insert(motion_hash, motion);
+            return Ok(());
+        }
+
+        CuratorMotions::<T>::remove(motion_hash);
+        Self::execute_curator_action(motion.action)?;
+
+        Self::deposit_event(Event::<T>::CuratorMotionExecuted(motion_hash));
+        Ok(())
+    }
+
+    /// Removes the curator motions that expired without enough approvals. Returns the weight it
+    /// consumed.
+    pub(crate) fn prune_curator_motions(block_number: u64) -> Weight {
+        let mut motions = 0u64;
+        let expired: Vec<_> = CuratorMotions::<T>::iter()
+            .inspect(|_| motions = motions.saturating_add(1))
+            .filter(|(_, motion)| motion.expiration_block <= block_number)
+            .map(|(motion_hash, _)| motion_hash)
+            .collect();
+
+        for motion_hash in &expired {
+            CuratorMotions::<T>::remove(motion_hash);
+        }
+
+        T::DbWeight::get().reads_writes(motions, expired.len() as u64)
+    }
+
+    fn check_curator_action(action: &CuratorAction<T>) -> DispatchResult {
+        match action {
+            CuratorAction::AddToWhitelist {
+                module_key,
+                recommended_weight,
+            } => {
+                // Make sure the key application was submitted
+                let application_exists = CuratorApplications::<T>::iter()
+                    .any(|(_, application)| application.user_id == *module_key);
+                ensure!(application_exists, Error::<T>::ApplicationNotFound);
+
+                ensure!(
+                    !Self::is_in_legit_whitelist(module_key),
+                    Error::<T>::AlreadyWhitelisted
+                );
+                ensure!(
+                    *recommended_weight <= 100 && *recommended_weight > 0,
+                    Error::<T>::InvalidRecommendedWeight
+                );
+            }
+            CuratorAction::RemoveFromWhitelist { module_key } => {
+                ensure!(
+                    Self::is_in_legit_whitelist(module_key),
+                    Error::<T>::NotWhitelisted
+                );
+            }
+            CuratorAction::RefuseDaoApplication { application_id } => {
+                Self::get_pending_application(*application_id)?;
+            }
+            CuratorAction::FastTrackProposal { proposal_id } => {
+                Self::get_fast_trackable_proposal(*proposal_id)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn execute_curator_action(action: CuratorAction<T>) -> DispatchResult {
+        match action {
+            CuratorAction::AddToWhitelist {
+                module_key,
+                recommended_weight,
+            } => {
+                Self::insert_to_whitelist(module_key.clone(), recommended_weight);
+                // only a pending application is paid back, refused or expired ones are not
+                if let Some(application_id) = Self::get_pending_application_id(&module_key) {
+                    Self::execute_application(application_id)?;
+                }
+
+                Self::deposit_event(Event::WhitelistModuleAdded(module_key));
+            }
+            CuratorAction::RemoveFromWhitelist { module_key } => {
+                Self::rm_from_whitelist(&module_key);
+
+                Self::deposit_event(Event::WhitelistModuleRemoved(module_key));
+            }
+            CuratorAction::RefuseDaoApplication { application_id } => {
+                Self::refuse_application(application_id)?;
+            }
+            CuratorAction::FastTrackProposal { proposal_id } => {
+                Self::fast_track(proposal_id)?;
+            }
+        }
+
+        Ok(())
+    }
+}
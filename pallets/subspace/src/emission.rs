@@ -0,0 +1,331 @@
+use super::*;
+use sp_arithmetic::{
+    per_things::{Perbill, Percent},
+    traits::IntegerSquareRoot,
+};
+use substrate_fixed::types::I64F64;
+
+/// Most points a single emission projection returns.
+pub const MAX_PROJECTION_POINTS: usize = 1024;
+
+/// How the emission per block falls as tokens get issued.
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo)]
+pub enum EmissionCurve {
+    /// The emission halves every `interval` tokens issued.
+    Halving { interval: u64 },
+    /// The emission shrinks by `rate` every `interval` tokens issued.
+    Decay { interval: u64, rate: Percent },
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo)]
+pub struct EmissionSchedule {
+    pub curve: EmissionCurve,
+    /// No tokens are emitted once this many were issued.
+    pub max_supply: u64,
+}
+
+impl EmissionSchedule {
+    fn interval(&self) -> u64 {
+        match self.curve {
+            EmissionCurve::Halving { interval } | EmissionCurve::Decay { interval, .. } => {
+                interval.max(1)
+            }
+        }
+    }
+
+    /// Returns the emission per block after `issued` tokens, starting at `unit_emission`.
+    pub fn emission_per_block(&self, unit_emission: u64, issued: u64) -> u64 {
+        let Some(left) = self.max_supply.checked_sub(issued) else {
+            return 0;
+        };
+
+        let steps = issued / self.interval();
+        let emission = match self.curve {
+            EmissionCurve::Halving { .. } => u32::try_from(steps)
+                .ok()
+                .and_then(|steps| unit_emission.checked_shr(steps))
+                .unwrap_or(0),
+            EmissionCurve::Decay { rate, .. } => {
+                let mut emission = unit_emission;
+                for _ in 0..steps {
+                    if emission == 0 {
+                        break;
+                    }
+                    emission = emission.saturating_sub(rate.mul_ceil(emission));
+                }
+                emission
+            }
+        };
+
+        emission.min(left)
+    }
+
+    /// Returns the amount that can be issued before the emission per block changes.
+    fn issuable_at_current_rate(&self, issued: u64) -> u64 {
+        let interval = self.interval();
+        let until_next_step = interval - issued % interval;
+        until_next_step.min(self.max_supply.saturating_sub(issued))
+    }
+}
+
+/// The emission expected at a future block, assuming every block emits in full.
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo)]
+pub struct EmissionProjection {
+    pub block: u64,
+    pub emission_per_block: u64,
+    pub total_issued: u64,
+}
+
+/// How the emission of a block is split between the subnets above the stake threshold.
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo)]
+pub enum EmissionAllocation {
+    /// In proportion to the stake of the subnets.
+    Stake,
+    /// In proportion to the square root of the stake of the subnets.
+    SqrtStake,
+    /// In proportion to the stake of the subnets, no subnet getting more than `max_share`. What
+    /// is cut off goes to the other subnets, and to the DAO treasury once all of them are capped.
+    CappedStake { max_share: Percent },
+    /// In proportion to the stake of the subnets, the general subnet getting at least `floor`.
+    GeneralSubnetFloor { floor: Percent },
+}
+
+impl EmissionAllocation {
+    /// Returns the part of the emission each subnet gets, from the stake of the subnets that
+    /// can get emission.
+    pub fn allocate(&self, stakes: &BTreeMap<u16, u64>) -> BTreeMap<u16, I64F64> {
+        match self {
+            EmissionAllocation::Stake => proportional(stakes),
+            EmissionAllocation::SqrtStake => {
+                let roots = stakes.iter().map(|(netuid, stake)| (*netuid, stake.integer_sqrt()));
+                proportional(&roots.collect())
+            }
+            EmissionAllocation::CappedStake { max_share } => {
+                capped(stakes, percent_to_fixed(*max_share))
+            }
+            EmissionAllocation::GeneralSubnetFloor { floor } => {
+                let mut shares = proportional(stakes);
+                let floor = percent_to_fixed(*floor);
+                let Some(general) = shares.get(&0).copied() else {
+                    return shares;
+                };
+                if general >= floor {
+                    return shares;
+                }
+
+                // the other subnets give up the difference in proportion to their share
+                let others = I64F64::from_num(1) - general;
+                let scale = if others > 0 {
+                    (I64F64::from_num(1) - floor) / others
+                } else {
+                    I64F64::from_num(0)
+                };
+                for (netuid, share) in shares.iter_mut() {
+                    *share = if *netuid == 0 { floor } else { *share * scale };
+                }
+                shares
+            }
+        }
+    }
+}
+
+fn percent_to_fixed(percent: Percent) -> I64F64 {
+    I64F64::from_num(percent.deconstruct()) / I64F64::from_num(100)
+}
+
+fn proportional(values: &BTreeMap<u16, u64>) -> BTreeMap<u16, I64F64> {
+    let total = I64F64::from_num(values.values().map(|v| *v as u128).sum::<u128>());
+    values
+        .iter()
+        .map(|(netuid, value)| {
+            let share = if total > 0 {
+                I64F64::from_num(*value) / total
+            } else {
+                I64F64::from_num(0)
+            };
+            (*netuid, share)
+        })
+        .collect()
+}
+
+fn capped(stakes: &BTreeMap<u16, u64>, cap: I64F64) -> BTreeMap<u16, I64F64> {
+    let mut shares = proportional(stakes);
+    let mut capped = BTreeSet::new();
+
+    // every round caps at least one more subnet, so this ends after as many rounds as subnets
+    loop {
+        let over: Vec<u16> = shares
+            .iter()
+            .filter(|(netuid, share)| !capped.contains(*netuid) && **share > cap)
+            .map(|(netuid, _)| *netuid)
+            .collect();
+        if over.is_empty() {
+            return shares;
+        }
+        capped.extend(over);
+
+        let capped_total = cap.saturating_mul(I64F64::from_num(capped.len()));
+        let free = I64F64::from_num(1).saturating_sub(capped_total).max(I64F64::from_num(0));
+        let uncapped: BTreeMap<u16, u64> = stakes
+            .iter()
+            .filter(|(netuid, _)| !capped.contains(*netuid))
+            .map(|(netuid, stake)| (*netuid, *stake))
+            .collect();
+
+        let uncapped_shares = proportional(&uncapped);
+        for (netuid, share) in shares.iter_mut() {
+            *share = match uncapped_shares.get(netuid) {
+                Some(uncapped_share) => free * *uncapped_share,
+                None => cap,
+            };
+        }
+    }
+}
+
+/// The part of the block emission a subnet gets.
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo)]
+pub struct SubnetEmissionShare {
+    pub netuid: u16,
+    pub share: Perbill,
+    pub emission_per_block: u64,
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn get_emission_schedule() -> EmissionSchedule {
+        EmissionScheduleGlobal::<T>::get()
+    }
+
+    pub fn check_emission_schedule(schedule: &EmissionSchedule) -> DispatchResult {
+        let valid_curve = match schedule.curve {
+            EmissionCurve::Halving { interval } => interval > 0,
+            EmissionCurve::Decay { interval, rate } => {
+                interval > 0 && rate.deconstruct() > 0 && rate.deconstruct() < 100
+            }
+        };
+        ensure!(valid_curve, Error::<T>::InvalidEmissionCurve);
+
+        // the tokens already issued can not be taken back
+        ensure!(
+            schedule.max_supply >= TotalIssued::<T>::get(),
+            Error::<T>::InvalidMaxSupply
+        );
+
+        Ok(())
+    }
+
+    /// Returns the emission per block and the total issued every `step` blocks, for the next
+    /// `blocks` blocks.
+    pub fn project_emission(blocks: u64, step: u64) -> Vec<EmissionProjection> {
+        let schedule = Self::get_emission_schedule();
+        let unit_emission = UnitEmission::<T>::get();
+        let step = step.max(1);
+
+        let mut block = Self::get_current_block_number();
+        let end = block.saturating_add(blocks);
+        let mut issued = TotalIssued::<T>::get();
+        let mut projection = Vec::new();
+
+        loop {
+            projection.push(EmissionProjection {
+                block,
+                emission_per_block: schedule.emission_per_block(unit_emission, issued),
+                total_issued: issued,
+            });
+            if block >= end || projection.len() >= MAX_PROJECTION_POINTS {
+                break;
+            }
+
+            // jump from one rate change to the next instead of going block by block
+            let mut remaining = step.min(end - block);
+            block += remaining;
+            while remaining > 0 {
+                let emission = schedule.emission_per_block(unit_emission, issued);
+                if emission == 0 {
+                    break;
+                }
+
+                let blocks_at_rate =
+                    schedule.issuable_at_current_rate(issued).div_ceil(emission).min(remaining);
+                issued = issued
+                    .saturating_add(blocks_at_rate.saturating_mul(emission))
+                    .min(schedule.max_supply);
+                remaining -= blocks_at_rate;
+            }
+        }
+
+        projection
+    }
+
+    pub fn get_emission_allocation() -> EmissionAllocation {
+        EmissionAllocationGlobal::<T>::get()
+    }
+
+    pub fn check_emission_allocation(allocation: &EmissionAllocation) -> DispatchResult {
+        let valid = match allocation {
+            EmissionAllocation::Stake | EmissionAllocation::SqrtStake => true,
+            EmissionAllocation::CappedStake { max_share } => {
+                max_share.deconstruct() > 0 && max_share.deconstruct() <= 100
+            }
+            EmissionAllocation::GeneralSubnetFloor { floor } => floor.deconstruct() <= 100,
+        };
+        ensure!(valid, Error::<T>::InvalidEmissionAllocation);
+
+        Ok(())
+    }
+
+    /// Returns the stake of the subnets that can get emission: the general subnet, and the ones
+    /// holding at least `subnet_stake_threshold` of the total stake.
+    pub fn get_eligible_subnet_stakes(subnet_stake_threshold: Percent) -> BTreeMap<u16, u64> {
+        let total_stake = I64F64::from_num(Self::total_stake());
+        let threshold = percent_to_fixed(subnet_stake_threshold);
+
+        N::<T>::iter_keys()
+            .filter_map(|netuid| {
+                let subnet_stake = Self::get_total_subnet_stake(netuid);
+                let eligible = netuid == 0
+                    || (total_stake > 0
+                        && I64F64::from_num(subnet_stake) / total_stake >= threshold);
+                eligible.then_some((netuid, subnet_stake))
+            })
+            .collect()
+    }
+
+    /// Returns the part of the block emission each subnet that can get emission gets.
+    pub fn get_subnet_emission_shares(subnet_stake_threshold: Percent) -> BTreeMap<u16, I64F64> {
+        let stakes = Self::get_eligible_subnet_stakes(subnet_stake_threshold);
+        Self::get_emission_allocation().allocate(&stakes)
+    }
+
+    /// Returns the part of the block emission no subnet gets, which happens when every subnet is
+    /// capped. It is issued to the DAO treasury instead.
+    pub fn get_unallocated_emission(subnet_stake_threshold: Percent) -> u64 {
+        let shares = Self::get_subnet_emission_shares(subnet_stake_threshold);
+        let allocated = shares.values().fold(I64F64::from_num(0), |total, share| total + *share);
+        // without any stake nothing is emitted at all
+        if allocated == 0 {
+            return 0;
+        }
+
+        let unallocated = I64F64::from_num(1).saturating_sub(allocated).max(I64F64::from_num(0));
+        (unallocated * I64F64::from_num(Self::get_total_emission_per_block())).to_num::<u64>()
+    }
+
+    /// Returns the share and emission of every subnet at the current block.
+    pub fn project_subnet_emissions() -> Vec<SubnetEmissionShare> {
+        let shares = Self::get_subnet_emission_shares(SubnetStakeThreshold::<T>::get());
+        let emission_per_block = I64F64::from_num(Self::get_total_emission_per_block());
+
+        Self::netuids()
+            .into_iter()
+            .map(|netuid| {
+                let share = shares.get(&netuid).copied().unwrap_or_default();
+                let parts = (share * I64F64::from_num(1_000_000_000)).to_num::<u32>();
+                SubnetEmissionShare {
+                    netuid,
+                    share: Perbill::from_parts(parts),
+                    emission_per_block: (share * emission_per_block).to_num::<u64>(),
+                }
+            })
+            .collect()
+    }
+}
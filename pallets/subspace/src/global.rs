@@ -11,7 +11,8 @@ impl<T: Config> Pallet<T> {
             max_allowed_subnets: Self::get_global_max_allowed_subnets(),
             max_allowed_modules: Self::get_max_allowed_modules(),
             unit_emission: UnitEmission::<T>::get(),
-            curator: Self::get_curator(),
+            curator_council: CuratorCouncil::<T>::get(),
+            curator_council_threshold: CuratorCouncilThreshold::<T>::get(),
             floor_founder_share: FloorFounderShare::<T>::get(),
             floor_delegation_fee: Self::get_floor_delegation_fee(),
             // burn & registrations
@@ -31,6 +32,25 @@ impl<T: Config> Pallet<T> {
                                                                                             in percent of the overall network stake */
             // s0
             general_subnet_application_cost: Self::get_general_subnet_application_cost(),
+            // delegation fee
+            delegation_fee_increase_delay: DelegationFeeIncreaseDelay::<T>::get(),
+            max_delegation_fee_increase: MaxDelegationFeeIncrease::<T>::get(),
+            // emission
+            emission_schedule: Self::get_emission_schedule(),
+            emission_allocation: Self::get_emission_allocation(),
+            // whitelist
+            curator_weight_ratio: CuratorWeightRatio::<T>::get(),
+            // runtime call proposals
+            runtime_call_participation_threshold: RuntimeCallParticipationThreshold::<T>::get(),
+            runtime_call_enactment_delay: RuntimeCallEnactmentDelay::<T>::get(),
+            // enactment
+            proposal_enactment_delay: ProposalEnactmentDelay::<T>::get(),
+            // conviction voting
+            conviction_lock_epochs: ConvictionLockEpochs::<T>::get(),
+            // curator applications
+            application_vote_mode: ApplicationVoteMode::<T>::get(),
+            // proposal pruning
+            proposal_retention: ProposalRetention::<T>::get(),
         }
     }
 
@@ -116,6 +136,45 @@ impl<T: Config> Pallet<T> {
             Error::<T>::InvalidProposalParticipationThreshold
         );
 
+        // a zero cap would freeze every delegation fee in place
+        ensure!(
+            params.max_delegation_fee_increase.deconstruct() > 0
+                && params.max_delegation_fee_increase.deconstruct() <= 100,
+            Error::<T>::InvalidMaxDelegationFeeIncrease
+        );
+
+        Self::check_emission_schedule(&params.emission_schedule)?;
+        Self::check_emission_allocation(&params.emission_allocation)?;
+
+        // calls run with root origin, so they need at least the support other proposals need
+        ensure!(
+            params.runtime_call_participation_threshold.deconstruct() <= 100
+                && params.runtime_call_participation_threshold
+                    >= params.proposal_participation_threshold,
+            Error::<T>::InvalidRuntimeCallParticipationThreshold
+        );
+        ensure!(
+            params.runtime_call_enactment_delay > 0,
+            Error::<T>::InvalidRuntimeCallEnactmentDelay
+        );
+
+        ensure!(
+            params.conviction_lock_epochs > 0,
+            Error::<T>::InvalidConvictionLockEpochs
+        );
+
+        ensure!(
+            params.proposal_retention > 0,
+            Error::<T>::InvalidProposalRetention
+        );
+
+        ensure!(
+            !params.curator_council.is_empty()
+                && params.curator_council_threshold > 0
+                && params.curator_council_threshold as usize <= params.curator_council.len(),
+            Error::<T>::InvalidCuratorCouncil
+        );
+
         Ok(())
     }
 
@@ -137,7 +196,7 @@ impl<T: Config> Pallet<T> {
         Self::set_subnet_stake_threshold(params.subnet_stake_threshold);
         Self::set_adjustment_alpha(params.adjustment_alpha);
         Self::set_floor_delegation_fee(params.floor_delegation_fee);
-        Self::set_curator(params.curator);
+        Self::set_curator_council(params.curator_council, params.curator_council_threshold);
         FloorFounderShare::<T>::put(params.floor_founder_share);
 
         // weights
@@ -148,14 +207,45 @@ impl<T: Config> Pallet<T> {
         Self::set_proposal_cost(params.proposal_cost);
         Self::set_proposal_expiration(params.proposal_expiration);
         Self::set_proposal_participation_threshold(params.proposal_participation_threshold);
-    }
 
-    pub fn get_curator() -> T::AccountId {
-        Curator::<T>::get()
+        // delegation fee
+        DelegationFeeIncreaseDelay::<T>::put(params.delegation_fee_increase_delay);
+        MaxDelegationFeeIncrease::<T>::put(params.max_delegation_fee_increase);
+
+        // emission
+        EmissionScheduleGlobal::<T>::put(params.emission_schedule);
+        EmissionAllocationGlobal::<T>::put(params.emission_allocation);
+
+        // whitelist
+        CuratorWeightRatio::<T>::put(params.curator_weight_ratio);
+
+        // runtime call proposals
+        RuntimeCallParticipationThreshold::<T>::put(params.runtime_call_participation_threshold);
+        RuntimeCallEnactmentDelay::<T>::put(params.runtime_call_enactment_delay);
+
+        // enactment
+        ProposalEnactmentDelay::<T>::put(params.proposal_enactment_delay);
+
+        // conviction voting
+        ConvictionLockEpochs::<T>::put(params.conviction_lock_epochs);
+
+        // curator applications
+        ApplicationVoteMode::<T>::put(params.application_vote_mode);
+
+        // proposal pruning
+        ProposalRetention::<T>::put(params.proposal_retention);
     }
 
-    pub fn set_curator(curator: T::AccountId) {
-        Curator::<T>::put(curator)
+    /// Changes the curator council. Motions still waiting for approvals are dropped when the
+    /// members or the threshold change.
+    pub fn set_curator_council(members: BTreeSet<T::AccountId>, threshold: u16) {
+        if members != CuratorCouncil::<T>::get() || threshold != CuratorCouncilThreshold::<T>::get()
+        {
+            let _ = CuratorMotions::<T>::clear(u32::MAX, None);
+        }
+
+        CuratorCouncil::<T>::put(members);
+        CuratorCouncilThreshold::<T>::put(threshold);
     }
 
     pub fn get_min_weight_stake() -> u64 {
@@ -12,7 +12,7 @@ pub mod migrations;
 
 use frame_support::{
     dispatch,
-    dispatch::{DispatchInfo, PostDispatchInfo},
+    dispatch::{DispatchInfo, GetDispatchInfo, PostDispatchInfo},
     ensure,
     traits::{tokens::WithdrawReasons, Currency, ExistenceRequirement, IsSubType},
 };
@@ -31,17 +31,23 @@ pub use autogen_weights::WeightInfo;
 #[cfg(debug_assertions)]
 pub use step::yuma;
 
+pub use step::simulation::{EpochOverrides, EpochSimulation};
+
 // =========================
 //	==== Pallet Imports =====
 // =========================
+pub mod council;
+pub mod emission;
 mod global;
 mod math;
 pub mod module;
 mod profit_share;
 mod registration;
+mod rewards;
 mod staking;
 mod step;
 pub mod subnet;
+pub mod treasury;
 pub mod voting;
 mod weights;
 
@@ -56,17 +62,32 @@ pub mod pallet {
         clippy::type_complexity
     )]
 
-    use self::voting::{CuratorApplication, Proposal, VoteMode};
+    use self::{
+        council::CuratorMotion,
+        emission::{EmissionAllocation, EmissionCurve, EmissionSchedule},
+        staking::{UnlockChunk, UnlockingChunks},
+        step::EpochRecord,
+        subnet::ConsensusMode,
+        treasury::{StreamSchedule, TreasuryStream},
+        voting::{
+            BoundedCallOf, Conviction, CuratorApplication, Proposal, ProposalData, StakeLock,
+            VoteMode,
+        },
+    };
 
     use super::*;
-    use frame_support::{pallet_prelude::*, traits::Currency, Identity};
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{Currency, QueryPreimage, StorePreimage},
+        Identity,
+    };
     use frame_system::pallet_prelude::*;
 
     use module::ModuleChangeset;
-    use sp_arithmetic::per_things::Percent;
+    use sp_arithmetic::per_things::{Perbill, Percent};
     pub use sp_std::{vec, vec::Vec};
 
-    const STORAGE_VERSION: StorageVersion = StorageVersion::new(10);
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(17);
 
     #[pallet::pallet]
     #[pallet::storage_version(STORAGE_VERSION)]
@@ -85,6 +106,18 @@ pub mod pallet {
 
         /// Type representing the weight of this pallet
         type WeightInfo: WeightInfo;
+
+        /// The call type dispatched with root origin by accepted runtime call proposals.
+        #[pallet::no_default]
+        type RuntimeCall: Parameter
+            + Dispatchable<RuntimeOrigin = Self::RuntimeOrigin, PostInfo = PostDispatchInfo>
+            + GetDispatchInfo
+            + From<frame_system::Call<Self>>
+            + IsType<<Self as frame_system::Config>::RuntimeCall>;
+
+        /// Keeps the calls of runtime call proposals that are too large to be stored inline.
+        #[pallet::no_default]
+        type Preimages: QueryPreimage<H = Self::Hashing> + StorePreimage;
     }
 
     pub type BalanceOf<T> =
@@ -102,6 +135,30 @@ pub mod pallet {
     #[pallet::storage] // --- ITEM ( unit_emission )
     pub(super) type UnitEmission<T> = StorageValue<_, u64, ValueQuery, DefaultUnitEmission<T>>;
 
+    #[pallet::type_value]
+    pub fn DefaultEmissionSchedule<T: Config>() -> EmissionSchedule {
+        EmissionSchedule {
+            curve: EmissionCurve::Halving {
+                interval: 10_000_000 * DefaultUnitEmission::<T>::get(),
+            },
+            max_supply: 1_000_000_000_000_000_000, // 1B $COMAI
+        }
+    }
+    #[pallet::storage] // --- ITEM ( emission_schedule )
+    pub type EmissionScheduleGlobal<T> =
+        StorageValue<_, EmissionSchedule, ValueQuery, DefaultEmissionSchedule<T>>;
+
+    #[pallet::storage] // --- ITEM ( total_issued )
+    pub type TotalIssued<T> = StorageValue<_, u64, ValueQuery>;
+
+    #[pallet::type_value]
+    pub fn DefaultEmissionAllocation<T: Config>() -> EmissionAllocation {
+        EmissionAllocation::Stake
+    }
+    #[pallet::storage] // --- ITEM ( emission_allocation )
+    pub type EmissionAllocationGlobal<T> =
+        StorageValue<_, EmissionAllocation, ValueQuery, DefaultEmissionAllocation<T>>;
+
     #[pallet::type_value]
     pub fn DefaultMinBurn<T: Config>() -> u64 {
         4_000_000_000 // 4 $COMAI
@@ -244,6 +301,22 @@ pub mod pallet {
     pub type FloorDelegationFee<T> =
         StorageValue<_, Percent, ValueQuery, DefaultMinDelegationFeeGlobal<T>>;
 
+    #[pallet::type_value]
+    pub fn DefaultDelegationFeeIncreaseDelay<T: Config>() -> u16 {
+        1
+    }
+    #[pallet::storage] // --- ITEM ( delegation_fee_increase_delay ) | In epochs
+    pub type DelegationFeeIncreaseDelay<T> =
+        StorageValue<_, u16, ValueQuery, DefaultDelegationFeeIncreaseDelay<T>>;
+
+    #[pallet::type_value]
+    pub fn DefaultMaxDelegationFeeIncrease<T: Config>() -> Percent {
+        Percent::from_percent(10u8)
+    }
+    #[pallet::storage] // --- ITEM ( max_delegation_fee_increase ) | Per change
+    pub type MaxDelegationFeeIncrease<T> =
+        StorageValue<_, Percent, ValueQuery, DefaultMaxDelegationFeeIncrease<T>>;
+
     #[pallet::storage] // --- MAP ( netuid ) --> min_allowed_weights
     pub type MinWeightStake<T> = StorageValue<_, u64, ValueQuery>;
 
@@ -263,6 +336,21 @@ pub mod pallet {
     pub type SetWeightCallsPerEpoch<T: Config> =
         StorageDoubleMap<_, Identity, u16, Identity, T::AccountId, u16, ValueQuery>;
 
+    #[pallet::storage] // --- MAP ( netuid ) --> use_weights_commit_reveal
+    pub type UseWeightsCommitReveal<T> = StorageMap<_, Identity, u16, bool, ValueQuery>;
+
+    #[pallet::type_value]
+    pub fn DefaultWeightsRevealWindow<T: Config>() -> u16 {
+        100
+    }
+    #[pallet::storage] // --- MAP ( netuid ) --> weights_reveal_window
+    pub type WeightsRevealWindow<T> =
+        StorageMap<_, Identity, u16, u16, ValueQuery, DefaultWeightsRevealWindow<T>>;
+
+    #[pallet::storage] // --- DMAP ( netuid, module_key ) --> (commit_hash, reveal_block)
+    pub type WeightCommits<T: Config> =
+        StorageDoubleMap<_, Identity, u16, Identity, T::AccountId, (T::Hash, u64)>;
+
     #[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo)]
     #[scale_info(skip_type_params(T))]
     pub struct ModuleParams<T: Config> {
@@ -293,7 +381,10 @@ pub mod pallet {
         // other
         pub adjustment_alpha: u64, // adjustment alpha
         pub unit_emission: u64,    // emission per block
-        pub curator: T::AccountId,
+
+        // subnet 0 curator council, and the approvals its motions need
+        pub curator_council: BTreeSet<T::AccountId>,
+        pub curator_council_threshold: u16,
 
         pub subnet_stake_threshold: Percent,
 
@@ -306,6 +397,33 @@ pub mod pallet {
 
         // founder share
         pub floor_founder_share: u8,
+
+        // delegation fee
+        pub delegation_fee_increase_delay: u16, // epochs until a fee increase takes effect
+        pub max_delegation_fee_increase: Percent, // max fee increase per change
+
+        // emission
+        pub emission_schedule: EmissionSchedule,
+        pub emission_allocation: EmissionAllocation,
+
+        // whitelist
+        pub curator_weight_ratio: Percent, // part of the netuid 0 incentive set by the curator
+
+        // runtime call proposals
+        pub runtime_call_participation_threshold: Percent,
+        pub runtime_call_enactment_delay: u64, // blocks before an accepted call can run
+
+        // blocks between the approval and the execution of a proposal
+        pub proposal_enactment_delay: u64,
+
+        // conviction voting
+        pub conviction_lock_epochs: u64, // epochs the lowest conviction locks the stake for
+
+        // whether curator applications are decided by the curator council or by a vote
+        pub application_vote_mode: VoteMode,
+
+        // blocks finalized proposals are kept for before they are pruned
+        pub proposal_retention: u64,
     }
 
     pub struct DefaultSubnetParams<T: Config>(sp_std::marker::PhantomData<((), T)>);
@@ -332,6 +450,11 @@ pub mod pallet {
                 target_registrations_per_interval: DefaultTargetRegistrationsPerInterval::<T>::get(
                 ),
                 max_registrations_per_interval: 42,
+                unbonding_period: DefaultUnbondingPeriod::<T>::get(),
+                use_weights_commit_reveal: false,
+                weights_reveal_window: DefaultWeightsRevealWindow::<T>::get(),
+                consensus_mode: DefaultConsensusMode::<T>::get(),
+                max_epoch_history: DefaultMaxEpochHistory::<T>::get(),
             }
         }
     }
@@ -366,6 +489,14 @@ pub mod pallet {
         pub target_registrations_interval: u16,
         pub target_registrations_per_interval: u16,
         pub max_registrations_per_interval: u16,
+        // staking
+        pub unbonding_period: u64,
+        // weights
+        pub use_weights_commit_reveal: bool,
+        pub weights_reveal_window: u16, // blocks after the commit epoch ends
+        // consensus
+        pub consensus_mode: ConsensusMode,
+        pub max_epoch_history: u16, // epochs of consensus outputs kept in storage
     }
 
     #[pallet::type_value]
@@ -477,6 +608,20 @@ pub mod pallet {
     #[pallet::storage] // --- MAP ( netuid ) --> epoch
     pub type Tempo<T> = StorageMap<_, Identity, u16, u16, ValueQuery, DefaultTempo<T>>;
 
+    #[pallet::storage] // --- MAP ( netuid ) --> epoch_offset
+    pub type EpochOffset<T> = StorageMap<_, Identity, u16, u16>;
+
+    #[pallet::type_value]
+    pub fn DefaultMaxEpochWeightPerBlock<T: Config>() -> Weight {
+        Perbill::from_percent(25) * T::BlockWeights::get().max_block
+    }
+    #[pallet::storage] // --- ITEM ( max_epoch_weight_per_block )
+    pub type MaxEpochWeightPerBlock<T> =
+        StorageValue<_, Weight, ValueQuery, DefaultMaxEpochWeightPerBlock<T>>;
+
+    #[pallet::storage] // --- ITEM ( deferred_epochs )
+    pub type DeferredEpochs<T> = StorageValue<_, Vec<u16>, ValueQuery>;
+
     #[pallet::storage] // --- MAP ( netuid ) --> epoch
     pub type TrustRatio<T> = StorageMap<_, Identity, u16, u16, ValueQuery>;
 
@@ -485,8 +630,10 @@ pub mod pallet {
     // =======================================
 
     #[pallet::type_value]
-    pub fn DefaultCurator<T: Config>() -> T::AccountId {
-        T::AccountId::decode(&mut sp_runtime::traits::TrailingZeroInput::zeroes()).unwrap()
+    pub fn DefaultCuratorCouncil<T: Config>() -> BTreeSet<T::AccountId> {
+        BTreeSet::from([
+            T::AccountId::decode(&mut sp_runtime::traits::TrailingZeroInput::zeroes()).unwrap(),
+        ])
     }
 
     #[pallet::storage]
@@ -496,6 +643,15 @@ pub mod pallet {
     #[pallet::storage]
     pub type GlobalDaoTreasury<T: Config> = StorageValue<_, u64, ValueQuery>;
 
+    #[pallet::storage] // --- ITEM ( reserved ) | Part of the treasury streams still have to pay out
+    pub type ReservedDaoTreasury<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    #[pallet::storage] // --- MAP ( stream_id ) --> stream
+    pub type TreasuryStreams<T: Config> = StorageMap<_, Identity, u64, TreasuryStream<T>>;
+
+    #[pallet::storage] // --- ITEM ( next_stream_id )
+    pub type NextStreamId<T: Config> = StorageValue<_, u64, ValueQuery>;
+
     #[pallet::type_value]
     pub fn DefaultDaoTreasuryDistribution<T: Config>() -> Percent {
         Percent::from_percent(5u8)
@@ -511,8 +667,21 @@ pub mod pallet {
         8
     }
 
-    #[pallet::storage]
-    pub type Curator<T: Config> = StorageValue<_, T::AccountId, ValueQuery, DefaultCurator<T>>;
+    #[pallet::storage] // --- ITEM ( curator_council ) | Members of the subnet 0 curator council
+    pub type CuratorCouncil<T: Config> =
+        StorageValue<_, BTreeSet<T::AccountId>, ValueQuery, DefaultCuratorCouncil<T>>;
+
+    #[pallet::type_value]
+    pub fn DefaultCuratorCouncilThreshold<T: Config>() -> u16 {
+        1
+    }
+
+    #[pallet::storage] // --- ITEM ( curator_council_threshold ) | Approvals a motion needs
+    pub type CuratorCouncilThreshold<T: Config> =
+        StorageValue<_, u16, ValueQuery, DefaultCuratorCouncilThreshold<T>>;
+
+    #[pallet::storage] // --- MAP ( motion_hash ) --> motion | Curator actions being approved
+    pub type CuratorMotions<T: Config> = StorageMap<_, Identity, T::Hash, CuratorMotion<T>>;
 
     // VOTING MODE
     #[pallet::type_value]
@@ -524,6 +693,14 @@ pub mod pallet {
     pub type VoteModeSubnet<T> =
         StorageMap<_, Identity, u16, VoteMode, ValueQuery, DefaultVoteMode<T>>;
 
+    #[pallet::type_value]
+    pub fn DefaultConsensusMode<T: Config>() -> ConsensusMode {
+        ConsensusMode::Yuma
+    }
+    #[pallet::storage] // --- MAP ( netuid ) --> consensus_mode
+    pub type ConsensusModeSubnet<T> =
+        StorageMap<_, Identity, u16, ConsensusMode, ValueQuery, DefaultConsensusMode<T>>;
+
     #[pallet::storage] // --- ITEM( tota_number_of_existing_networks )
     pub type TotalSubnets<T> = StorageValue<_, u16, ValueQuery>;
 
@@ -583,6 +760,18 @@ pub mod pallet {
         DefaultDelegationFee<T>,
     >;
 
+    #[pallet::storage] // -- DMAP(netuid, module_key) -> (delegation_fee, activation_block)
+    pub type PendingDelegationFee<T: Config> =
+        StorageDoubleMap<_, Identity, u16, Blake2_128Concat, T::AccountId, (Percent, u64)>;
+
+    #[pallet::storage] // -- DMAP(netuid, module_key) -> controller
+    pub type Controller<T: Config> =
+        StorageDoubleMap<_, Identity, u16, Blake2_128Concat, T::AccountId, T::AccountId>;
+
+    #[pallet::storage] // -- DMAP(netuid, controller) -> module_key
+    pub type ControlledModule<T: Config> =
+        StorageDoubleMap<_, Identity, u16, Blake2_128Concat, T::AccountId, T::AccountId>;
+
     // STATE OF THE MODULE
     #[pallet::storage] // --- DMAP ( netuid, uid ) --> block number that the module is registered
     pub type RegistrationBlock<T: Config> =
@@ -619,6 +808,32 @@ pub mod pallet {
         ValueQuery,
     >;
 
+    #[pallet::storage] // --- MAP ( netuid ) --> delegations | Number of entries in the `StakeFrom` maps of
+                       // the subnet
+    pub type DelegationCount<T> = StorageMap<_, Identity, u16, u32, ValueQuery>;
+
+    #[pallet::storage] // --- DMAP ( netuid, module_key ) --> reward_per_share | Delegation rewards accrued
+                       // per unit of delegated stake, scaled by `REWARD_PER_SHARE_PRECISION`
+    pub type DelegationRewardPerShare<T: Config> =
+        StorageDoubleMap<_, Identity, u16, Identity, T::AccountId, u128, ValueQuery>;
+
+    #[pallet::storage] // --- DMAP ( netuid, account_id ) --> Vec<(module_key, reward_per_share )> | The reward per
+                       // share of each module the account was last settled at
+    pub type DelegationRewardCheckpoints<T: Config> = StorageDoubleMap<
+        _,
+        Identity,
+        u16,
+        Identity,
+        T::AccountId,
+        BTreeMap<T::AccountId, u128>,
+        ValueQuery,
+    >;
+
+    #[pallet::storage] // --- DMAP ( netuid, module_key ) --> unsettled_rewards | Delegation rewards in the
+                       // stake of the module that were not settled into a delegation yet
+    pub type UnsettledDelegationRewards<T: Config> =
+        StorageDoubleMap<_, Identity, u16, Identity, T::AccountId, u64, ValueQuery>;
+
     #[pallet::storage] // --- MAP( netuid ) --> lowest_subnet
     pub type SubnetGaps<T> = StorageValue<_, BTreeSet<u16>, ValueQuery>;
 
@@ -626,6 +841,29 @@ pub mod pallet {
     #[pallet::storage] // --- MAP ( netuid ) --> subnet_total_stake
     pub type TotalStake<T> = StorageMap<_, Identity, u16, u64, ValueQuery>;
 
+    #[pallet::type_value]
+    pub fn DefaultUnbondingPeriod<T: Config>() -> u64 {
+        0 // stake is released immediately
+    }
+    #[pallet::storage] // --- MAP ( netuid ) --> unbonding_period
+    pub type UnbondingPeriod<T> =
+        StorageMap<_, Identity, u16, u64, ValueQuery, DefaultUnbondingPeriod<T>>;
+
+    #[pallet::storage] // --- MAP ( account_id ) --> Vec<UnlockChunk> | Stake waiting to be withdrawn
+    pub type UnlockingQueue<T: Config> =
+        StorageMap<_, Identity, T::AccountId, UnlockingChunks<T>, ValueQuery>;
+
+    #[pallet::storage] // --- DMAP ( netuid, module_key ) --> unbonding stake of each account
+    pub type UnbondingFrom<T: Config> = StorageDoubleMap<
+        _,
+        Identity,
+        u16,
+        Identity,
+        T::AccountId,
+        BTreeMap<T::AccountId, u64>,
+        ValueQuery,
+    >;
+
     // PROFIT SHARE VARIABLES
     #[pallet::storage] // --- DMAP ( netuid, account_id ) --> Vec<(module_key, stake )> | Returns the list of the
     pub type ProfitShares<T: Config> =
@@ -650,6 +888,19 @@ pub mod pallet {
     pub type Dividends<T: Config> = StorageMap<_, Identity, u16, Vec<u16>, ValueQuery>;
     #[pallet::storage] // --- MAP ( netuid ) --> emission
     pub type Emission<T: Config> = StorageMap<_, Identity, u16, Vec<u64>, ValueQuery>;
+
+    #[pallet::type_value]
+    pub fn DefaultMaxEpochHistory<T: Config>() -> u16 {
+        10
+    }
+    #[pallet::storage] // --- MAP ( netuid ) --> max_epoch_history
+    pub type MaxEpochHistory<T> =
+        StorageMap<_, Identity, u16, u16, ValueQuery, DefaultMaxEpochHistory<T>>;
+    #[pallet::storage] // --- MAP ( netuid ) --> epochs_recorded
+    pub type EpochsRecorded<T> = StorageMap<_, Identity, u16, u64, ValueQuery>;
+    #[pallet::storage] // --- DMAP ( netuid, epoch ) --> epoch_record
+    pub type EpochHistory<T: Config> =
+        StorageDoubleMap<_, Identity, u16, Identity, u64, EpochRecord<T>>;
     #[pallet::storage] // --- MAP ( netuid ) --> last_update
     pub type LastUpdate<T: Config> = StorageMap<_, Identity, u16, Vec<u64>, ValueQuery>;
 
@@ -661,6 +912,10 @@ pub mod pallet {
     #[pallet::storage]
     pub type LegitWhitelist<T: Config> = StorageMap<_, Identity, T::AccountId, u8, ValueQuery>;
 
+    // part of the netuid 0 incentive following the weights recommended in the whitelist
+    #[pallet::storage] // --- ITEM ( curator_weight_ratio )
+    pub type CuratorWeightRatio<T> = StorageValue<_, Percent, ValueQuery>;
+
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
@@ -715,6 +970,44 @@ pub mod pallet {
         GlobalProposalAccepted(u64), // (id)
         CustomProposalAccepted(u64), // (id)
         SubnetProposalAccepted(u64, u16), // (id, netuid)
+        RuntimeCallProposalAccepted(u64), // (id)
+        DaoApplicationAccepted(u64), // (application_id)
+        TreasuryStreamStarted(u64, T::AccountId, u64), // (stream_id, dest, value)
+        TreasuryStreamReleased(u64, u64), // (stream_id, amount)
+        TreasuryStreamCancelled(u64, u64), // (stream_id, unreleased amount)
+        ProposalScheduled(u64, u64), // (id, enactment_block)
+        ProposalPruned(u64),      // (id)
+        ProposalCancelled(u64, u64), // (id, refunded cost)
+        ProposalAmended(u64),     // (id)
+        StakeLocked(T::AccountId, u64, u64), // (voter, amount, unlock_block)
+        VoteDelegated(T::AccountId, T::AccountId, Option<u16>), // (delegator, delegatee, netuid)
+        VoteUndelegated(T::AccountId, Option<u16>), // (delegator, netuid)
+        CuratorMotionApproved(T::Hash, T::AccountId), // (motion_hash, curator)
+        CuratorMotionExecuted(T::Hash), // (motion_hash)
+
+        // staking
+        StakeUnbonding(T::AccountId, T::AccountId, u64, u64), /* (key, module_key, amount,
+                                                               * unlock_block) */
+        StakeWithdrawn(T::AccountId, u64), // (key, amount)
+        // (key, from_netuid, from_module_key, to_netuid, to_module_key, amount)
+        StakeTransferredAcrossSubnets(T::AccountId, u16, T::AccountId, u16, T::AccountId, u64),
+        DelegationRewardsClaimed(u16, T::AccountId, T::AccountId, u64), /* (netuid, key,
+                                                                         * module_key, amount) */
+
+        // delegation fee
+        // (netuid, module_key, delegation_fee, activation_block)
+        DelegationFeeIncreaseAnnounced(u16, T::AccountId, Percent, u64),
+        DelegationFeeIncreaseActivated(u16, T::AccountId, Percent), // (netuid, module_key, fee)
+
+        // controller
+        ControllerSet(u16, T::AccountId, T::AccountId), // (netuid, module_key, controller)
+        ControllerCleared(u16, T::AccountId),           // (netuid, module_key)
+
+        ModuleKeySwapped(u16, T::AccountId, T::AccountId), // (netuid, old_key, new_key)
+
+        // commit-reveal
+        WeightsCommitted(u16, u16),             // (netuid, uid)
+        WeightCommitExpired(u16, T::AccountId), // (netuid, module_key)
     }
 
     // Errors inform users that something went wrong.
@@ -781,8 +1074,8 @@ pub mod pallet {
         KeyAlreadyRegistered,
         EmptyKeys,
         TooManyKeys,
-        NotCurator, /* --- Thrown when the user tries to set the curator and is not the
-                     * curator */
+        NotCurator, /* --- Thrown when the user tries to act as a curator and is not a
+                     * member of the curator council */
         ApplicationNotFound,
         AlreadyWhitelisted, /* --- Thrown when the user tries to whitelist an account that is
                              * already whitelisted. */
@@ -876,6 +1169,107 @@ pub mod pallet {
 
         MaximumSetWeightsPerEpochReached,
         InsufficientDaoTreasuryFunds,
+
+        // Staking
+        InvalidUnbondingPeriod,
+        /// There is no unbonded stake ready to be withdrawn.
+        NothingToWithdraw,
+        /// The key is unbonding too many chunks of stake, it has to withdraw some first.
+        TooManyUnlockingChunks,
+        /// Stake can only be moved across different subnets.
+        TransferToSameSubnet,
+        /// The destination subnet unbonds stake faster than the source subnet.
+        UnbondingPeriodTooShort,
+        /// The key has no delegation rewards pending on the module.
+        NoDelegationRewards,
+
+        // Delegation fee
+        /// The delegation fee increase is larger than the allowed increase per change.
+        DelegationFeeIncreaseTooLarge,
+        InvalidMaxDelegationFeeIncrease,
+
+        // Controller
+        /// The controller can not be the module key itself or a module registered on the subnet.
+        InvalidController,
+        /// The account already controls a module on this subnet.
+        ControllerAlreadyInUse,
+        /// The module has no controller set.
+        ControllerNotSet,
+
+        // Commit-reveal
+        /// Weights on this subnet have to be committed and revealed.
+        WeightsCommitRevealEnabled,
+        /// This subnet does not use commit-reveal for weights.
+        WeightsCommitRevealDisabled,
+        InvalidWeightsRevealWindow,
+        /// There is no weight commit for this module.
+        NoWeightCommit,
+        /// The weights can only be revealed after the epoch of the commit has ended.
+        RevealTooEarly,
+        /// The reveal window of the commit is over.
+        RevealTooLate,
+        /// The revealed weights do not match the commit.
+        InvalidReveal,
+
+        // Epoch history
+        /// The epoch history can keep at most 100 epochs.
+        InvalidMaxEpochHistory,
+
+        // Emission
+        /// The emission curve needs a non zero interval, and a decay rate between 0 and 100%.
+        InvalidEmissionCurve,
+        /// The max supply is lower than the amount already issued.
+        InvalidMaxSupply,
+        /// The capped share has to be above 0%, and shares and floors at most 100%.
+        InvalidEmissionAllocation,
+
+        // Runtime call proposals
+        /// The call is not available as a preimage, or can not be decoded.
+        InvalidRuntimeCall,
+        /// The call weighs more than `MAX_RUNTIME_CALL_WEIGHT` of a block.
+        RuntimeCallTooHeavy,
+        /// Runtime calls need at least the participation of other proposals, and at most 100%.
+        InvalidRuntimeCallParticipationThreshold,
+        /// Accepted runtime calls have to wait at least one block.
+        InvalidRuntimeCallEnactmentDelay,
+
+        // Enactment
+        /// Only approved proposals waiting for their enactment can be fast-tracked.
+        ProposalNotApproved,
+        /// Runtime calls always wait for their enactment delay.
+        CannotFastTrackRuntimeCall,
+        /// Finalized proposals have to be kept for at least one block.
+        InvalidProposalRetention,
+
+        // Proposal cancellation and amendment
+        /// Only the proposer can cancel or amend a proposal.
+        NotProposer,
+        /// The data can not be proposed directly, or the proposal can not be changed.
+        InvalidProposalData,
+
+        // Conviction voting
+        /// The stake is locked by a conviction vote until the lock expires.
+        StakeIsLocked,
+        /// Convictions have to lock the stake for at least one epoch.
+        InvalidConvictionLockEpochs,
+
+        // Curator council
+        /// The council needs members, and a threshold between 1 and its size.
+        InvalidCuratorCouncil,
+        /// The curator already approved this motion.
+        AlreadyApprovedMotion,
+
+        // Treasury streams
+        /// Streams need a value, and a schedule that releases all of it.
+        InvalidTreasuryStream,
+        /// The treasury stream does not exist, or was paid out already.
+        TreasuryStreamNotFound,
+
+        // Vote delegation
+        /// Accounts can not delegate their vote to themselves.
+        CannotDelegateVoteToSelf,
+        /// The account did not delegate its vote on this scope.
+        VoteNotDelegated,
     }
 
     // ==================
@@ -986,12 +1380,104 @@ pub mod pallet {
     pub type GeneralSubnetApplicationCost<T: Config> =
         StorageValue<_, u64, ValueQuery, DefaultGeneralSubnetApplicationCost<T>>;
 
+    #[pallet::type_value]
+    pub fn DefaultRuntimeCallParticipationThreshold<T: Config>() -> Percent {
+        Percent::from_percent(75)
+    }
+
+    #[pallet::storage]
+    pub type RuntimeCallParticipationThreshold<T: Config> =
+        StorageValue<_, Percent, ValueQuery, DefaultRuntimeCallParticipationThreshold<T>>;
+
+    #[pallet::type_value]
+    pub fn DefaultRuntimeCallEnactmentDelay<T: Config>() -> u64 {
+        10_800 // Aprox 1 day
+    }
+
+    #[pallet::storage]
+    pub type RuntimeCallEnactmentDelay<T: Config> =
+        StorageValue<_, u64, ValueQuery, DefaultRuntimeCallEnactmentDelay<T>>;
+
+    #[pallet::type_value]
+    pub fn DefaultProposalEnactmentDelay<T: Config>() -> u64 {
+        10_800 // Aprox 1 day
+    }
+
+    #[pallet::storage]
+    pub type ProposalEnactmentDelay<T: Config> =
+        StorageValue<_, u64, ValueQuery, DefaultProposalEnactmentDelay<T>>;
+
+    #[pallet::storage] // --- MAP ( block ) --> proposal_ids
+    pub type EnactmentQueue<T: Config> = StorageMap<_, Identity, u64, Vec<u64>, ValueQuery>;
+
+    #[pallet::type_value]
+    pub fn DefaultConvictionLockEpochs<T: Config>() -> u64 {
+        108 // Aprox 1 day with the default tempo
+    }
+
+    #[pallet::storage]
+    pub type ConvictionLockEpochs<T: Config> =
+        StorageValue<_, u64, ValueQuery, DefaultConvictionLockEpochs<T>>;
+
+    #[pallet::storage] // --- DMAP ( proposal_id, voter ) --> conviction
+    pub type VoteConvictions<T: Config> =
+        StorageDoubleMap<_, Identity, u64, Identity, T::AccountId, Conviction, ValueQuery>;
+
+    #[pallet::storage] // --- MAP ( account_id ) --> Vec<StakeLock> | Stake locked by conviction votes
+    pub type StakeLocks<T: Config> =
+        StorageMap<_, Identity, T::AccountId, Vec<StakeLock>, ValueQuery>;
+
+    #[pallet::storage] // --- DMAP ( delegator, netuid ) --> delegatee | None is the whole network
+    pub type VoteDelegations<T: Config> =
+        StorageDoubleMap<_, Identity, T::AccountId, Identity, Option<u16>, T::AccountId>;
+
+    #[pallet::storage] // --- DMAP ( delegatee, netuid ) --> delegators | Reverse of VoteDelegations
+    pub type VoteDelegators<T: Config> = StorageDoubleMap<
+        _,
+        Identity,
+        T::AccountId,
+        Identity,
+        Option<u16>,
+        BTreeSet<T::AccountId>,
+        ValueQuery,
+    >;
+
     #[pallet::storage]
     pub type Proposals<T: Config> = StorageMap<_, Identity, u64, Proposal<T>>;
 
+    #[pallet::storage] // --- ITEM ( next_proposal_id )
+    pub type NextProposalId<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    #[pallet::storage] // --- ITEM ( proposal_ids ) | Proposals still open to votes
+    pub type ActiveProposals<T: Config> = StorageValue<_, BTreeSet<u64>, ValueQuery>;
+
+    #[pallet::type_value]
+    pub fn DefaultProposalRetention<T: Config>() -> u64 {
+        324_000 // Aprox 30 days
+    }
+
+    #[pallet::storage] // --- ITEM ( proposal_retention ) | Blocks finalized proposals are kept for
+    pub type ProposalRetention<T: Config> =
+        StorageValue<_, u64, ValueQuery, DefaultProposalRetention<T>>;
+
+    #[pallet::storage] // --- MAP ( block ) --> proposal_ids | Finalized proposals to prune
+    pub type ProposalPruningQueue<T: Config> = StorageMap<_, Identity, u64, Vec<u64>, ValueQuery>;
+
     #[pallet::storage]
     pub type CuratorApplications<T: Config> = StorageMap<_, Identity, u64, CuratorApplication<T>>;
 
+    #[pallet::storage] // --- ITEM ( next_application_id )
+    pub type NextApplicationId<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    #[pallet::type_value]
+    pub fn DefaultApplicationVoteMode<T: Config>() -> VoteMode {
+        VoteMode::Authority
+    }
+
+    #[pallet::storage] // --- ITEM ( application_vote_mode ) | Authority leaves it to the council
+    pub type ApplicationVoteMode<T: Config> =
+        StorageValue<_, VoteMode, ValueQuery, DefaultApplicationVoteMode<T>>;
+
     // ================
     // ==== Hooks =====
     // ================
@@ -1001,9 +1487,7 @@ pub mod pallet {
         /// ---- Called on the initialization of this pallet. (the order of on_finalize calls is
         /// determined in the runtime)
         fn on_initialize(_block_number: BlockNumberFor<T>) -> Weight {
-            Self::block_step();
-
-            Weight::zero()
+            Self::block_step()
         }
     }
 
@@ -1022,6 +1506,26 @@ pub mod pallet {
             Self::do_set_weights(origin, netuid, uids, weights)
         }
 
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn commit_weights(
+            origin: OriginFor<T>,
+            netuid: u16,
+            commit_hash: T::Hash,
+        ) -> DispatchResult {
+            Self::do_commit_weights(origin, netuid, commit_hash)
+        }
+
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn reveal_weights(
+            origin: OriginFor<T>,
+            netuid: u16,
+            uids: Vec<u16>,
+            weights: Vec<u16>,
+            salt: Vec<u8>,
+        ) -> DispatchResult {
+            Self::do_reveal_weights(origin, netuid, uids, weights, salt)
+        }
+
         #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
         pub fn add_stake(
             origin: OriginFor<T>,
@@ -1063,6 +1567,20 @@ pub mod pallet {
             Self::do_remove_stake_multiple(origin, netuid, module_keys, amounts)
         }
 
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn withdraw_unbonded(origin: OriginFor<T>) -> DispatchResult {
+            Self::do_withdraw_unbonded(origin)
+        }
+
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn claim_delegation_rewards(
+            origin: OriginFor<T>,
+            netuid: u16,
+            module_key: T::AccountId,
+        ) -> DispatchResult {
+            Self::do_claim_delegation_rewards(origin, netuid, module_key)
+        }
+
         #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
         pub fn transfer_stake(
             origin: OriginFor<T>,         // --- The account that is calling this function.
@@ -1074,6 +1592,25 @@ pub mod pallet {
             Self::do_transfer_stake(origin, netuid, module_key, new_module_key, amount)
         }
 
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn transfer_stake_across_subnets(
+            origin: OriginFor<T>,          // --- The account that is calling this function.
+            from_netuid: u16,              // --- The network id the stake is moved from.
+            from_module_key: T::AccountId, // --- The module key the stake is moved from.
+            to_netuid: u16,                // --- The network id the stake is moved to.
+            to_module_key: T::AccountId,   // --- The module key the stake is moved to.
+            amount: u64,                   // --- The amount of stake to transfer.
+        ) -> DispatchResult {
+            Self::do_transfer_stake_across_subnets(
+                origin,
+                from_netuid,
+                from_module_key,
+                to_netuid,
+                to_module_key,
+                amount,
+            )
+        }
+
         #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
         pub fn transfer_multiple(
             origin: OriginFor<T>, // --- The account that is calling this function.
@@ -1092,7 +1629,8 @@ pub mod pallet {
             delegation_fee: Option<Percent>,
             metadata: Option<Vec<u8>>,
         ) -> DispatchResult {
-            let key = ensure_signed(origin.clone())?;
+            let caller = ensure_signed(origin.clone())?;
+            let key = Self::get_module_key_for_caller(netuid, caller);
             ensure!(Self::is_registered(netuid, &key), Error::<T>::NotRegistered);
 
             let params = Self::module_params(netuid, &key);
@@ -1102,6 +1640,29 @@ pub mod pallet {
             Self::do_update_module(origin, netuid, changeset)
         }
 
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn set_controller(
+            origin: OriginFor<T>,
+            netuid: u16,
+            controller: T::AccountId,
+        ) -> DispatchResult {
+            Self::do_set_controller(origin, netuid, controller)
+        }
+
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn clear_controller(origin: OriginFor<T>, netuid: u16) -> DispatchResult {
+            Self::do_clear_controller(origin, netuid)
+        }
+
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn swap_module_key(
+            origin: OriginFor<T>,
+            netuid: u16,
+            new_key: T::AccountId,
+        ) -> DispatchResult {
+            Self::do_swap_module_key(origin, netuid, new_key)
+        }
+
         #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
         pub fn register(
             origin: OriginFor<T>,
@@ -1169,6 +1730,11 @@ pub mod pallet {
             target_registrations_interval: u16,
             target_registrations_per_interval: u16,
             max_registrations_per_interval: u16,
+            unbonding_period: u64,
+            use_weights_commit_reveal: bool,
+            weights_reveal_window: u16,
+            consensus_mode: ConsensusMode,
+            max_epoch_history: u16,
         ) -> DispatchResult {
             let params = SubnetParams {
                 founder,
@@ -1189,6 +1755,11 @@ pub mod pallet {
                 target_registrations_interval,
                 target_registrations_per_interval,
                 max_registrations_per_interval,
+                unbonding_period,
+                use_weights_commit_reveal,
+                weights_reveal_window,
+                consensus_mode,
+                max_epoch_history,
             };
 
             let changeset = SubnetChangeset::update(netuid, params)?;
@@ -1212,7 +1783,8 @@ pub mod pallet {
             min_weight_stake: u64,            // min weight stake required
             adjustment_alpha: u64,            // adjustment alpha
             unit_emission: u64,               // emission per block
-            curator: T::AccountId,            // subnet 0 dao multisig
+            curator_council: BTreeSet<T::AccountId>, // members of the subnet 0 curator council
+            curator_council_threshold: u16,   // approvals a curator motion needs
             subnet_stake_threshold: Percent,  // stake needed to start subnet emission
             proposal_cost: u64,               /*amount of $COMAI to create a proposal
                                                * returned if proposal gets accepted */
@@ -1221,6 +1793,17 @@ pub mod pallet {
                                        * stake,
                                        *  in order for proposal to get executed */
             general_subnet_application_cost: u64,
+            delegation_fee_increase_delay: u16, // epochs until a fee increase takes effect
+            max_delegation_fee_increase: Percent, // max fee increase per change
+            emission_schedule: EmissionSchedule, // emission curve and max supply
+            emission_allocation: EmissionAllocation, // how emission is split between subnets
+            curator_weight_ratio: Percent,      // curator share of the netuid 0 incentive
+            runtime_call_participation_threshold: Percent, // stake needed to run a call
+            runtime_call_enactment_delay: u64,  // blocks before an accepted call can run
+            proposal_enactment_delay: u64,      // blocks before an approved proposal runs
+            conviction_lock_epochs: u64,        // epochs the lowest conviction locks the stake for
+            application_vote_mode: VoteMode,    // who decides on curator applications
+            proposal_retention: u64,            // blocks finalized proposals are kept for
         ) -> DispatchResult {
             let mut params = Self::global_params();
             params.max_name_length = max_name_length;
@@ -1236,12 +1819,24 @@ pub mod pallet {
             params.min_weight_stake = min_weight_stake;
             params.adjustment_alpha = adjustment_alpha;
             params.unit_emission = unit_emission;
-            params.curator = curator;
+            params.curator_council = curator_council;
+            params.curator_council_threshold = curator_council_threshold;
             params.subnet_stake_threshold = subnet_stake_threshold;
             params.proposal_cost = proposal_cost;
             params.proposal_expiration = proposal_expiration;
             params.proposal_participation_threshold = proposal_participation_threshold;
             params.general_subnet_application_cost = general_subnet_application_cost;
+            params.delegation_fee_increase_delay = delegation_fee_increase_delay;
+            params.max_delegation_fee_increase = max_delegation_fee_increase;
+            params.emission_schedule = emission_schedule;
+            params.emission_allocation = emission_allocation;
+            params.curator_weight_ratio = curator_weight_ratio;
+            params.runtime_call_participation_threshold = runtime_call_participation_threshold;
+            params.runtime_call_enactment_delay = runtime_call_enactment_delay;
+            params.proposal_enactment_delay = proposal_enactment_delay;
+            params.conviction_lock_epochs = conviction_lock_epochs;
+            params.application_vote_mode = application_vote_mode;
+            params.proposal_retention = proposal_retention;
             Self::do_add_global_proposal(origin, params)
         }
 
@@ -1270,6 +1865,11 @@ pub mod pallet {
             target_registrations_interval: u16,
             target_registrations_per_interval: u16,
             max_registrations_per_interval: u16,
+            unbonding_period: u64,
+            use_weights_commit_reveal: bool,
+            weights_reveal_window: u16,
+            consensus_mode: ConsensusMode,
+            max_epoch_history: u16,
         ) -> DispatchResult {
             let mut params = Self::subnet_params(netuid);
             params.founder = founder;
@@ -1290,6 +1890,11 @@ pub mod pallet {
             params.target_registrations_interval = target_registrations_interval;
             params.target_registrations_per_interval = target_registrations_per_interval;
             params.max_registrations_per_interval = max_registrations_per_interval;
+            params.unbonding_period = unbonding_period;
+            params.use_weights_commit_reveal = use_weights_commit_reveal;
+            params.weights_reveal_window = weights_reveal_window;
+            params.consensus_mode = consensus_mode;
+            params.max_epoch_history = max_epoch_history;
             Self::do_add_subnet_proposal(origin, netuid, params)
         }
 
@@ -1332,19 +1937,89 @@ pub mod pallet {
             Self::do_add_transfer_dao_treasury_proposal(origin, data, value, dest)
         }
 
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn add_treasury_stream_proposal(
+            origin: OriginFor<T>,
+            data: Vec<u8>,
+            value: u64,
+            dest: T::AccountId,
+            schedule: StreamSchedule,
+        ) -> DispatchResult {
+            Self::do_add_treasury_stream_proposal(origin, data, value, dest, schedule)
+        }
+
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn add_cancel_treasury_stream_proposal(
+            origin: OriginFor<T>,
+            stream_id: u64,
+        ) -> DispatchResult {
+            Self::do_add_cancel_treasury_stream_proposal(origin, stream_id)
+        }
+
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn add_runtime_call_proposal(
+            origin: OriginFor<T>,
+            call: BoundedCallOf<T>, // inline, or the hash of a noted preimage
+        ) -> DispatchResult {
+            Self::do_add_runtime_call_proposal(origin, call)
+        }
+
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn fast_track_proposal(origin: OriginFor<T>, proposal_id: u64) -> DispatchResult {
+            Self::do_fast_track_proposal(origin, proposal_id)
+        }
+
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn cancel_proposal(origin: OriginFor<T>, proposal_id: u64) -> DispatchResult {
+            Self::do_cancel_proposal(origin, proposal_id)
+        }
+
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn amend_proposal(
+            origin: OriginFor<T>,
+            proposal_id: u64,
+            data: ProposalData<T>, // replaces the data, votes are reset
+        ) -> DispatchResult {
+            Self::do_amend_proposal(origin, proposal_id, data)
+        }
+
         #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
         pub fn vote_proposal(
             origin: OriginFor<T>,
             proposal_id: u64,
             agree: bool,
         ) -> DispatchResult {
-            Self::do_vote_proposal(origin, proposal_id, agree)
+            Self::do_vote_proposal(origin, proposal_id, agree, Conviction::None)
+        }
+
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn vote_proposal_with_conviction(
+            origin: OriginFor<T>,
+            proposal_id: u64,
+            agree: bool,
+            conviction: Conviction,
+        ) -> DispatchResult {
+            Self::do_vote_proposal(origin, proposal_id, agree, conviction)
         }
 
         #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
         pub fn unvote_proposal(origin: OriginFor<T>, proposal_id: u64) -> DispatchResult {
             Self::do_unregister_vote(origin, proposal_id)
         }
+
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn delegate_vote(
+            origin: OriginFor<T>,
+            delegatee: T::AccountId,
+            netuid: Option<u16>, // None delegates on the whole network
+        ) -> DispatchResult {
+            Self::do_delegate_vote(origin, delegatee, netuid)
+        }
+
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn undelegate_vote(origin: OriginFor<T>, netuid: Option<u16>) -> DispatchResult {
+            Self::do_undelegate_vote(origin, netuid)
+        }
     }
 
     // ---- Subspace helper functions.
@@ -1395,7 +2070,8 @@ pub struct SubspaceSignedExtension<T: Config + Send + Sync + TypeInfo>(pub Phant
 
 impl<T: Config + Send + Sync + TypeInfo> Default for SubspaceSignedExtension<T>
 where
-    T::RuntimeCall: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+    <T as frame_system::Config>::RuntimeCall:
+        Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
     <T as frame_system::Config>::RuntimeCall: IsSubType<Call<T>>,
 {
     fn default() -> Self {
@@ -1405,7 +2081,8 @@ where
 
 impl<T: Config + Send + Sync + TypeInfo> SubspaceSignedExtension<T>
 where
-    T::RuntimeCall: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+    <T as frame_system::Config>::RuntimeCall:
+        Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
     <T as frame_system::Config>::RuntimeCall: IsSubType<Call<T>>,
 {
     pub fn new() -> Self {
@@ -1447,13 +2124,14 @@ impl<T: Config + Send + Sync + TypeInfo> sp_std::fmt::Debug for SubspaceSignedEx
 
 impl<T: Config + Send + Sync + TypeInfo> SignedExtension for SubspaceSignedExtension<T>
 where
-    T::RuntimeCall: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+    <T as frame_system::Config>::RuntimeCall:
+        Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
     <T as frame_system::Config>::RuntimeCall: IsSubType<Call<T>>,
 {
     const IDENTIFIER: &'static str = "SubspaceSignedExtension";
 
     type AccountId = T::AccountId;
-    type Call = T::RuntimeCall;
+    type Call = <T as frame_system::Config>::RuntimeCall;
     type AdditionalSigned = ();
     type Pre = (CallType, u64, Self::AccountId);
 
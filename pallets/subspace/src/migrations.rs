@@ -142,3 +142,508 @@ pub mod v10 {
         }
     }
 }
+
+pub mod v11 {
+    use super::*;
+    use crate::subnet::ConsensusMode;
+
+    pub struct MigrateToV11<T>(sp_std::marker::PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV11<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let on_chain_version = StorageVersion::get::<Pallet<T>>();
+
+            if on_chain_version != 10 {
+                log::info!("Storage v11 already updated");
+                return Weight::zero();
+            }
+
+            // Subnet 0 used to be hardcoded to linear rewards, every other subnet ran yuma.
+            // Make the choice explicit, so it can be changed through governance.
+            let mut writes = 1;
+            for netuid in N::<T>::iter_keys() {
+                let consensus_mode = if netuid == 0 {
+                    ConsensusMode::Linear
+                } else {
+                    ConsensusMode::Yuma
+                };
+                ConsensusModeSubnet::<T>::insert(netuid, consensus_mode);
+                writes += 1;
+            }
+            log::info!(
+                "Migrated consensus modes to V11: {:?}",
+                ConsensusModeSubnet::<T>::iter().collect::<Vec<_>>()
+            );
+
+            StorageVersion::new(11).put::<Pallet<T>>();
+
+            T::DbWeight::get().writes(writes)
+        }
+    }
+}
+
+pub mod v12 {
+    use super::*;
+
+    pub struct MigrateToV12<T>(sp_std::marker::PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV12<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let on_chain_version = StorageVersion::get::<Pallet<T>>();
+
+            if on_chain_version != 11 {
+                log::info!("Storage v12 already updated");
+                return Weight::zero();
+            }
+
+            // Delegation rewards are accrued per share from now on. The rewards paid until now
+            // already are part of the stake, so every existing delegation starts out settled.
+            let mut reads = 0;
+            let mut writes = 1;
+            for (netuid, key, stake_to) in StakeTo::<T>::iter() {
+                reads += 1;
+
+                let checkpoints: BTreeMap<_, _> = stake_to
+                    .into_keys()
+                    .filter(|module_key| *module_key != key)
+                    .map(|module_key| {
+                        let reward_per_share =
+                            DelegationRewardPerShare::<T>::get(netuid, &module_key);
+                        (module_key, reward_per_share)
+                    })
+                    .collect();
+                if !checkpoints.is_empty() {
+                    DelegationRewardCheckpoints::<T>::insert(netuid, &key, checkpoints);
+                    writes += 1;
+                }
+            }
+
+            // The share of the delegators is derived from the module stake, make sure it matches
+            // the delegations it is made of, and that the subnet totals follow. The delegations
+            // are counted for the epoch weight on the way.
+            let mut total_stakes: BTreeMap<u16, u64> = BTreeMap::new();
+            let mut delegation_counts: BTreeMap<u16, u32> = BTreeMap::new();
+            for (netuid, module_key, stake_from) in StakeFrom::<T>::iter() {
+                reads += 1;
+
+                let count = delegation_counts.entry(netuid).or_default();
+                *count = count.saturating_add(stake_from.len() as u32);
+
+                let stake: u64 = stake_from.into_values().sum();
+                if Stake::<T>::get(netuid, &module_key) != stake {
+                    log::warn!("fixing stake of {module_key:?} on subnet {netuid} to {stake}");
+                    Stake::<T>::insert(netuid, &module_key, stake);
+                    writes += 1;
+                }
+
+                let total = total_stakes.entry(netuid).or_default();
+                *total = total.saturating_add(stake);
+            }
+
+            // subnets without any delegation left have no stake either
+            for netuid in TotalStake::<T>::iter_keys() {
+                total_stakes.entry(netuid).or_default();
+            }
+
+            for (netuid, total_stake) in total_stakes {
+                reads += 1;
+
+                if TotalStake::<T>::get(netuid) != total_stake {
+                    log::warn!("fixing total stake of subnet {netuid} to {total_stake}");
+                    TotalStake::<T>::insert(netuid, total_stake);
+                    writes += 1;
+                }
+            }
+
+            for (netuid, count) in delegation_counts {
+                DelegationCount::<T>::insert(netuid, count);
+                writes += 1;
+            }
+
+            log::info!("Migrated delegation rewards to V12");
+
+            StorageVersion::new(12).put::<Pallet<T>>();
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+    }
+}
+
+pub mod v13 {
+    use super::*;
+
+    pub struct MigrateToV13<T>(sp_std::marker::PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV13<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let on_chain_version = StorageVersion::get::<Pallet<T>>();
+
+            if on_chain_version != 12 {
+                log::info!("Storage v13 already updated");
+                return Weight::zero();
+            }
+
+            // The emission schedule follows the amount issued, which starts out as everything
+            // that exists: free balances, stake and the emission not distributed yet.
+            let balances = Pallet::<T>::balance_to_u64(T::Currency::total_issuance());
+            let stake = Pallet::<T>::total_stake();
+            let pending: u64 = PendingEmission::<T>::iter_values().sum();
+            let issued = balances.saturating_add(stake).saturating_add(pending);
+            TotalIssued::<T>::put(issued);
+
+            log::info!("Migrated emission schedule to V13, {issued} tokens issued");
+
+            StorageVersion::new(13).put::<Pallet<T>>();
+
+            let subnets = TotalSubnets::<T>::get() as u64;
+            T::DbWeight::get().reads_writes(2 + subnets * 2, 2)
+        }
+    }
+}
+
+pub mod v14 {
+    use super::*;
+
+    pub struct MigrateToV14<T>(sp_std::marker::PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV14<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let on_chain_version = StorageVersion::get::<Pallet<T>>();
+
+            if on_chain_version != 13 {
+                log::info!("Storage v14 already updated");
+                return Weight::zero();
+            }
+
+            // Registration on the general subnet is now restricted to whitelisted modules, so
+            // the ones already registered without being whitelisted are removed. Going from the
+            // last uid down means the module swapped into a removed uid was already checked.
+            // The last module is kept either way, as an empty subnet gets removed.
+            let n = Pallet::<T>::get_subnet_n(0);
+            let mut removed = 0u64;
+            for uid in (0..n).rev() {
+                let key = Keys::<T>::get(0, uid);
+                if !Pallet::<T>::is_in_legit_whitelist(&key) && Pallet::<T>::get_subnet_n(0) > 1 {
+                    Pallet::<T>::remove_module(0, uid);
+                    removed += 1;
+                }
+            }
+
+            log::info!("Migrated general subnet whitelist to V14, {removed} modules removed");
+
+            StorageVersion::new(14).put::<Pallet<T>>();
+
+            T::DbWeight::get().reads_writes(2 + n as u64 * 2, 1 + removed * 30)
+        }
+    }
+}
+
+pub mod v15 {
+    use super::*;
+    use crate::voting::{Proposal, ProposalData, ProposalStatus, VoteMode};
+    use sp_arithmetic::per_things::Percent;
+
+    // The proposal types as they were stored before V15. The params carried by proposals gained
+    // fields since, so they can not be decoded with the current types.
+
+    #[derive(Encode, Decode)]
+    pub struct OldGlobalParams<T: Config> {
+        pub max_name_length: u16,
+        pub min_name_length: u16,
+        pub max_allowed_subnets: u16,
+        pub max_allowed_modules: u16,
+        pub max_registrations_per_block: u16,
+        pub max_allowed_weights: u16,
+        pub min_burn: u64,
+        pub max_burn: u64,
+        pub floor_delegation_fee: Percent,
+        pub min_weight_stake: u64,
+        pub adjustment_alpha: u64,
+        pub unit_emission: u64,
+        pub curator: T::AccountId,
+        pub subnet_stake_threshold: Percent,
+        pub proposal_cost: u64,
+        pub proposal_expiration: u32,
+        pub proposal_participation_threshold: Percent,
+        pub general_subnet_application_cost: u64,
+        pub floor_founder_share: u8,
+    }
+
+    #[derive(Encode, Decode)]
+    pub struct OldSubnetParams<T: Config> {
+        pub founder: T::AccountId,
+        pub founder_share: u16,
+        pub immunity_period: u16,
+        pub incentive_ratio: u16,
+        pub max_allowed_uids: u16,
+        pub max_allowed_weights: u16,
+        pub min_allowed_weights: u16,
+        pub max_weight_age: u64,
+        pub min_stake: u64,
+        pub name: Vec<u8>,
+        pub tempo: u16,
+        pub trust_ratio: u16,
+        pub maximum_set_weight_calls_per_epoch: u16,
+        pub vote_mode: VoteMode,
+        pub bonds_ma: u64,
+        pub target_registrations_interval: u16,
+        pub target_registrations_per_interval: u16,
+        pub max_registrations_per_interval: u16,
+    }
+
+    #[derive(Encode, Decode)]
+    pub enum OldProposalData<T: Config> {
+        Custom(Vec<u8>),
+        GlobalParams(OldGlobalParams<T>),
+        SubnetParams {
+            netuid: u16,
+            params: OldSubnetParams<T>,
+        },
+        SubnetCustom {
+            netuid: u16,
+            data: Vec<u8>,
+        },
+        Expired,
+        TransferDaoTreasury {
+            data: Vec<u8>,
+            value: u64,
+            dest: T::AccountId,
+        },
+    }
+
+    #[derive(Encode, Decode)]
+    pub struct OldProposal<T: Config> {
+        pub id: u64,
+        pub proposer: T::AccountId,
+        pub expiration_block: u64,
+        pub data: OldProposalData<T>,
+        pub status: ProposalStatus,
+        pub votes_for: BTreeSet<T::AccountId>,
+        pub votes_against: BTreeSet<T::AccountId>,
+        pub proposal_cost: u64,
+        pub creation_block: u64,
+        pub finalization_block: Option<u64>,
+    }
+
+    impl<T: Config> OldProposalData<T> {
+        /// Converts the data, the fields added since are filled with their current values. The
+        /// curator becomes the only member of a council acting alone, like `MigrateToV16` does.
+        fn migrate(self) -> ProposalData<T> {
+            match self {
+                Self::Custom(data) => ProposalData::Custom(data),
+                Self::GlobalParams(old) => ProposalData::GlobalParams(GlobalParams {
+                    max_name_length: old.max_name_length,
+                    min_name_length: old.min_name_length,
+                    max_allowed_subnets: old.max_allowed_subnets,
+                    max_allowed_modules: old.max_allowed_modules,
+                    max_registrations_per_block: old.max_registrations_per_block,
+                    max_allowed_weights: old.max_allowed_weights,
+                    min_burn: old.min_burn,
+                    max_burn: old.max_burn,
+                    floor_delegation_fee: old.floor_delegation_fee,
+                    min_weight_stake: old.min_weight_stake,
+                    adjustment_alpha: old.adjustment_alpha,
+                    unit_emission: old.unit_emission,
+                    curator_council: BTreeSet::from([old.curator]),
+                    curator_council_threshold: 1,
+                    subnet_stake_threshold: old.subnet_stake_threshold,
+                    proposal_cost: old.proposal_cost,
+                    proposal_expiration: old.proposal_expiration,
+                    proposal_participation_threshold: old.proposal_participation_threshold,
+                    general_subnet_application_cost: old.general_subnet_application_cost,
+                    floor_founder_share: old.floor_founder_share,
+                    ..Pallet::<T>::global_params()
+                }),
+                Self::SubnetParams {
+                    netuid,
+                    params: old,
+                } => ProposalData::SubnetParams {
+                    netuid,
+                    params: SubnetParams {
+                        founder: old.founder,
+                        founder_share: old.founder_share,
+                        immunity_period: old.immunity_period,
+                        incentive_ratio: old.incentive_ratio,
+                        max_allowed_uids: old.max_allowed_uids,
+                        max_allowed_weights: old.max_allowed_weights,
+                        min_allowed_weights: old.min_allowed_weights,
+                        max_weight_age: old.max_weight_age,
+                        min_stake: old.min_stake,
+                        name: old.name,
+                        tempo: old.tempo,
+                        trust_ratio: old.trust_ratio,
+                        maximum_set_weight_calls_per_epoch: old.maximum_set_weight_calls_per_epoch,
+                        vote_mode: old.vote_mode,
+                        bonds_ma: old.bonds_ma,
+                        target_registrations_interval: old.target_registrations_interval,
+                        target_registrations_per_interval: old.target_registrations_per_interval,
+                        max_registrations_per_interval: old.max_registrations_per_interval,
+                        ..Pallet::<T>::subnet_params(netuid)
+                    },
+                },
+                Self::SubnetCustom { netuid, data } => ProposalData::SubnetCustom { netuid, data },
+                Self::Expired => ProposalData::Expired,
+                Self::TransferDaoTreasury { data, value, dest } => {
+                    ProposalData::TransferDaoTreasury { data, value, dest }
+                }
+            }
+        }
+    }
+
+    /// Records the current stake of the voters as their voting power.
+    fn snapshot<T: Config>(
+        voters: BTreeSet<T::AccountId>,
+        netuid: Option<u16>,
+    ) -> BTreeMap<T::AccountId, u64> {
+        voters
+            .into_iter()
+            .map(|voter| {
+                let stake = Pallet::<T>::get_account_stake(&voter, netuid);
+                (voter, stake)
+            })
+            .collect()
+    }
+
+    pub struct MigrateToV15<T>(sp_std::marker::PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV15<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let on_chain_version = StorageVersion::get::<Pallet<T>>();
+
+            if on_chain_version != 14 {
+                log::info!("Storage v15 already updated");
+                return Weight::zero();
+            }
+
+            // Votes now carry the voting power of the voter. The votes cast so far are recorded
+            // with the stake the voters have at the upgrade.
+            let mut translated = 0u64;
+            let mut votes = 0u64;
+            Proposals::<T>::translate(|_, old: OldProposal<T>| {
+                translated += 1;
+                votes += (old.votes_for.len() + old.votes_against.len()) as u64;
+
+                let data = old.data.migrate();
+                let netuid = data.netuid();
+                Some(Proposal {
+                    id: old.id,
+                    proposer: old.proposer,
+                    expiration_block: old.expiration_block,
+                    data,
+                    status: old.status,
+                    votes_for: snapshot::<T>(old.votes_for, netuid),
+                    votes_against: snapshot::<T>(old.votes_against, netuid),
+                    proposal_cost: old.proposal_cost,
+                    creation_block: old.creation_block,
+                    finalization_block: old.finalization_block,
+                })
+            });
+
+            log::info!("Migrated {translated} proposals to V15");
+
+            StorageVersion::new(15).put::<Pallet<T>>();
+
+            T::DbWeight::get().reads_writes(translated + votes * 2, translated + 1)
+        }
+    }
+}
+
+pub mod v16 {
+    use super::*;
+
+    #[frame_support::storage_alias]
+    type Curator<T: Config> = StorageValue<Pallet<T>, <T as frame_system::Config>::AccountId>;
+
+    pub struct MigrateToV16<T>(sp_std::marker::PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV16<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let on_chain_version = StorageVersion::get::<Pallet<T>>();
+
+            if on_chain_version != 15 {
+                log::info!("Storage v16 already updated");
+                return Weight::zero();
+            }
+
+            // The single curator is replaced by a council. The current curator becomes its only
+            // member, with a threshold of 1 it keeps acting alone until governance changes it.
+            if let Some(curator) = Curator::<T>::take() {
+                log::info!("Seeding the curator council with {curator:?}");
+                CuratorCouncil::<T>::put(BTreeSet::from([curator]));
+            }
+            CuratorCouncilThreshold::<T>::put(1);
+
+            log::info!("Migrated curator council to V16");
+
+            StorageVersion::new(16).put::<Pallet<T>>();
+
+            T::DbWeight::get().reads_writes(2, 4)
+        }
+    }
+}
+
+pub mod v17 {
+    use super::*;
+    use crate::voting::ProposalStatus;
+
+    pub struct MigrateToV17<T>(sp_std::marker::PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV17<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let on_chain_version = StorageVersion::get::<Pallet<T>>();
+
+            if on_chain_version != 16 {
+                log::info!("Storage v17 already updated");
+                return Weight::zero();
+            }
+
+            // IDs now come from counters, they continue after the highest ID in use.
+            let next_proposal_id = Proposals::<T>::iter_keys().max().map_or(0, |id| id + 1);
+            NextProposalId::<T>::put(next_proposal_id);
+            let next_application_id =
+                CuratorApplications::<T>::iter_keys().max().map_or(0, |id| id + 1);
+            NextApplicationId::<T>::put(next_application_id);
+
+            // Pending proposals are indexed, finalized ones are kept for the retention period
+            // from their finalization, or from the upgrade if that already passed.
+            let current_block = Pallet::<T>::get_current_block_number();
+            let retention = ProposalRetention::<T>::get();
+            let mut proposals = 0u64;
+            let mut active = BTreeSet::new();
+            for proposal in Proposals::<T>::iter_values() {
+                proposals += 1;
+
+                match proposal.status {
+                    ProposalStatus::Pending => {
+                        active.insert(proposal.id);
+                    }
+                    ProposalStatus::Approved { .. } => {}
+                    ProposalStatus::Accepted
+                    | ProposalStatus::Refused
+                    | ProposalStatus::Expired
+                    | ProposalStatus::Cancelled => {
+                        let finalization_block =
+                            proposal.finalization_block.unwrap_or(current_block);
+                        let pruning_block = finalization_block
+                            .saturating_add(retention)
+                            .max(current_block.saturating_add(1));
+                        ProposalPruningQueue::<T>::append(pruning_block, proposal.id);
+                    }
+                }
+            }
+
+            log::info!(
+                "Indexed {} active proposals out of {proposals}, next proposal id is \
+                 {next_proposal_id}",
+                active.len()
+            );
+            ActiveProposals::<T>::put(active);
+
+            log::info!("Migrated proposal lifecycle storage to V17");
+
+            StorageVersion::new(17).put::<Pallet<T>>();
+
+            T::DbWeight::get().reads_writes(proposals * 2 + 1, proposals + 4)
+        }
+    }
+}
@@ -12,6 +12,7 @@ pub struct ModuleStats<T: Config> {
     pub registration_block: u64,
     pub stake_from: BTreeMap<T::AccountId, u64>, /* map of key to stake on this module/key *
                                                   * (includes delegations) */
+    pub unbonding: BTreeMap<T::AccountId, u64>, // map of key to stake unbonding from this module
     pub emission: u64,
     pub incentive: u16,
     pub dividends: u16,
@@ -94,7 +95,11 @@ impl ModuleChangeset {
             let floor = Pallet::<T>::get_floor_delegation_fee();
             ensure!(fee >= floor, Error::<T>::InvalidMinDelegationFee);
 
-            DelegationFee::<T>::insert(netuid, &key, fee);
+            if Pallet::<T>::is_registered(netuid, &key) {
+                Pallet::<T>::update_delegation_fee(netuid, &key, fee)?;
+            } else {
+                DelegationFee::<T>::insert(netuid, &key, fee);
+            }
         }
 
         if let Some(metadata) = self.metadata {
@@ -116,7 +121,8 @@ impl<T: Config> Pallet<T> {
         changeset: ModuleChangeset,
     ) -> DispatchResult {
         // 1. We check the callers (key) signature.
-        let key = ensure_signed(origin)?;
+        let caller = ensure_signed(origin)?;
+        let key = Self::get_module_key_for_caller(netuid, caller);
         let uid: u16 = Self::get_uid_for_key(netuid, &key);
 
         // 2. Apply the changeset
@@ -125,6 +131,280 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
+    pub fn do_set_controller(
+        origin: T::RuntimeOrigin,
+        netuid: u16,
+        controller: T::AccountId,
+    ) -> DispatchResult {
+        // 1. Only the module key itself can assign a controller.
+        let key = ensure_signed(origin)?;
+        ensure!(Self::is_registered(netuid, &key), Error::<T>::NotRegistered);
+
+        // 2. The controller must not be ambiguous, it can not be a module of the subnet or
+        // control another module on it.
+        ensure!(
+            controller != key && !Self::is_registered(netuid, &controller),
+            Error::<T>::InvalidController
+        );
+        ensure!(
+            !ControlledModule::<T>::contains_key(netuid, &controller),
+            Error::<T>::ControllerAlreadyInUse
+        );
+
+        // 3. Replace the previous controller, if any.
+        if let Some(old_controller) = Controller::<T>::get(netuid, &key) {
+            ControlledModule::<T>::remove(netuid, old_controller);
+        }
+        Controller::<T>::insert(netuid, &key, &controller);
+        ControlledModule::<T>::insert(netuid, &controller, &key);
+
+        Self::deposit_event(Event::ControllerSet(netuid, key, controller));
+
+        Ok(())
+    }
+
+    pub fn do_clear_controller(origin: T::RuntimeOrigin, netuid: u16) -> DispatchResult {
+        // 1. Only the module key itself can remove its controller.
+        let key = ensure_signed(origin)?;
+        ensure!(Self::is_registered(netuid, &key), Error::<T>::NotRegistered);
+
+        // 2. Remove both directions of the association.
+        let controller = Controller::<T>::take(netuid, &key).ok_or(Error::<T>::ControllerNotSet)?;
+        ControlledModule::<T>::remove(netuid, controller);
+
+        Self::deposit_event(Event::ControllerCleared(netuid, key));
+
+        Ok(())
+    }
+
+    /// Returns the module key the caller acts for on the subnet. Registered keys always act for
+    /// themselves, controllers act for the module that assigned them.
+    pub fn get_module_key_for_caller(netuid: u16, caller: T::AccountId) -> T::AccountId {
+        if Self::is_registered(netuid, &caller) {
+            return caller;
+        }
+
+        ControlledModule::<T>::get(netuid, &caller).unwrap_or(caller)
+    }
+
+    /// Removes the controller association of the module, if any.
+    pub fn remove_controller(netuid: u16, key: &T::AccountId) {
+        if let Some(controller) = Controller::<T>::take(netuid, key) {
+            ControlledModule::<T>::remove(netuid, controller);
+        }
+    }
+
+    /// Moves the modules of the caller to `new_key` on every subnet the caller is registered on.
+    /// The uids, stake, delegations, weights and governance state of the modules are kept.
+    pub fn do_swap_module_key(
+        origin: T::RuntimeOrigin,
+        netuid: u16,
+        new_key: T::AccountId,
+    ) -> DispatchResult {
+        // 1. The old key must be registered on the subnet.
+        let old_key = ensure_signed(origin)?;
+        ensure!(
+            Self::is_registered(netuid, &old_key),
+            Error::<T>::NotRegistered
+        );
+
+        // 2. The new key must not be registered anywhere, otherwise two modules would be merged.
+        ensure!(
+            new_key != old_key && !Self::is_key_registered_on_any_network(&new_key),
+            Error::<T>::KeyAlreadyRegistered
+        );
+
+        // 3. Rewrite the module on every subnet the old key is registered on.
+        let netuids: Vec<u16> = Self::netuids()
+            .into_iter()
+            .filter(|netuid| Self::is_registered(*netuid, &old_key))
+            .collect();
+        for netuid in netuids {
+            Self::swap_module_key_on_subnet(netuid, &old_key, &new_key);
+            Self::deposit_event(Event::ModuleKeySwapped(
+                netuid,
+                old_key.clone(),
+                new_key.clone(),
+            ));
+        }
+
+        // 4. Move the storage that is not bound to a subnet.
+        if ProfitShares::<T>::contains_key(&old_key) {
+            ProfitShares::<T>::insert(&new_key, ProfitShares::<T>::take(&old_key));
+        }
+
+        for mut chunk in UnlockingQueue::<T>::take(&old_key) {
+            Self::forget_unbonding(chunk.netuid, &chunk.module_key, &old_key, chunk.amount);
+            if chunk.module_key == old_key {
+                chunk.module_key = new_key.clone();
+            }
+            Self::queue_unlock_chunk(&new_key, chunk)?;
+        }
+
+        if LegitWhitelist::<T>::contains_key(&old_key) {
+            let weight = LegitWhitelist::<T>::take(&old_key);
+            LegitWhitelist::<T>::mutate(&new_key, |new_weight| {
+                *new_weight = (*new_weight).max(weight)
+            });
+        }
+
+        Self::swap_governance_key(&old_key, &new_key);
+
+        Ok(())
+    }
+
+    fn swap_module_key_on_subnet(netuid: u16, old_key: &T::AccountId, new_key: &T::AccountId) {
+        // Moves the entry of `old_key` into the entry of `new_key`, adding up the amounts.
+        let rename = |mut map: BTreeMap<T::AccountId, u64>| {
+            if let Some(amount) = map.remove(old_key) {
+                let entry = map.entry(new_key.clone()).or_insert(0);
+                *entry = entry.saturating_add(amount);
+            }
+            map
+        };
+
+        // HANDLE THE KEY AND UID ASSOCIATIONS
+        let Some(uid) = Uids::<T>::take(netuid, old_key) else {
+            return;
+        };
+        Uids::<T>::insert(netuid, new_key, uid);
+        Keys::<T>::insert(netuid, uid, new_key);
+
+        // HANDLE THE DELEGATION REWARDS
+        // Everything the swap touches is settled, so the positions can be moved as they are.
+        Self::settle_all_delegation_rewards(netuid, old_key);
+        for key in [old_key, new_key] {
+            for module_key in StakeTo::<T>::get(netuid, key).into_keys() {
+                Self::settle_delegation_rewards(netuid, key, &module_key);
+            }
+        }
+
+        // HANDLE THE STAKE
+        Stake::<T>::insert(netuid, new_key, Stake::<T>::take(netuid, old_key));
+
+        // the delegators of the module now stake to the new key
+        let stake_from = Self::take_stake_from_vector(netuid, old_key);
+        for staker in stake_from.keys().filter(|staker| *staker != old_key) {
+            let stake_to = Self::get_stake_to_vector(netuid, staker);
+            Self::set_stake_to_vector(netuid, staker, rename(stake_to));
+        }
+
+        // the modules the old key staked to are now staked to by the new key
+        let stake_to = StakeTo::<T>::take(netuid, old_key);
+        for module_key in stake_to.keys().filter(|module_key| *module_key != old_key) {
+            let stake_from = Self::get_stake_from_vector(netuid, module_key);
+            Self::set_stake_from_vector(netuid, module_key, rename(stake_from));
+        }
+
+        Self::set_stake_from_vector(netuid, new_key, rename(stake_from));
+        let mut new_stake_to = Self::get_stake_to_vector(netuid, new_key);
+        for (module_key, amount) in rename(stake_to) {
+            let entry = new_stake_to.entry(module_key).or_insert(0);
+            *entry = entry.saturating_add(amount);
+        }
+        Self::set_stake_to_vector(netuid, new_key, new_stake_to);
+
+        let reward_per_share = DelegationRewardPerShare::<T>::take(netuid, old_key);
+        DelegationRewardPerShare::<T>::insert(netuid, new_key, reward_per_share);
+        let unsettled = UnsettledDelegationRewards::<T>::take(netuid, old_key);
+        UnsettledDelegationRewards::<T>::insert(netuid, new_key, unsettled);
+        DelegationRewardCheckpoints::<T>::remove(netuid, old_key);
+        Self::reset_delegation_checkpoints(netuid, new_key);
+        for staker in StakeFrom::<T>::get(netuid, new_key).into_keys().filter(|k| k != new_key) {
+            DelegationRewardCheckpoints::<T>::mutate(netuid, &staker, |checkpoints| {
+                checkpoints.remove(old_key);
+                checkpoints.insert(new_key.clone(), reward_per_share);
+            });
+        }
+
+        // HANDLE THE DELEGATION FEE
+        DelegationFee::<T>::insert(netuid, new_key, DelegationFee::<T>::take(netuid, old_key));
+        if let Some(pending) = PendingDelegationFee::<T>::take(netuid, old_key) {
+            PendingDelegationFee::<T>::insert(netuid, new_key, pending);
+        }
+
+        // HANDLE THE METADATA
+        if let Some(metadata) = Metadata::<T>::take(netuid, old_key) {
+            Metadata::<T>::insert(netuid, new_key, metadata);
+        }
+
+        // HANDLE THE CONTROLLER
+        if let Some(controller) = Controller::<T>::take(netuid, old_key) {
+            ControlledModule::<T>::insert(netuid, &controller, new_key);
+            Controller::<T>::insert(netuid, new_key, controller);
+        }
+
+        // HANDLE THE WEIGHT COMMIT
+        // commits are bound to the uid, so they can still be revealed by the new key
+        if let Some(commit) = WeightCommits::<T>::take(netuid, old_key) {
+            WeightCommits::<T>::insert(netuid, new_key, commit);
+        }
+
+        // HANDLE THE SET WEIGHTS RATE LIMITER
+        if SetWeightCallsPerEpoch::<T>::contains_key(netuid, old_key) {
+            SetWeightCallsPerEpoch::<T>::insert(
+                netuid,
+                new_key,
+                SetWeightCallsPerEpoch::<T>::take(netuid, old_key),
+            );
+        }
+    }
+
+    /// Updates the delegation fee of a registered module. Decreases take effect right away,
+    /// increases are capped by `MaxDelegationFeeIncrease` and only become active after
+    /// `DelegationFeeIncreaseDelay` epochs, giving delegators time to move their stake. Setting
+    /// the current fee again withdraws an announced increase.
+    pub fn update_delegation_fee(netuid: u16, key: &T::AccountId, fee: Percent) -> DispatchResult {
+        let current_fee = DelegationFee::<T>::get(netuid, key);
+
+        if fee <= current_fee {
+            if fee < current_fee {
+                DelegationFee::<T>::insert(netuid, key, fee);
+            }
+            PendingDelegationFee::<T>::remove(netuid, key);
+            return Ok(());
+        }
+
+        let increase = fee.deconstruct().saturating_sub(current_fee.deconstruct());
+        ensure!(
+            increase <= MaxDelegationFeeIncrease::<T>::get().deconstruct(),
+            Error::<T>::DelegationFeeIncreaseTooLarge
+        );
+
+        let delay = DelegationFeeIncreaseDelay::<T>::get();
+        if delay == 0 {
+            DelegationFee::<T>::insert(netuid, key, fee);
+            PendingDelegationFee::<T>::remove(netuid, key);
+            return Ok(());
+        }
+
+        let activation_block = Self::get_current_block_number()
+            .saturating_add((delay as u64).saturating_mul(Self::get_tempo(netuid) as u64));
+        PendingDelegationFee::<T>::insert(netuid, key, (fee, activation_block));
+
+        Self::deposit_event(Event::DelegationFeeIncreaseAnnounced(
+            netuid,
+            key.clone(),
+            fee,
+            activation_block,
+        ));
+
+        Ok(())
+    }
+
+    /// Activates the announced delegation fee increases of the subnet whose delay is over.
+    pub fn activate_delegation_fee_increases(netuid: u16, block_number: u64) {
+        let ready: Vec<_> = PendingDelegationFee::<T>::iter_prefix(netuid)
+            .filter(|(_, (_, activation_block))| *activation_block <= block_number)
+            .collect();
+
+        for (key, (fee, _)) in ready {
+            PendingDelegationFee::<T>::remove(netuid, &key);
+            DelegationFee::<T>::insert(netuid, &key, fee);
+            Self::deposit_event(Event::DelegationFeeIncreaseActivated(netuid, key, fee));
+        }
+    }
+
     pub fn does_module_name_exist(netuid: u16, name: &[u8]) -> bool {
         Name::<T>::iter_prefix_values(netuid).any(|existing| existing == name)
     }
@@ -137,7 +417,7 @@ impl<T: Config> Pallet<T> {
             address: Address::<T>::get(netuid, uid),
             metadata: Metadata::<T>::get(netuid, key),
             delegation_fee: DelegationFee::<T>::get(netuid, key),
-            controller: key.clone(),
+            controller: Controller::<T>::get(netuid, key).unwrap_or_else(|| key.clone()),
         }
     }
 
@@ -296,6 +576,10 @@ impl<T: Config> Pallet<T> {
             DelegationFee::<T>::get(netuid, &replace_key),
         ); // Make uid - key association.
         DelegationFee::<T>::remove(netuid, &replace_key); // Make uid - key association.
+        PendingDelegationFee::<T>::remove(netuid, &module_key);
+
+        // HANDLE THE CONTROLLER
+        Self::remove_controller(netuid, &module_key);
 
         // remove stake from old key and add to new key
         Self::remove_stake_from_storage(netuid, &module_key);
@@ -325,11 +609,13 @@ impl<T: Config> Pallet<T> {
             .filter_map(|(i, w)| if *w > 0 { Some((*i, *w)) } else { None })
             .collect();
         let stake_from: BTreeMap<T::AccountId, u64> = StakeFrom::<T>::get(netuid, key);
+        let unbonding = Self::get_unbonding_from(netuid, key);
 
         let registration_block = Self::get_registration_block_for_uid(netuid, uid);
 
         ModuleStats {
             stake_from,
+            unbonding,
             emission,
             incentive,
             dividends,
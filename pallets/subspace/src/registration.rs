@@ -1,4 +1,4 @@
-use crate::{module::ModuleChangeset, subnet::SubnetChangeset};
+use crate::{council::CuratorAction, module::ModuleChangeset, subnet::SubnetChangeset};
 
 use super::*;
 
@@ -17,37 +17,15 @@ impl<T: Config> Pallet<T> {
         // --- 1. Check that the caller has signed the transaction.
         let key = ensure_signed(origin)?;
 
-        // --- 2. Ensure that the key is the curator multisig.
-        ensure!(Self::get_curator() == key, Error::<T>::NotCurator);
-
-        // --- 2.1 Make sure the key application was submitted
-        let application_exists = CuratorApplications::<T>::iter()
-            .any(|(_, application)| application.user_id == module_key);
-
-        ensure!(application_exists, Error::<T>::ApplicationNotFound);
-
-        // --- 3. Ensure that the module_key is not already in the whitelist.
-        ensure!(
-            !Self::is_in_legit_whitelist(&module_key),
-            Error::<T>::AlreadyWhitelisted
-        );
-
-        ensure!(
-            recommended_weight <= 100 && recommended_weight > 0,
-            Error::<T>::InvalidRecommendedWeight
-        );
-
-        // --- 4. Insert the module_key into the whitelist.
-        Self::insert_to_whitelist(module_key.clone(), recommended_weight);
-
-        // execute the application
-        Self::execute_application(&module_key).unwrap();
-
-        // -- deposit event
-        Self::deposit_event(Event::WhitelistModuleAdded(module_key));
-
-        // --- 5. Ok and done.
-        Ok(())
+        // --- 2. Approve the addition as a member of the curator council. The module is
+        // whitelisted and its application executed once the council agrees.
+        Self::approve_curator_action(
+            key,
+            CuratorAction::AddToWhitelist {
+                module_key,
+                recommended_weight,
+            },
+        )
     }
 
     pub fn do_remove_from_whitelist(
@@ -57,23 +35,8 @@ impl<T: Config> Pallet<T> {
         // --- 1. Check that the caller has signed the transaction.
         let key = ensure_signed(origin)?;
 
-        // --- 2. Ensure that the key is the curator multisig.
-        ensure!(Self::get_curator() == key, Error::<T>::NotCurator);
-
-        // --- 3. Ensure that the module_key is in the whitelist.
-        ensure!(
-            Self::is_in_legit_whitelist(&module_key),
-            Error::<T>::NotWhitelisted
-        );
-
-        // --- 4. Remove the module_key from the whitelist.
-        Self::rm_from_whitelist(&module_key);
-
-        // -- deposit event
-        Self::deposit_event(Event::WhitelistModuleRemoved(module_key));
-
-        // --- 5. Ok and done.
-        Ok(())
+        // --- 2. Approve the removal as a member of the curator council.
+        Self::approve_curator_action(key, CuratorAction::RemoveFromWhitelist { module_key })
     }
 
     pub fn do_register(
@@ -126,15 +89,11 @@ impl<T: Config> Pallet<T> {
             Error::<T>::TooManyRegistrationsPerInterval
         );
 
-        // TODO: later, once legit whitelist has been filled up, turn on the code below.
-        // We also have to declear a migration, of modules on netuid 0 that are not whitelisted.
-
-        // --- 4.1 Ensure that the module_key is in the whitelist, if netuid is 0.
-
-        // ensure!(
-        //     netuid != 0 || Self::is_in_legit_whitelist(&module_key),
-        //     Error::<T>::NotWhitelisted
-        // );
+        // --- 4.2 Ensure that the module_key is in the whitelist, if netuid is 0.
+        ensure!(
+            netuid != 0 || Self::is_in_legit_whitelist(&module_key),
+            Error::<T>::NotWhitelisted
+        );
 
         // --- 5. Ensure the caller has enough stake to register.
         let min_stake: u64 = MinStake::<T>::get(netuid);
@@ -0,0 +1,191 @@
+use super::*;
+
+use sp_std::collections::btree_map::BTreeMap;
+
+/// Scale of `DelegationRewardPerShare`, one unit of stake earning one token is stored as this
+/// value.
+pub const REWARD_PER_SHARE_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+// Delegators are not paid out on every epoch. Each module keeps a running total of the rewards
+// one unit of delegated stake has earned, and every delegator remembers the total it was last
+// settled at. The epoch adds the rewards to the stake of the module and of the subnet right away,
+// and the difference is only moved into the delegation whenever it changes or the delegator
+// claims it, so the cost of an epoch does not depend on the number of delegators.
+impl<T: Config> Pallet<T> {
+    /// Credits the delegators of `module_key` with their part of the validator emission of the
+    /// module, after the delegation fee. Returns the amount that was credited.
+    pub fn reward_delegators(
+        netuid: u16,
+        module_key: &T::AccountId,
+        validator_emission: u64,
+    ) -> u64 {
+        // rewards that were not settled yet do not earn rewards of their own
+        let unsettled = UnsettledDelegationRewards::<T>::get(netuid, module_key);
+        let total_stake = Stake::<T>::get(netuid, module_key).saturating_sub(unsettled);
+        let own_stake = StakeTo::<T>::get(netuid, module_key)
+            .get(module_key)
+            .copied()
+            .unwrap_or_default();
+        let delegated_stake = total_stake.saturating_sub(own_stake);
+        if validator_emission == 0 || delegated_stake == 0 {
+            return 0;
+        }
+
+        let delegators_share = (validator_emission as u128)
+            .saturating_mul(delegated_stake as u128)
+            .checked_div(total_stake as u128)
+            .unwrap_or_default() as u64;
+        let to_module = Self::get_delegation_fee(netuid, module_key).mul_floor(delegators_share);
+        let to_delegators = delegators_share.saturating_sub(to_module);
+
+        let reward_per_share = (to_delegators as u128)
+            .saturating_mul(REWARD_PER_SHARE_PRECISION)
+            .checked_div(delegated_stake as u128)
+            .unwrap_or_default();
+        if reward_per_share == 0 {
+            return 0;
+        }
+
+        // the accumulator only ever grows, wrapping keeps the differences right
+        DelegationRewardPerShare::<T>::mutate(netuid, module_key, |total| {
+            *total = total.wrapping_add(reward_per_share)
+        });
+
+        // Every delegator rounds its own rewards down, so rounding the credit up leaves enough to
+        // settle all of them. The dust that is left stays with the module.
+        let credited = (delegated_stake as u128)
+            .saturating_mul(reward_per_share)
+            .div_ceil(REWARD_PER_SHARE_PRECISION) as u64;
+
+        UnsettledDelegationRewards::<T>::mutate(netuid, module_key, |unsettled| {
+            *unsettled = unsettled.saturating_add(credited)
+        });
+        Stake::<T>::mutate(netuid, module_key, |stake| {
+            *stake = stake.saturating_add(credited)
+        });
+        TotalStake::<T>::mutate(netuid, |total_stake| {
+            *total_stake = total_stake.saturating_add(credited)
+        });
+
+        credited
+    }
+
+    /// Returns the rewards `key` has accrued on `module_key` that were not settled yet.
+    pub fn get_pending_delegation_rewards(
+        netuid: u16,
+        key: &T::AccountId,
+        module_key: &T::AccountId,
+    ) -> u64 {
+        if key == module_key {
+            return 0;
+        }
+
+        let stake = StakeTo::<T>::get(netuid, key).get(module_key).copied().unwrap_or_default();
+        let checkpoint = DelegationRewardCheckpoints::<T>::get(netuid, key)
+            .get(module_key)
+            .copied()
+            .unwrap_or_default();
+        let reward_per_share =
+            DelegationRewardPerShare::<T>::get(netuid, module_key).wrapping_sub(checkpoint);
+
+        Self::reward_for_stake(stake, reward_per_share)
+    }
+
+    /// Moves the pending rewards of `key` on `module_key` into its delegation. Has to be called
+    /// before the delegated amount changes. Returns the settled amount.
+    pub fn settle_delegation_rewards(
+        netuid: u16,
+        key: &T::AccountId,
+        module_key: &T::AccountId,
+    ) -> u64 {
+        if key == module_key {
+            return 0;
+        }
+
+        let pending = Self::get_pending_delegation_rewards(netuid, key, module_key);
+        let reward_per_share = DelegationRewardPerShare::<T>::get(netuid, module_key);
+        DelegationRewardCheckpoints::<T>::mutate(netuid, key, |checkpoints| {
+            checkpoints.insert(module_key.clone(), reward_per_share);
+        });
+
+        // the module and the subnet were credited by the epoch already
+        if pending > 0 {
+            Self::add_stake_to_vectors(netuid, key, module_key, pending);
+            UnsettledDelegationRewards::<T>::mutate(netuid, module_key, |unsettled| {
+                *unsettled = unsettled.saturating_sub(pending)
+            });
+        }
+
+        pending
+    }
+
+    /// Settles the pending rewards of every delegator of `module_key`.
+    pub fn settle_all_delegation_rewards(netuid: u16, module_key: &T::AccountId) {
+        for key in StakeFrom::<T>::get(netuid, module_key).into_keys() {
+            Self::settle_delegation_rewards(netuid, &key, module_key);
+        }
+    }
+
+    /// Forgets the checkpoint of a delegation that no longer exists.
+    pub fn remove_delegation_checkpoint(
+        netuid: u16,
+        key: &T::AccountId,
+        module_key: &T::AccountId,
+    ) {
+        let mut checkpoints = DelegationRewardCheckpoints::<T>::get(netuid, key);
+        if checkpoints.remove(module_key).is_none() {
+            return;
+        }
+
+        if checkpoints.is_empty() {
+            DelegationRewardCheckpoints::<T>::remove(netuid, key);
+        } else {
+            DelegationRewardCheckpoints::<T>::insert(netuid, key, checkpoints);
+        }
+    }
+
+    /// Sets the checkpoints of every delegation of `key` on the subnet to the current reward per
+    /// share of the modules. Only valid once all of them were settled.
+    pub fn reset_delegation_checkpoints(netuid: u16, key: &T::AccountId) {
+        let checkpoints: BTreeMap<_, _> = StakeTo::<T>::get(netuid, key)
+            .into_keys()
+            .filter(|module_key| module_key != key)
+            .map(|module_key| {
+                let reward_per_share = DelegationRewardPerShare::<T>::get(netuid, &module_key);
+                (module_key, reward_per_share)
+            })
+            .collect();
+
+        if checkpoints.is_empty() {
+            DelegationRewardCheckpoints::<T>::remove(netuid, key);
+        } else {
+            DelegationRewardCheckpoints::<T>::insert(netuid, key, checkpoints);
+        }
+    }
+
+    pub fn do_claim_delegation_rewards(
+        origin: T::RuntimeOrigin,
+        netuid: u16,
+        module_key: T::AccountId,
+    ) -> dispatch::DispatchResult {
+        // --- 1. We check the transaction is signed by the caller and retrieve the T::AccountId key
+        // information.
+        let key = ensure_signed(origin)?;
+
+        // --- 2. Settle the rewards into the stake of the key.
+        let amount = Self::settle_delegation_rewards(netuid, &key, &module_key);
+        ensure!(amount > 0, Error::<T>::NoDelegationRewards);
+
+        Self::deposit_event(Event::DelegationRewardsClaimed(
+            netuid, key, module_key, amount,
+        ));
+
+        // --- 3. Done and ok.
+        Ok(())
+    }
+
+    pub(crate) fn reward_for_stake(stake: u64, reward_per_share: u128) -> u64 {
+        let reward = (stake as u128).saturating_mul(reward_per_share) / REWARD_PER_SHARE_PRECISION;
+        reward.try_into().unwrap_or(u64::MAX)
+    }
+}
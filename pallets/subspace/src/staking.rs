@@ -1,9 +1,32 @@
 use super::*;
 
+use frame_support::{pallet_prelude::DispatchResult, traits::ConstU32, BoundedVec};
 use sp_arithmetic::per_things::Percent;
 use sp_runtime::DispatchError;
 use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
 
+/// Longest unbonding period a subnet can set, roughly 20 days of 8 second blocks, so founders can
+/// not lock delegators out forever.
+pub const MAX_UNBONDING_PERIOD: u64 = 216_000;
+
+/// How many chunks of stake an account can be unbonding at once. Chunks unlocking at the same
+/// block from the same module are merged.
+pub const MAX_UNLOCKING_CHUNKS: u32 = 32;
+
+/// The chunks of stake an account is unbonding.
+pub type UnlockingChunks<T> = BoundedVec<UnlockChunk<T>, ConstU32<MAX_UNLOCKING_CHUNKS>>;
+
+/// A chunk of stake that was removed from a module and is waiting for the
+/// unbonding period of its subnet to pass before it can be withdrawn.
+#[derive(Clone, Debug, PartialEq, Eq, TypeInfo, Decode, Encode)]
+#[scale_info(skip_type_params(T))]
+pub struct UnlockChunk<T: Config> {
+    pub netuid: u16,
+    pub module_key: T::AccountId,
+    pub amount: u64,
+    pub unlock_block: u64,
+}
+
 impl<T: Config> Pallet<T> {
     /// Adds stake to multiple modules in a single transaction
     pub fn do_add_stake_multiple(
@@ -123,7 +146,7 @@ impl<T: Config> Pallet<T> {
         amount: u64,
     ) -> dispatch::DispatchResult {
         // --- 1. We check that the transaction is signed by the caller and retrieve the
-        let key = ensure_signed(origin.clone())?;
+        let key = ensure_signed(origin)?;
 
         // --- 2. Check if both modules are registered
         // --- 2.1 old module check
@@ -143,10 +166,75 @@ impl<T: Config> Pallet<T> {
             Error::<T>::NotEnoughStakeToWithdraw
         );
 
-        // --- 4. Remove stake from the source module and add it to the destination module
-        Self::do_remove_stake(origin.clone(), netuid, module_key.clone(), amount)?;
-        // don't allow zero stakes
-        Self::do_add_stake(origin.clone(), netuid, new_module_key, amount)?;
+        // --- 4. Move the stake from the source module to the destination module.
+        // The stake never leaves the subnet, so it does not go through the unbonding period, and
+        // stake locked by conviction votes stays locked.
+        Self::decrease_stake(netuid, &key, &module_key, amount);
+        Self::increase_stake(netuid, &key, &new_module_key, amount);
+
+        Self::deposit_event(Event::StakeRemoved(key.clone(), module_key, amount));
+        Self::deposit_event(Event::StakeAdded(key, new_module_key, amount));
+
+        // --- 5. Done and ok
+        Ok(())
+    }
+
+    /// Transfers stake from a module on one subnet to a module on another subnet.
+    /// The stake stays bonded the whole time, so no unbonding period applies.
+    pub fn do_transfer_stake_across_subnets(
+        origin: T::RuntimeOrigin,
+        from_netuid: u16,
+        from_module_key: T::AccountId,
+        to_netuid: u16,
+        to_module_key: T::AccountId,
+        amount: u64,
+    ) -> dispatch::DispatchResult {
+        // --- 1. We check that the transaction is signed by the caller and retrieve the
+        let key = ensure_signed(origin)?;
+
+        // --- 1.1 Transfers inside a subnet go through `transfer_stake`
+        ensure!(from_netuid != to_netuid, Error::<T>::TransferToSameSubnet);
+
+        // --- 2. Check if both modules are registered
+        // --- 2.1 source module check
+        ensure!(
+            Self::is_registered(from_netuid, &from_module_key),
+            Error::<T>::NotRegistered
+        );
+        // --- 2.2 destination module check
+        ensure!(
+            Self::is_registered(to_netuid, &to_module_key),
+            Error::<T>::NotRegistered
+        );
+
+        // --- 3. Check if the caller has enough stake in the source module
+        ensure!(
+            Self::has_enough_stake(from_netuid, &key, &from_module_key, amount),
+            Error::<T>::NotEnoughStakeToWithdraw
+        );
+
+        // --- 3.1 Stake locked by conviction votes on the subnet stays where it is, the stake
+        // locked on the whole network stays on the network anyway
+        Self::ensure_subnet_stake_unlocked(&key, from_netuid, amount)?;
+
+        // --- 3.2 The stake can not be moved somewhere it would unbond faster than at the source
+        ensure!(
+            UnbondingPeriod::<T>::get(to_netuid) >= UnbondingPeriod::<T>::get(from_netuid),
+            Error::<T>::UnbondingPeriodTooShort
+        );
+
+        // --- 4. Move the stake, this updates the stake maps and the total stake of both subnets
+        Self::decrease_stake(from_netuid, &key, &from_module_key, amount);
+        Self::increase_stake(to_netuid, &key, &to_module_key, amount);
+
+        Self::deposit_event(Event::StakeTransferredAcrossSubnets(
+            key,
+            from_netuid,
+            from_module_key,
+            to_netuid,
+            to_module_key,
+            amount,
+        ));
 
         // --- 5. Done and ok
         Ok(())
@@ -240,6 +328,9 @@ impl<T: Config> Pallet<T> {
             Error::<T>::NotEnoughStakeToWithdraw
         );
 
+        // --- 3.1 We check that the stake is not locked by a conviction vote.
+        Self::ensure_stake_unlocked(&key, netuid, amount)?;
+
         // --- 4. Make sure we can convert to balance
         let stake_to_be_added_as_currency = Self::u64_to_balance(amount);
         ensure!(
@@ -256,7 +347,31 @@ impl<T: Config> Pallet<T> {
         Self::decrease_stake(netuid, &key, &module_key, amount);
 
         // --- 7. We add the balancer to the key. If the above fails we will not credit this key.
-        Self::add_balance_to_account(&key, Self::u64_to_balance(amount).unwrap());
+        // Subnets with an unbonding period queue the stake instead, it has to be withdrawn
+        // with `withdraw_unbonded` once the period is over.
+        let unbonding_period = UnbondingPeriod::<T>::get(netuid);
+        let released_amount = if unbonding_period == 0 {
+            Self::add_balance_to_account(&key, stake_to_be_added_as_currency.unwrap());
+            amount
+        } else {
+            let unlock_block = Self::get_current_block_number().saturating_add(unbonding_period);
+            Self::queue_unlock_chunk(
+                &key,
+                UnlockChunk {
+                    netuid,
+                    module_key: module_key.clone(),
+                    amount,
+                    unlock_block,
+                },
+            )?;
+            Self::deposit_event(Event::StakeUnbonding(
+                key.clone(),
+                module_key.clone(),
+                amount,
+                unlock_block,
+            ));
+            0
+        };
 
         // --- 8. Check after values
         let stake_after_remove: u64 = Self::get_stake_to_module(netuid, &key, &module_key.clone());
@@ -270,7 +385,7 @@ impl<T: Config> Pallet<T> {
             Error::<T>::StakeNotRemoved
         );
         ensure!(
-            balance_after_remove == balance_before_remove.saturating_add(amount),
+            balance_after_remove == balance_before_remove.saturating_add(released_amount),
             Error::<T>::BalanceNotAdded
         );
         ensure!(
@@ -284,6 +399,135 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
+    /// Withdraws all unbonded stake of the caller whose unbonding period is over
+    pub fn do_withdraw_unbonded(origin: T::RuntimeOrigin) -> dispatch::DispatchResult {
+        // --- 1. We check the transaction is signed by the caller and retrieve the T::AccountId key
+        // information.
+        let key = ensure_signed(origin)?;
+
+        // --- 2. Split the chunks that can be withdrawn from the ones that are still unbonding.
+        let current_block = Self::get_current_block_number();
+        let mut unlocking = UnlockingQueue::<T>::get(&key);
+        let mut withdrawable: u64 = 0;
+        unlocking.retain(|chunk| {
+            if chunk.unlock_block <= current_block {
+                withdrawable = withdrawable.saturating_add(chunk.amount);
+                Self::forget_unbonding(chunk.netuid, &chunk.module_key, &key, chunk.amount);
+                false
+            } else {
+                true
+            }
+        });
+        ensure!(withdrawable > 0, Error::<T>::NothingToWithdraw);
+
+        // --- 3. Make sure we can convert to balance
+        let withdrawable_as_currency =
+            Self::u64_to_balance(withdrawable).ok_or(Error::<T>::CouldNotConvertToBalance)?;
+
+        // --- 4. Update the queue and credit the key.
+        if unlocking.is_empty() {
+            UnlockingQueue::<T>::remove(&key);
+        } else {
+            UnlockingQueue::<T>::insert(&key, unlocking);
+        }
+        Self::add_balance_to_account(&key, withdrawable_as_currency);
+
+        Self::deposit_event(Event::StakeWithdrawn(key, withdrawable));
+
+        // --- 5. Done and ok.
+        Ok(())
+    }
+
+    /// Returns the chunks of stake the key is currently unbonding.
+    pub fn get_unlocking_chunks(key: &T::AccountId) -> Vec<UnlockChunk<T>> {
+        UnlockingQueue::<T>::get(key).into_inner()
+    }
+
+    /// Returns the stake each account is unbonding from the module.
+    pub fn get_unbonding_from(
+        netuid: u16,
+        module_key: &T::AccountId,
+    ) -> BTreeMap<T::AccountId, u64> {
+        UnbondingFrom::<T>::get(netuid, module_key)
+    }
+
+    /// Queues a chunk of stake of the key, merging it into a chunk of the same module that
+    /// unlocks at the same block.
+    pub(crate) fn queue_unlock_chunk(key: &T::AccountId, chunk: UnlockChunk<T>) -> DispatchResult {
+        UnlockingQueue::<T>::try_mutate(key, |queue| {
+            let same_chunk = queue.iter_mut().find(|queued| {
+                queued.netuid == chunk.netuid
+                    && queued.module_key == chunk.module_key
+                    && queued.unlock_block == chunk.unlock_block
+            });
+            match same_chunk {
+                Some(queued) => queued.amount = queued.amount.saturating_add(chunk.amount),
+                None => {
+                    queue.try_push(chunk.clone()).map_err(|_| Error::<T>::TooManyUnlockingChunks)?
+                }
+            }
+            Ok::<(), DispatchError>(())
+        })?;
+
+        Self::note_unbonding(chunk.netuid, &chunk.module_key, key, chunk.amount);
+        Ok(())
+    }
+
+    /// Queues a chunk of stake that can not be refused, like the stake of a removed module. If the
+    /// queue of the key is full, the chunk is merged into the one that unlocks last, which then
+    /// unlocks at the later of both blocks, so the stake is never released early. Returns the
+    /// block the stake unlocks at.
+    pub(crate) fn force_queue_unlock_chunk(key: &T::AccountId, chunk: UnlockChunk<T>) -> u64 {
+        if Self::queue_unlock_chunk(key, chunk.clone()).is_ok() {
+            return chunk.unlock_block;
+        }
+
+        let merged = UnlockingQueue::<T>::mutate(key, |queue| {
+            let last = queue.iter_mut().max_by_key(|queued| queued.unlock_block)?;
+            last.amount = last.amount.saturating_add(chunk.amount);
+            last.unlock_block = last.unlock_block.max(chunk.unlock_block);
+            Some((last.netuid, last.module_key.clone(), last.unlock_block))
+        });
+
+        // a full queue always has a chunk to merge into
+        match merged {
+            Some((netuid, module_key, unlock_block)) => {
+                Self::note_unbonding(netuid, &module_key, key, chunk.amount);
+                unlock_block
+            }
+            None => chunk.unlock_block,
+        }
+    }
+
+    fn note_unbonding(netuid: u16, module_key: &T::AccountId, key: &T::AccountId, amount: u64) {
+        UnbondingFrom::<T>::mutate(netuid, module_key, |unbonding| {
+            let entry = unbonding.entry(key.clone()).or_insert(0);
+            *entry = entry.saturating_add(amount);
+        });
+    }
+
+    pub(crate) fn forget_unbonding(
+        netuid: u16,
+        module_key: &T::AccountId,
+        key: &T::AccountId,
+        amount: u64,
+    ) {
+        UnbondingFrom::<T>::mutate_exists(netuid, module_key, |unbonding| {
+            let Some(map) = unbonding else {
+                return;
+            };
+            if let Some(entry) = map.get_mut(key) {
+                *entry = entry.saturating_sub(amount);
+                if *entry == 0 {
+                    map.remove(key);
+                }
+            }
+            if map.is_empty() {
+                *unbonding = None;
+            }
+        });
+    }
+
     /// Returns the total amount of stake in the staking table.
     pub fn get_total_subnet_stake(netuid: u16) -> u64 {
         TotalStake::<T>::get(netuid)
@@ -316,12 +560,18 @@ impl<T: Config> Pallet<T> {
         amount > 0 && Self::get_stake_to_module(netuid, key, module_key) >= amount
     }
 
+    /// Returns the stake of the key on the module, including the delegation rewards that were
+    /// not settled yet.
     pub fn get_stake_to_module(netuid: u16, key: &T::AccountId, module_key: &T::AccountId) -> u64 {
-        Self::get_stake_to_vector(netuid, key)
+        let stake = Self::get_stake_to_vector(netuid, key)
             .into_iter()
             .find(|(k, _)| k == module_key)
             .map(|(_, v)| v)
-            .unwrap_or(0)
+            .unwrap_or(0);
+
+        stake.saturating_add(Self::get_pending_delegation_rewards(
+            netuid, key, module_key,
+        ))
     }
 
     pub fn get_stake_to_vector(netuid: u16, key: &T::AccountId) -> BTreeMap<T::AccountId, u64> {
@@ -345,9 +595,25 @@ impl<T: Config> Pallet<T> {
         module_key: &T::AccountId,
         stake_from_vector: BTreeMap<T::AccountId, u64>,
     ) {
+        let previous = StakeFrom::<T>::decode_len(netuid, module_key).unwrap_or_default() as u32;
+        DelegationCount::<T>::mutate(netuid, |count| {
+            *count = count.saturating_sub(previous).saturating_add(stake_from_vector.len() as u32)
+        });
         StakeFrom::<T>::insert(netuid, module_key, stake_from_vector);
     }
 
+    /// Removes the delegations to the module and returns them.
+    pub(crate) fn take_stake_from_vector(
+        netuid: u16,
+        module_key: &T::AccountId,
+    ) -> BTreeMap<T::AccountId, u64> {
+        let stake_from_vector = StakeFrom::<T>::take(netuid, module_key);
+        DelegationCount::<T>::mutate(netuid, |count| {
+            *count = count.saturating_sub(stake_from_vector.len() as u32)
+        });
+        stake_from_vector
+    }
+
     pub fn get_stake_from_vector(
         netuid: u16,
         module_key: &T::AccountId,
@@ -365,6 +631,37 @@ impl<T: Config> Pallet<T> {
         module_key: &T::AccountId,
         amount: u64,
     ) -> bool {
+        Self::settle_delegation_rewards(netuid, key, module_key);
+        Self::add_stake_to_storage(netuid, key, module_key, amount)
+    }
+
+    // Increases the stake without settling the delegation rewards first.
+    pub(crate) fn add_stake_to_storage(
+        netuid: u16,
+        key: &T::AccountId,
+        module_key: &T::AccountId,
+        amount: u64,
+    ) -> bool {
+        Self::add_stake_to_vectors(netuid, key, module_key, amount);
+
+        Stake::<T>::mutate(netuid, module_key, |stake| {
+            *stake = stake.saturating_add(amount)
+        });
+        TotalStake::<T>::mutate(netuid, |total_stake| {
+            *total_stake = total_stake.saturating_add(amount)
+        });
+
+        true
+    }
+
+    // Adds the amount to the delegation of the key, leaving the stake of the module and the subnet
+    // as they are.
+    pub(crate) fn add_stake_to_vectors(
+        netuid: u16,
+        key: &T::AccountId,
+        module_key: &T::AccountId,
+        amount: u64,
+    ) {
         let mut stake_from_vector = Self::get_stake_from_vector(netuid, module_key);
         let found_key_in_vector = stake_from_vector.iter_mut().find(|(k, _)| *k == key);
         if let Some((_, v)) = found_key_in_vector {
@@ -384,15 +681,6 @@ impl<T: Config> Pallet<T> {
 
         Self::set_stake_to_vector(netuid, key, stake_to_vector);
         Self::set_stake_from_vector(netuid, module_key, stake_from_vector);
-
-        Stake::<T>::mutate(netuid, module_key, |stake| {
-            *stake = stake.saturating_add(amount)
-        });
-        TotalStake::<T>::mutate(netuid, |total_stake| {
-            *total_stake = total_stake.saturating_add(amount)
-        });
-
-        true
     }
 
     pub fn decrease_stake(
@@ -401,6 +689,8 @@ impl<T: Config> Pallet<T> {
         module_key: &T::AccountId,
         amount: u64,
     ) -> bool {
+        Self::settle_delegation_rewards(netuid, key, module_key);
+
         // FROM DELEGATE STAKE
         let mut stake_from_vector = Self::get_stake_from_vector(netuid, module_key);
         for (k, v) in stake_from_vector.iter_mut() {
@@ -423,6 +713,9 @@ impl<T: Config> Pallet<T> {
             }
         }
         stake_to_vector.retain(|_, v| *v != 0);
+        if !stake_to_vector.contains_key(module_key) {
+            Self::remove_delegation_checkpoint(netuid, key, module_key);
+        }
         Self::set_stake_to_vector(netuid, key, stake_to_vector);
 
         // --- 8. We add the balancer to the key. If the above fails we will not credit this key.
@@ -437,17 +730,63 @@ impl<T: Config> Pallet<T> {
 
     // Decreases the stake by the amount while decreasing other counters.
     pub fn remove_stake_from_storage(netuid: u16, module_key: &T::AccountId) {
+        // pending delegation rewards are paid out together with the stake, the rounding dust left
+        // after settling them goes to the module
+        Self::settle_all_delegation_rewards(netuid, module_key);
+        let dust = UnsettledDelegationRewards::<T>::take(netuid, module_key);
+        if dust > 0 {
+            Self::add_stake_to_vectors(netuid, module_key, module_key, dust);
+        }
+
+        // The stake is unbonded like removed stake. The part locked by conviction votes is queued
+        // until the locks expire, if that is later.
+        let current_block = Self::get_current_block_number();
+        let unbonding_block = current_block.saturating_add(UnbondingPeriod::<T>::get(netuid));
         let stake_from_vector = Self::get_stake_from_vector(netuid, module_key);
         for (delegate_key, delegate_stake_amount) in stake_from_vector.iter() {
+            let (locked, lock_expiry) =
+                Self::get_locked_part(delegate_key, netuid, *delegate_stake_amount);
             Self::decrease_stake(netuid, delegate_key, module_key, *delegate_stake_amount);
-            Self::add_balance_to_account(
-                delegate_key,
-                Self::u64_to_balance(*delegate_stake_amount).unwrap(),
-            );
+
+            let unlocked = delegate_stake_amount.saturating_sub(locked);
+            let parts = [
+                (unlocked, unbonding_block),
+                (locked, lock_expiry.max(unbonding_block)),
+            ];
+
+            let mut released = 0u64;
+            for (amount, unlock_block) in parts {
+                if amount == 0 {
+                    continue;
+                }
+                if unlock_block <= current_block {
+                    released = released.saturating_add(amount);
+                    continue;
+                }
+
+                let chunk = UnlockChunk {
+                    netuid,
+                    module_key: module_key.clone(),
+                    amount,
+                    unlock_block,
+                };
+                let unlock_block = Self::force_queue_unlock_chunk(delegate_key, chunk);
+                Self::deposit_event(Event::StakeUnbonding(
+                    delegate_key.clone(),
+                    module_key.clone(),
+                    amount,
+                    unlock_block,
+                ));
+            }
+
+            if released > 0 {
+                Self::add_balance_to_account(delegate_key, Self::u64_to_balance(released).unwrap());
+            }
         }
 
-        StakeFrom::<T>::remove(netuid, module_key);
+        Self::take_stake_from_vector(netuid, module_key);
         Stake::<T>::remove(netuid, module_key);
+        DelegationRewardPerShare::<T>::remove(netuid, module_key);
     }
 
     pub fn add_balance_to_account(key: &T::AccountId, amount: BalanceOf<T>) {
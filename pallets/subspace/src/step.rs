@@ -1,24 +1,57 @@
 use super::*;
-use crate::math::*;
-use frame_support::storage::with_storage_layer;
+use crate::{math::*, subnet::ConsensusMode};
+use frame_support::{storage::with_storage_layer, traits::Get, weights::Weight};
 use sp_arithmetic::per_things::Percent;
 use sp_std::vec;
 use substrate_fixed::types::{I110F18, I32F32, I64F64};
 
+pub mod scheduler;
+pub mod simulation;
 pub mod yuma;
 
+/// The consensus outputs of a subnet at the end of an epoch, indexed by uid. Uids are reused
+/// by later registrations, `keys` tells which module each entry belonged to.
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct EpochRecord<T: Config> {
+    pub block: u64,
+    pub keys: Vec<T::AccountId>,
+    pub incentive: Vec<u16>,
+    pub dividends: Vec<u16>,
+    pub emission: Vec<u64>,
+    pub trust: Vec<u16>,
+    pub rank: Vec<u16>,
+    pub consensus: Vec<u16>,
+}
+
 impl<T: Config> Pallet<T> {
-    pub fn block_step() {
+    /// Ticks every subnet and runs the epochs scheduled for this block. Returns the weight it
+    /// consumed.
+    pub fn block_step() -> Weight {
         let block_number: u64 = Self::get_current_block_number();
         log::debug!("stepping block {block_number:?}");
 
         RegistrationsPerBlock::<T>::mutate(|val: &mut u16| *val = 0);
+        let mut weight = T::DbWeight::get().reads_writes(3, 2);
 
         // Execute proposals if any should be executed, this is done every 100 blocks.
         if block_number % 100 == 0 {
-            Self::resolve_proposals(block_number);
+            weight = weight.saturating_add(Self::resolve_proposals(block_number));
+        }
+
+        // Execute the approved proposals whose enactment is due.
+        weight = weight.saturating_add(Self::enact_proposals(block_number));
+
+        // Pay out the treasury streams and drop the expired curator motions, on the same schedule
+        // proposals are resolved on.
+        if block_number % 100 == 0 {
+            weight = weight.saturating_add(Self::release_treasury_streams(block_number));
+            weight = weight.saturating_add(Self::prune_curator_motions(block_number));
         }
 
+        // Remove the finalized proposals whose retention ended.
+        weight = weight.saturating_add(Self::prune_proposals(block_number));
+
         // -- Adjust registrations parameters --
 
         let total_stake = Self::total_stake() as u128;
@@ -26,6 +59,17 @@ impl<T: Config> Pallet<T> {
 
         log::debug!("ticking subnets, total stake: {total_stake}, stake threshold: {subnet_stake_threshold:?}");
 
+        // The emission the allocation leaves to no subnet goes to the treasury, at the same rate
+        // the subnets are paid at.
+        let unallocated_emission = Self::get_unallocated_emission(subnet_stake_threshold);
+
+        // every subnet adjusts its registrations and queues its emission, which is computed from
+        // the shares of all the subnets
+        let subnets = Self::num_subnets() as u32;
+        let subnet_weight = T::WeightInfo::adjust_registration()
+            .saturating_add(T::WeightInfo::queue_subnet_emission(subnets));
+
+        let mut due = Vec::new();
         for (netuid, tempo) in Tempo::<T>::iter() {
             // Query the target interval
             let target_registrations_interval = TargetRegistrationsInterval::<T>::get(netuid);
@@ -49,54 +93,92 @@ impl<T: Config> Pallet<T> {
                 *queued += new_queued_emission;
                 *queued
             });
+            TotalIssued::<T>::mutate(|issued| *issued = issued.saturating_add(new_queued_emission));
             log::trace!("subnet {netuid} total pending emission: {emission_to_drain}, increased {new_queued_emission}");
+            weight = weight.saturating_add(subnet_weight);
 
-            if Self::blocks_until_next_epoch(netuid, tempo, block_number) > 0 {
-                continue;
+            if Self::blocks_until_next_epoch(netuid, tempo, block_number) == 0 {
+                due.push(netuid);
             }
+        }
 
-            log::trace!("running epoch for subnet {netuid}");
+        if unallocated_emission > 0 {
+            GlobalDaoTreasury::<T>::mutate(|treasury| {
+                *treasury = treasury.saturating_add(unallocated_emission)
+            });
+            TotalIssued::<T>::mutate(|issued| {
+                *issued = issued.saturating_add(unallocated_emission)
+            });
+        }
+        weight = weight.saturating_add(T::DbWeight::get().reads_writes(4, 2));
 
-            // Clearing `set_weight` rate limiter values.
-            let _ = SetWeightCallsPerEpoch::<T>::clear_prefix(netuid, u32::MAX, None);
+        for (netuid, epoch_weight) in Self::schedule_epochs(due) {
+            Self::run_epoch(netuid, block_number, total_stake, subnet_stake_threshold);
+            weight = weight.saturating_add(epoch_weight);
+        }
 
-            let has_enough_stake_for_yuma = || {
-                let subnet_stake = Self::get_total_subnet_stake(netuid) as u128;
+        weight
+    }
 
-                // simplify this to just checking if there are pending emission
-                if total_stake == 0 {
-                    false
-                } else {
-                    let subnet_stake_percent = (subnet_stake * 100) / total_stake;
-                    subnet_stake_threshold <= Percent::from_parts(subnet_stake_percent as u8)
-                }
-            };
-
-            if netuid == 0 {
-                Self::linear_epoch(netuid, emission_to_drain)
-            } else if has_enough_stake_for_yuma() {
-                let res = with_storage_layer(|| {
-                    let Err(err) = yuma::YumaCalc::<T>::new(netuid, emission_to_drain).run() else {
-                        return Ok(());
-                    };
+    fn run_epoch(
+        netuid: u16,
+        block_number: u64,
+        total_stake: u128,
+        subnet_stake_threshold: Percent,
+    ) {
+        let emission_to_drain = PendingEmission::<T>::get(netuid);
+
+        log::trace!("running epoch for subnet {netuid}");
+
+        // Clearing `set_weight` rate limiter values.
+        let _ = SetWeightCallsPerEpoch::<T>::clear_prefix(netuid, u32::MAX, None);
+
+        // Delegation fee increases only become active at the start of an epoch.
+        Self::activate_delegation_fee_increases(netuid, block_number);
 
-                    log::error!(
-                        "\
+        // Commits that were not revealed in time are dropped.
+        Self::expire_weight_commits(netuid, block_number);
+
+        let has_enough_stake_for_yuma = || {
+            let subnet_stake = Self::get_total_subnet_stake(netuid) as u128;
+
+            // simplify this to just checking if there are pending emission
+            if total_stake == 0 {
+                false
+            } else {
+                let subnet_stake_percent = (subnet_stake * 100) / total_stake;
+                subnet_stake_threshold <= Percent::from_parts(subnet_stake_percent as u8)
+            }
+        };
+
+        let consensus_mode = ConsensusModeSubnet::<T>::get(netuid);
+        if consensus_mode == ConsensusMode::Linear {
+            Self::linear_epoch(netuid, emission_to_drain);
+            Self::record_epoch_history(netuid, block_number);
+        } else if has_enough_stake_for_yuma() {
+            let res = with_storage_layer(|| {
+                let Err(err) = yuma::YumaCalc::<T>::new(netuid, emission_to_drain).run() else {
+                    return Ok(());
+                };
+
+                log::error!(
+                    "\
 failed to run yuma consensus algorithm: {err:?}, skipping this block. \
 {emission_to_drain} tokens will be emitted on the next epoch.\
 "
-                    );
+                );
 
-                    Err("yuma failed")
-                });
+                Err("yuma failed")
+            });
 
-                if res.is_err() {
-                    continue;
-                }
+            if res.is_err() {
+                return;
             }
 
-            PendingEmission::<T>::insert(netuid, 0);
+            Self::record_epoch_history(netuid, block_number);
         }
+
+        PendingEmission::<T>::insert(netuid, 0);
     }
 
     /// This function acts as the main function of the entire blockchain reward distribution.
@@ -156,6 +238,23 @@ failed to run yuma consensus algorithm: {err:?}, skipping this block. \
         let mut incentive: Vec<I32F32> =
             Self::compute_incentive(&weights, &stake, &uid_key_tuples, n);
 
+        // CURATOR
+        // on the general subnet, part of the incentive follows the whitelist recommendations
+        let curator_ratio = global_params.curator_weight_ratio;
+        if netuid == 0 && curator_ratio.deconstruct() > 0 {
+            if let Some(curator_incentive) = Self::compute_curator_incentive(&uid_key_tuples, n) {
+                let curator_share =
+                    I32F32::from_num(curator_ratio.deconstruct()) / I32F32::from_num(100);
+                let validator_share = I32F32::from_num(1).saturating_sub(curator_share);
+
+                incentive = incentive
+                    .iter()
+                    .zip(curator_incentive.iter())
+                    .map(|(inc, cur)| (inc * validator_share) + (cur * curator_share))
+                    .collect();
+            }
+        }
+
         // TRUST
         // trust that acts as a multiplier for the incentive
         let trust_ratio: u16 = Self::get_trust_ratio(netuid);
@@ -254,26 +353,10 @@ failed to run yuma consensus algorithm: {err:?}, skipping this block. \
             emission[*module_uid as usize] = owner_emission_incentive + owner_dividends_emission;
 
             if owner_dividends_emission > 0 {
-                let ownership_vector: Vec<(T::AccountId, I64F64)> =
-                    Self::get_ownership_ratios(netuid, module_key);
-
-                let delegation_fee = Self::get_delegation_fee(netuid, module_key);
-
-                let total_owner_dividends_emission: u64 = owner_dividends_emission;
-                for (delegate_key, delegate_ratio) in ownership_vector.iter() {
-                    if delegate_key == module_key {
-                        continue;
-                    }
-
-                    let dividends_from_delegate: u64 =
-                        (I64F64::from_num(total_owner_dividends_emission) * *delegate_ratio)
-                            .to_num::<u64>();
-                    let to_module: u64 = delegation_fee.mul_floor(dividends_from_delegate);
-                    let to_delegate: u64 = dividends_from_delegate.saturating_sub(to_module);
-                    Self::increase_stake(netuid, delegate_key, module_key, to_delegate);
-                    emitted = emitted.saturating_add(to_delegate);
-                    owner_dividends_emission = owner_dividends_emission.saturating_sub(to_delegate);
-                }
+                let to_delegators =
+                    Self::reward_delegators(netuid, module_key, owner_dividends_emission);
+                emitted = emitted.saturating_add(to_delegators);
+                owner_dividends_emission = owner_dividends_emission.saturating_sub(to_delegators);
             }
 
             let owner_emission: u64 = owner_emission_incentive + owner_dividends_emission;
@@ -464,6 +547,25 @@ failed to run yuma consensus algorithm: {err:?}, skipping this block. \
         incentive
     }
 
+    /// Returns the weights recommended in the whitelist for the modules, normalized, or `None`
+    /// if no module has a recommended weight.
+    fn compute_curator_incentive(
+        uid_key_tuples: &[(u16, T::AccountId)],
+        n: u16,
+    ) -> Option<Vec<I32F32>> {
+        let mut curator_incentive: Vec<I32F32> = vec![I32F32::from_num(0); n as usize];
+        for (uid, key) in uid_key_tuples.iter() {
+            curator_incentive[*uid as usize] = I32F32::from_num(LegitWhitelist::<T>::get(key));
+        }
+
+        if is_zero(&curator_incentive) {
+            return None;
+        }
+
+        inplace_normalize(&mut curator_incentive);
+        Some(curator_incentive)
+    }
+
     fn get_current_weight_age(last_update_vector: &[u64], current_block: u64, uid_i: u16) -> u64 {
         current_block.saturating_sub(last_update_vector[uid_i as usize])
     }
@@ -582,12 +684,61 @@ failed to run yuma consensus algorithm: {err:?}, skipping this block. \
         block_at_registration
     }
 
+    /// Keeps the outputs of the epoch that just ran, dropping the oldest record once the
+    /// subnet's history is full.
+    fn record_epoch_history(netuid: u16, block: u64) {
+        if MaxEpochHistory::<T>::get(netuid) == 0 {
+            return;
+        }
+
+        let epoch = EpochsRecorded::<T>::mutate(netuid, |recorded| {
+            let epoch = *recorded;
+            *recorded = recorded.saturating_add(1);
+            epoch
+        });
+
+        let record = EpochRecord::<T> {
+            block,
+            keys: Self::get_keys(netuid),
+            incentive: Incentive::<T>::get(netuid),
+            dividends: Dividends::<T>::get(netuid),
+            emission: Emission::<T>::get(netuid),
+            trust: Trust::<T>::get(netuid),
+            rank: Rank::<T>::get(netuid),
+            consensus: Consensus::<T>::get(netuid),
+        };
+        EpochHistory::<T>::insert(netuid, epoch, record);
+
+        Self::prune_epoch_history(netuid);
+    }
+
+    /// Removes the records that no longer fit into the subnet's history.
+    pub fn prune_epoch_history(netuid: u16) {
+        let max_history = MaxEpochHistory::<T>::get(netuid) as u64;
+        let oldest_kept = EpochsRecorded::<T>::get(netuid).saturating_sub(max_history);
+
+        let expired: Vec<u64> = EpochHistory::<T>::iter_key_prefix(netuid)
+            .filter(|epoch| *epoch < oldest_kept)
+            .collect();
+        for epoch in expired {
+            EpochHistory::<T>::remove(netuid, epoch);
+        }
+    }
+
+    /// Returns the recorded epochs of a subnet, oldest first.
+    pub fn get_epoch_history(netuid: u16) -> Vec<EpochRecord<T>> {
+        let mut history: Vec<(u64, EpochRecord<T>)> =
+            EpochHistory::<T>::iter_prefix(netuid).collect();
+        history.sort_by_key(|(epoch, _)| *epoch);
+        history.into_iter().map(|(_, record)| record).collect()
+    }
+
     pub fn blocks_until_next_epoch(netuid: u16, tempo: u16, block_number: u64) -> u64 {
         // in this case network never runs
         if tempo == 0 {
             return 1000;
         }
-        (block_number + netuid as u64) % (tempo as u64)
+        (block_number + Self::get_epoch_offset(netuid)) % (tempo as u64)
     }
 
     pub fn get_ownership_ratios(
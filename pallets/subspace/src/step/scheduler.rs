@@ -0,0 +1,108 @@
+use crate::{
+    subnet::ConsensusMode, Config, ConsensusModeSubnet, DeferredEpochs, DelegationCount,
+    EpochOffset, MaxEpochWeightPerBlock, Pallet, Tempo, WeightInfo,
+};
+use frame_support::weights::Weight;
+use sp_std::vec::Vec;
+
+// Subnets run their epoch when `(block + offset) % tempo == 0`. The offset is picked when the
+// subnet is created so that epochs are spread over the blocks of the tempo, and the epochs that
+// still end up on the same block are capped by `MaxEpochWeightPerBlock`. The ones that do not fit
+// are deferred to the following blocks, keeping their pending emission.
+impl<T: Config> Pallet<T> {
+    /// Returns the epoch offset of the subnet. Subnets created before offsets were assigned use
+    /// their netuid.
+    pub fn get_epoch_offset(netuid: u16) -> u64 {
+        EpochOffset::<T>::get(netuid).unwrap_or(netuid) as u64
+    }
+
+    /// Picks the offset in `0..tempo` that collides with the least modules of the other subnets,
+    /// preferring the lowest one.
+    pub fn assign_epoch_offset(netuid: u16, tempo: u16) {
+        if tempo == 0 {
+            EpochOffset::<T>::insert(netuid, 0);
+            return;
+        }
+
+        let others: Vec<_> = Tempo::<T>::iter()
+            .filter(|(other, tempo)| *other != netuid && *tempo > 0)
+            .map(|(other, other_tempo)| {
+                let load = Self::get_max_allowed_uids(other).max(1) as u64;
+                (Self::get_epoch_offset(other), other_tempo as u64, load)
+            })
+            .collect();
+
+        let tempo = tempo as u64;
+        let offset = (0..tempo)
+            .min_by_key(|offset| {
+                // two subnets share a block eventually iff their offsets are congruent
+                // modulo the gcd of their tempos
+                others
+                    .iter()
+                    .filter(|(other_offset, other_tempo, _)| {
+                        let gcd = gcd(tempo, *other_tempo);
+                        offset % gcd == other_offset % gcd
+                    })
+                    .map(|(_, _, load)| load)
+                    .sum::<u64>()
+            })
+            .unwrap_or_default();
+
+        EpochOffset::<T>::insert(netuid, offset as u16);
+    }
+
+    /// Returns the weight of running the epoch of the subnet, from the benchmark of its
+    /// consensus mode, its module count and the number of delegations to its modules.
+    pub fn estimate_epoch_weight(netuid: u16) -> Weight {
+        let modules = Self::get_subnet_n(netuid) as u32;
+        let delegations = DelegationCount::<T>::get(netuid);
+
+        match ConsensusModeSubnet::<T>::get(netuid) {
+            ConsensusMode::Linear => T::WeightInfo::linear_epoch(modules, delegations),
+            ConsensusMode::Yuma => T::WeightInfo::yuma_epoch(modules, delegations),
+        }
+    }
+
+    /// Splits the subnets whose epoch is due at this block, after the ones deferred earlier, into
+    /// those that fit into `MaxEpochWeightPerBlock` and those that have to wait. The first epoch
+    /// always runs, so a subnet larger than the limit is not deferred forever.
+    pub fn schedule_epochs(due: Vec<u16>) -> Vec<(u16, Weight)> {
+        let mut queue = DeferredEpochs::<T>::get();
+        for netuid in due {
+            if !queue.contains(&netuid) {
+                queue.push(netuid);
+            }
+        }
+
+        let limit = MaxEpochWeightPerBlock::<T>::get();
+        let mut used = Weight::zero();
+        let mut scheduled = Vec::new();
+        let mut deferred = Vec::new();
+
+        for netuid in queue {
+            if !Self::if_subnet_exist(netuid) {
+                continue;
+            }
+
+            let weight = Self::estimate_epoch_weight(netuid);
+            if !scheduled.is_empty() && used.saturating_add(weight).any_gt(limit) {
+                log::trace!("deferring epoch of subnet {netuid}");
+                deferred.push(netuid);
+                continue;
+            }
+
+            used = used.saturating_add(weight);
+            scheduled.push((netuid, weight));
+        }
+
+        DeferredEpochs::<T>::put(deferred);
+        scheduled
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
@@ -0,0 +1,167 @@
+use super::yuma::YumaCalc;
+use crate::{
+    subnet::ConsensusMode, Active, Bonds, BondsMovingAverage, Config, Consensus,
+    ConsensusModeSubnet, DelegationRewardPerShare, Dividends, Error, Incentive, IncentiveRatio,
+    Kappa, MaxAllowedValidators, Pallet, PendingEmission, PruningScores, Rank, StakeFrom, Trust,
+};
+use frame_support::{
+    ensure,
+    storage::{with_transaction, TransactionOutcome},
+};
+use sp_runtime::DispatchError;
+use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+
+/// Parameters to use instead of the stored ones when simulating an epoch. Fields left as `None`
+/// keep their current value.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EpochOverrides {
+    /// Amount to distribute, defaults to the subnet's pending emission.
+    pub emission: Option<u64>,
+    pub kappa: Option<u16>,
+    pub bonds_ma: Option<u64>,
+    pub max_allowed_validators: Option<u16>,
+    pub incentive_ratio: Option<u16>,
+}
+
+/// Everything an epoch computed for a subnet. Vectors are indexed by uid and are left empty when
+/// the subnet's consensus mode does not produce them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpochSimulation<T: Config> {
+    pub active_stake: Vec<u16>,
+    pub consensus: Vec<u16>,
+    pub ranks: Vec<u16>,
+    pub trust: Vec<u16>,
+    pub incentives: Vec<u16>,
+    pub dividends: Vec<u16>,
+    pub bonds: Vec<Vec<(u16, u16)>>,
+    pub pruning_scores: Vec<u16>,
+    /// map of module key to the amount each account received through it
+    pub emissions: BTreeMap<T::AccountId, BTreeMap<T::AccountId, u64>>,
+}
+
+impl<T: Config> Pallet<T> {
+    /// Runs the epoch of `netuid` as if it was due now and returns its results. All storage
+    /// changes, including the overrides, are rolled back afterwards.
+    pub fn simulate_epoch(
+        netuid: u16,
+        overrides: EpochOverrides,
+    ) -> Result<EpochSimulation<T>, DispatchError> {
+        ensure!(
+            Self::if_subnet_exist(netuid),
+            Error::<T>::NetworkDoesNotExist
+        );
+
+        with_transaction(|| {
+            TransactionOutcome::Rollback(Self::run_simulated_epoch(netuid, overrides))
+        })
+    }
+
+    fn run_simulated_epoch(
+        netuid: u16,
+        overrides: EpochOverrides,
+    ) -> Result<EpochSimulation<T>, DispatchError> {
+        if let Some(kappa) = overrides.kappa {
+            Kappa::<T>::put(kappa);
+        }
+        if let Some(bonds_ma) = overrides.bonds_ma {
+            BondsMovingAverage::<T>::insert(netuid, bonds_ma);
+        }
+        if let Some(max_allowed_validators) = overrides.max_allowed_validators {
+            MaxAllowedValidators::<T>::insert(netuid, Some(max_allowed_validators));
+        }
+        if let Some(incentive_ratio) = overrides.incentive_ratio {
+            IncentiveRatio::<T>::insert(netuid, incentive_ratio);
+        }
+
+        let emission = overrides.emission.unwrap_or_else(|| PendingEmission::<T>::get(netuid));
+
+        // Clear the previous epoch's results, so whatever is left afterwards was produced here.
+        Active::<T>::remove(netuid);
+        Consensus::<T>::remove(netuid);
+        Rank::<T>::remove(netuid);
+        Trust::<T>::remove(netuid);
+        Incentive::<T>::remove(netuid);
+        Dividends::<T>::remove(netuid);
+        PruningScores::<T>::remove(netuid);
+
+        let reward_per_share_before: BTreeMap<_, _> =
+            DelegationRewardPerShare::<T>::iter_prefix(netuid).collect();
+
+        let consensus_mode = ConsensusModeSubnet::<T>::get(netuid);
+        let (active_stake, bonds, mut emissions) = if consensus_mode == ConsensusMode::Linear {
+            // The linear epoch does not report what it distributed, so it is taken from the
+            // stake difference.
+            let stake_before: BTreeMap<_, _> = StakeFrom::<T>::iter_prefix(netuid).collect();
+            Self::linear_epoch(netuid, emission);
+
+            let emissions = StakeFrom::<T>::iter_prefix(netuid)
+                .filter_map(|(module_key, stake_from)| {
+                    let before = stake_before.get(&module_key);
+                    let received: BTreeMap<_, _> = stake_from
+                        .into_iter()
+                        .filter_map(|(key, amount)| {
+                            let before = before.and_then(|b| b.get(&key)).copied();
+                            let amount = amount.saturating_sub(before.unwrap_or_default());
+                            (amount > 0).then_some((key, amount))
+                        })
+                        .collect();
+                    (!received.is_empty()).then_some((module_key, received))
+                })
+                .collect();
+
+            (Vec::new(), Vec::new(), emissions)
+        } else {
+            let output = YumaCalc::<T>::new(netuid, emission).run_detailed().map_err(|err| {
+                log::debug!("simulated yuma failed for subnet {netuid}: {err:?}");
+                DispatchError::Other("yuma failed")
+            })?;
+
+            let bonds = (0..Self::get_subnet_n(netuid))
+                .map(|uid| Bonds::<T>::get(netuid, uid))
+                .collect();
+            let emissions = output
+                .emissions
+                .into_iter()
+                .map(|(module_key, received)| {
+                    let received = received.into_iter().map(|(key, v)| (key.0, v)).collect();
+                    (module_key.0, received)
+                })
+                .collect();
+
+            (output.active_stake, bonds, emissions)
+        };
+
+        // Delegator rewards are only accrued by the epoch, they are added as if settled.
+        for (module_key, reward_per_share) in DelegationRewardPerShare::<T>::iter_prefix(netuid) {
+            let before = reward_per_share_before.get(&module_key).copied().unwrap_or_default();
+            let accrued = reward_per_share.wrapping_sub(before);
+            if accrued == 0 {
+                continue;
+            }
+
+            for (key, stake) in StakeFrom::<T>::get(netuid, &module_key) {
+                let amount = Self::reward_for_stake(stake, accrued);
+                if key == module_key || amount == 0 {
+                    continue;
+                }
+
+                let received: &mut BTreeMap<T::AccountId, u64> =
+                    emissions.entry(module_key.clone()).or_default();
+                let entry = received.entry(key).or_default();
+                *entry = entry.saturating_add(amount);
+            }
+        }
+
+        Ok(EpochSimulation {
+            active_stake,
+            consensus: Consensus::<T>::get(netuid),
+            ranks: Rank::<T>::get(netuid),
+            trust: Trust::<T>::get(netuid),
+            incentives: Incentive::<T>::get(netuid),
+            dividends: Dividends::<T>::get(netuid),
+            bonds,
+            pruning_scores: PruningScores::<T>::get(netuid),
+            emissions,
+        })
+    }
+}
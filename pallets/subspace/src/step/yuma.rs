@@ -13,6 +13,14 @@ use sp_std::vec::Vec;
 
 pub type EmissionMap<T> = BTreeMap<ModuleKey<T>, BTreeMap<AccountKey<T>, u64>>;
 
+/// Values produced by a YUMA run that are not kept in storage.
+pub struct YumaOutput<T: Config> {
+    /// Normalized stake of the active, permitted validators.
+    pub active_stake: Vec<u16>,
+    /// Emissions distributed per module key.
+    pub emissions: EmissionMap<T>,
+}
+
 pub struct YumaCalc<T: Config> {
     /// The amount of modules on the subnet
     module_count: u16,
@@ -71,6 +79,12 @@ impl<T: Config> YumaCalc<T> {
     /// Runs the YUMA consensus calculation on the network and distributes the emissions. Returns a
     /// map of emissions distributed per module key.
     pub fn run(self) -> Result<EmissionMap<T>, YumaError> {
+        self.run_detailed().map(|output| output.emissions)
+    }
+
+    /// Same as [`YumaCalc::run`], but also returns the intermediate values that are not
+    /// persisted.
+    pub fn run_detailed(self) -> Result<YumaOutput<T>, YumaError> {
         log::debug!(
             "running yuma for netuid {}, will emit {} modules and {} to founder",
             self.netuid,
@@ -132,6 +146,9 @@ impl<T: Config> YumaCalc<T> {
             combined_emissions,
         } = self.compute_emissions(&stake, &active_stake, &incentives, &dividends);
 
+        let active_stake: Vec<_> =
+            active_stake.into_inner().into_iter().map(fixed_proportion_to_u16).collect();
+
         let consensus: Vec<_> =
             consensus.into_inner().into_iter().map(fixed_proportion_to_u16).collect();
         let incentives: Vec<_> =
@@ -199,7 +216,10 @@ impl<T: Config> YumaCalc<T> {
             self.netuid
         );
 
-        distribute_emissions
+        Ok(YumaOutput {
+            active_stake,
+            emissions: distribute_emissions?,
+        })
     }
 
     fn distribute_emissions(
@@ -218,6 +238,17 @@ impl<T: Config> YumaCalc<T> {
         }
 
         for (module_key, server_emission, mut validator_emission) in result {
+            // Delegators are credited through the reward accumulator of the module, their
+            // delegations only change once the rewards are settled.
+            if validator_emission > 0 {
+                let to_delegators =
+                    Pallet::<T>::reward_delegators(self.netuid, &module_key.0, validator_emission);
+                validator_emission = validator_emission
+                    .checked_sub(to_delegators)
+                    .ok_or("more validator emissions were done than expected")?;
+                emitted += to_delegators;
+            }
+
             let mut increase_stake = |account_key: &AccountKey<T>, amount: u64| {
                 Pallet::<T>::increase_stake(self.netuid, &account_key.0, &module_key.0, amount);
                 *emissions
@@ -228,31 +259,6 @@ impl<T: Config> YumaCalc<T> {
                 emitted += amount;
             };
 
-            if validator_emission > 0 {
-                let ownership_vector =
-                    Pallet::<T>::get_ownership_ratios(self.netuid, &module_key.0);
-                let delegation_fee = Pallet::<T>::get_delegation_fee(self.netuid, &module_key.0);
-
-                let total_validator_emission = I64F64::from_num(validator_emission);
-                for (delegate_key, delegate_ratio) in ownership_vector {
-                    if delegate_key == module_key.0 {
-                        continue;
-                    }
-
-                    let dividends_from_delegate: u64 =
-                        (total_validator_emission * delegate_ratio).to_num::<u64>();
-
-                    let to_module: u64 = delegation_fee.mul_floor(dividends_from_delegate);
-                    let to_delegate: u64 = dividends_from_delegate.saturating_sub(to_module);
-
-                    increase_stake(&AccountKey(delegate_key), to_delegate);
-
-                    validator_emission = validator_emission
-                        .checked_sub(to_delegate)
-                        .ok_or("more validator emissions were done than expected")?;
-                }
-            }
-
             let mut remaining_emission = server_emission + validator_emission;
             if remaining_emission > 0 {
                 let profit_share_emissions =
@@ -10,6 +10,13 @@ use sp_runtime::DispatchError;
 use sp_std::vec::Vec;
 use substrate_fixed::types::I64F64;
 
+/// The algorithm used to distribute the emission of a subnet on every epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TypeInfo, Decode, Encode)]
+pub enum ConsensusMode {
+    Linear = 0,
+    Yuma = 1,
+}
+
 #[derive(Debug)]
 pub struct SubnetChangeset<T: Config> {
     params: SubnetParams<T>,
@@ -43,6 +50,7 @@ impl<T: Config> SubnetChangeset<T> {
         TrustRatio::<T>::insert(netuid, self.params.trust_ratio);
         IncentiveRatio::<T>::insert(netuid, self.params.incentive_ratio);
         VoteModeSubnet::<T>::insert(netuid, self.params.vote_mode);
+        ConsensusModeSubnet::<T>::insert(netuid, self.params.consensus_mode);
         BondsMovingAverage::<T>::insert(netuid, self.params.bonds_ma);
         TargetRegistrationsInterval::<T>::insert(netuid, self.params.target_registrations_interval);
         TargetRegistrationsPerInterval::<T>::insert(
@@ -53,6 +61,19 @@ impl<T: Config> SubnetChangeset<T> {
             netuid,
             self.params.max_registrations_per_interval,
         );
+        UnbondingPeriod::<T>::insert(netuid, self.params.unbonding_period);
+        WeightsRevealWindow::<T>::insert(netuid, self.params.weights_reveal_window);
+        MaxEpochHistory::<T>::insert(netuid, self.params.max_epoch_history);
+        Pallet::<T>::prune_epoch_history(netuid);
+
+        // Plain weights set before switching to commit-reveal must not leak into consensus.
+        if self.params.use_weights_commit_reveal && !UseWeightsCommitReveal::<T>::get(netuid) {
+            let _ = Weights::<T>::clear_prefix(netuid, u32::MAX, None);
+        }
+        if !self.params.use_weights_commit_reveal {
+            let _ = WeightCommits::<T>::clear_prefix(netuid, u32::MAX, None);
+        }
+        UseWeightsCommitReveal::<T>::insert(netuid, self.params.use_weights_commit_reveal);
 
         if self.params.maximum_set_weight_calls_per_epoch == 0 {
             MaximumSetWeightCallsPerEpoch::<T>::remove(netuid);
@@ -121,6 +142,21 @@ impl<T: Config> SubnetChangeset<T> {
             Error::<T>::InvalidMaxAllowedWeights
         );
 
+        ensure!(
+            params.unbonding_period <= staking::MAX_UNBONDING_PERIOD,
+            Error::<T>::InvalidUnbondingPeriod
+        );
+
+        ensure!(
+            params.weights_reveal_window > 0,
+            Error::<T>::InvalidWeightsRevealWindow
+        );
+
+        ensure!(
+            params.max_epoch_history <= 100,
+            Error::<T>::InvalidMaxEpochHistory
+        );
+
         match Pallet::<T>::get_netuid_for_name(&params.name) {
             Some(id) if netuid.is_some_and(|netuid| netuid == id) => { /* subnet kept same name */ }
             Some(_) => return Err(Error::<T>::SubnetNameAlreadyExists.into()),
@@ -192,6 +228,11 @@ impl<T: Config> Pallet<T> {
             target_registrations_interval: TargetRegistrationsInterval::<T>::get(netuid),
             target_registrations_per_interval: TargetRegistrationsPerInterval::<T>::get(netuid),
             max_registrations_per_interval: MaxRegistrationsPerInterval::<T>::get(netuid),
+            unbonding_period: UnbondingPeriod::<T>::get(netuid),
+            use_weights_commit_reveal: UseWeightsCommitReveal::<T>::get(netuid),
+            weights_reveal_window: WeightsRevealWindow::<T>::get(netuid),
+            consensus_mode: ConsensusModeSubnet::<T>::get(netuid),
+            max_epoch_history: MaxEpochHistory::<T>::get(netuid),
         }
     }
 
@@ -245,26 +286,11 @@ impl<T: Config> Pallet<T> {
         UnitEmission::<T>::put(unit_emission)
     }
 
-    // Returns the total amount of stake in the staking table.
-    // TODO: refactor the halving logic, it's not correct.
+    // Returns the emission of the current block, following the emission schedule from the
+    // amount issued so far.
     pub fn get_total_emission_per_block() -> u64 {
-        let total_stake: u64 = Self::total_stake();
-        let unit_emission: u64 = UnitEmission::<T>::get();
-        let mut emission_per_block: u64 = unit_emission; // assuming 8 second block times
-        let halving_total_stake_checkpoints: Vec<u64> =
-            [10_000_000, 20_000_000, 30_000_000, 40_000_000]
-                .iter()
-                .map(|x| x * unit_emission)
-                .collect();
-        for (i, having_stake) in halving_total_stake_checkpoints.iter().enumerate() {
-            let halving_factor: u64 = 2u64.pow((i) as u32);
-            if total_stake < *having_stake {
-                emission_per_block /= halving_factor;
-                break;
-            }
-        }
-
-        emission_per_block
+        let unit_emission: u64 = UnitEmission::<T>::get(); // assuming 8 second block times
+        Self::get_emission_schedule().emission_per_block(unit_emission, TotalIssued::<T>::get())
     }
 
     /// Empties out all:
@@ -288,74 +314,31 @@ impl<T: Config> Pallet<T> {
     }
 
     pub fn calculate_network_emission(netuid: u16, subnet_stake_threshold: Percent) -> u64 {
-        let subnet_stake: I64F64 = I64F64::from_num(Self::get_total_subnet_stake(netuid));
-        let total_stake: I64F64 = Self::adjust_total_stake(subnet_stake_threshold);
+        let shares = Self::get_subnet_emission_shares(subnet_stake_threshold);
 
         log::trace!(
-            "calculating subnet emission {netuid} with stake {subnet_stake}, \
-total stake {total_stake}, \
+            "calculating subnet emission {netuid} with shares {shares:?}, \
 threshold {subnet_stake_threshold:?}"
         );
 
-        let subnet_ratio = if total_stake > I64F64::from_num(0) {
-            subnet_stake / total_stake
-        } else {
-            I64F64::from_num(0)
-        };
-
-        // Convert subnet_stake_threshold from % to I64F64
-        let subnet_stake_threshold_i64f64 =
-            I64F64::from_num(subnet_stake_threshold.deconstruct()) / I64F64::from_num(100);
-
-        // Check if subnet_ratio meets the subnet_stake_threshold,
-        // or netuid is not the general subnet
-        if subnet_ratio < subnet_stake_threshold_i64f64 && netuid != 0 {
-            // Return early if the threshold is not met,
-            // this prevents emission gapping, of subnets that don't meet emission threshold
+        // Subnets below the threshold are left out of the shares,
+        // this prevents emission gapping, of subnets that don't meet emission threshold
+        let Some(subnet_share) = shares.get(&netuid).copied() else {
             Self::deactivate_subnet(netuid);
             log::trace!("subnet {netuid} is not eligible for yuma consensus");
             return 0;
-        }
+        };
 
         let total_emission_per_block: u64 = Self::get_total_emission_per_block();
 
         let token_emission: u64 =
-            (subnet_ratio * I64F64::from_num(total_emission_per_block)).to_num::<u64>();
+            (subnet_share * I64F64::from_num(total_emission_per_block)).to_num::<u64>();
 
         SubnetEmission::<T>::insert(netuid, token_emission);
 
         token_emission
     }
 
-    // This is the total stake of the network without subnets that can not get emission
-    // TODO: could be optimized
-    pub fn adjust_total_stake(subnet_stake_threshold: Percent) -> I64F64 {
-        let total_global_stake = I64F64::from_num(Self::total_stake());
-        if total_global_stake == 0 {
-            return I64F64::from_num(0);
-        }
-
-        let mut total_stake = I64F64::from_num(0);
-        let subnet_stake_threshold_i64f64 =
-            I64F64::from_num(subnet_stake_threshold.deconstruct()) / I64F64::from_num(100);
-        // Iterate over all subnets
-        for netuid in N::<T>::iter_keys() {
-            let subnet_stake: I64F64 = I64F64::from_num(Self::get_total_subnet_stake(netuid));
-            if subnet_stake == 0 {
-                continue;
-            }
-            let subnet_ratio = subnet_stake / total_global_stake;
-            // Check if subnet_ratio meets the subnet_stake_threshold,
-            // or the netuid is the general subnet
-            if subnet_ratio >= subnet_stake_threshold_i64f64 || netuid == 0 {
-                // Add subnet_stake to total_stake if it meets the threshold
-                total_stake += subnet_stake;
-            }
-        }
-
-        total_stake
-    }
-
     pub fn add_subnet(
         changeset: SubnetChangeset<T>,
         netuid: Option<u16>,
@@ -365,8 +348,15 @@ threshold {subnet_stake_threshold:?}"
             None => TotalSubnets::<T>::get(),
         });
 
+        // The general subnet starts out with linear rewards, it can be changed later on.
+        let mut changeset = changeset;
+        if netuid == 0 {
+            changeset.params.consensus_mode = ConsensusMode::Linear;
+        }
+
         let name = changeset.params.name.clone();
         changeset.apply(netuid)?;
+        Self::assign_epoch_offset(netuid, Self::get_tempo(netuid));
         TotalSubnets::<T>::mutate(|n| *n += 1);
         N::<T>::insert(netuid, 0);
         SubnetEmission::<T>::insert(netuid, 0);
@@ -414,6 +404,10 @@ threshold {subnet_stake_threshold:?}"
         // --- 4. Remove all stake.
         Stake::<T>::remove_prefix(netuid, None);
         TotalStake::<T>::remove(netuid);
+        DelegationCount::<T>::remove(netuid);
+        let _ = DelegationRewardPerShare::<T>::clear_prefix(netuid, u32::MAX, None);
+        let _ = UnsettledDelegationRewards::<T>::clear_prefix(netuid, u32::MAX, None);
+        let _ = DelegationRewardCheckpoints::<T>::clear_prefix(netuid, u32::MAX, None);
     }
 
     pub fn remove_subnet(netuid: u16) -> u16 {
@@ -435,6 +429,9 @@ threshold {subnet_stake_threshold:?}"
         Uids::<T>::clear_prefix(netuid, u32::MAX, None);
         Keys::<T>::clear_prefix(netuid, u32::MAX, None);
         DelegationFee::<T>::clear_prefix(netuid, u32::MAX, None);
+        PendingDelegationFee::<T>::clear_prefix(netuid, u32::MAX, None);
+        Controller::<T>::clear_prefix(netuid, u32::MAX, None);
+        ControlledModule::<T>::clear_prefix(netuid, u32::MAX, None);
 
         // Remove consnesus vectors
         Weights::<T>::clear_prefix(netuid, u32::MAX, None);
@@ -465,8 +462,18 @@ threshold {subnet_stake_threshold:?}"
         SelfVote::<T>::remove(netuid);
         SubnetEmission::<T>::remove(netuid);
         Tempo::<T>::remove(netuid);
+        EpochOffset::<T>::remove(netuid);
+        DeferredEpochs::<T>::mutate(|deferred| deferred.retain(|n| *n != netuid));
         TrustRatio::<T>::remove(netuid);
+        UnbondingPeriod::<T>::remove(netuid);
+        UseWeightsCommitReveal::<T>::remove(netuid);
+        WeightsRevealWindow::<T>::remove(netuid);
+        let _ = WeightCommits::<T>::clear_prefix(netuid, u32::MAX, None);
         VoteModeSubnet::<T>::remove(netuid);
+        ConsensusModeSubnet::<T>::remove(netuid);
+        MaxEpochHistory::<T>::remove(netuid);
+        EpochsRecorded::<T>::remove(netuid);
+        let _ = EpochHistory::<T>::clear_prefix(netuid, u32::MAX, None);
 
         // Adjust the total number of subnets. and remove the subnet from the list of subnets.
         N::<T>::remove(netuid);
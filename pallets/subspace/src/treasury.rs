@@ -0,0 +1,189 @@
+use super::*;
+use frame_support::{
+    pallet_prelude::{DispatchResult, Weight},
+    traits::ConstU32,
+    BoundedVec,
+};
+use sp_runtime::DispatchError;
+
+/// How many tranches a treasury stream can be split into.
+pub const MAX_STREAM_TRANCHES: u32 = 32;
+
+pub type StreamTranches = BoundedVec<(u64, u64), ConstU32<MAX_STREAM_TRANCHES>>;
+
+/// How a treasury stream releases its value.
+#[derive(Clone, Debug, PartialEq, Eq, TypeInfo, Decode, Encode)]
+pub enum StreamSchedule {
+    /// The value is released evenly over `duration` blocks.
+    Linear { duration: u64 },
+    /// Each `(end, amount)` tranche is released once `end` blocks passed since the start of the
+    /// stream, the tranche covers the range since the end of the previous one.
+    Tranches(StreamTranches),
+}
+
+impl StreamSchedule {
+    /// Whether the schedule releases exactly `value`, in tranches with increasing ends.
+    pub fn is_valid(&self, value: u64) -> bool {
+        match self {
+            Self::Linear { duration } => *duration > 0,
+            Self::Tranches(tranches) => {
+                let increasing = tranches.windows(2).all(|pair| pair[0].0 < pair[1].0);
+                let total =
+                    tranches.iter().try_fold(0u64, |total, (_, amount)| total.checked_add(*amount));
+
+                !tranches.is_empty() && increasing && total == Some(value)
+            }
+        }
+    }
+
+    /// How much of `value` is released `elapsed` blocks after the start of the stream.
+    pub fn released_after(&self, value: u64, elapsed: u64) -> u64 {
+        match self {
+            Self::Linear { duration } if elapsed >= *duration => value,
+            Self::Linear { duration } => {
+                (value as u128 * elapsed as u128 / *duration as u128) as u64
+            }
+            Self::Tranches(tranches) => tranches
+                .iter()
+                .filter(|(end, _)| *end <= elapsed)
+                .fold(0u64, |total, (_, amount)| total.saturating_add(*amount)),
+        }
+    }
+
+    /// How many blocks after its start the stream is fully released.
+    pub fn duration(&self) -> u64 {
+        match self {
+            Self::Linear { duration } => *duration,
+            Self::Tranches(tranches) => tranches.last().map(|(end, _)| *end).unwrap_or_default(),
+        }
+    }
+}
+
+/// Funds of the DAO treasury paid out to `dest` over time. The part not released yet is
+/// reserved in the treasury.
+#[derive(Clone, Debug, PartialEq, Eq, TypeInfo, Decode, Encode)]
+#[scale_info(skip_type_params(T))]
+pub struct TreasuryStream<T: Config> {
+    pub id: u64,
+    pub dest: T::AccountId,
+    pub value: u64,
+    pub released: u64,
+    pub start_block: u64,
+    pub schedule: StreamSchedule,
+}
+
+impl<T: Config> TreasuryStream<T> {
+    /// How much the stream releases by `block_number` that was not paid out yet.
+    pub fn releasable(&self, block_number: u64) -> u64 {
+        let elapsed = block_number.saturating_sub(self.start_block);
+        self.schedule.released_after(self.value, elapsed).saturating_sub(self.released)
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Returns the part of the DAO treasury not reserved by streams.
+    pub fn get_available_dao_treasury() -> u64 {
+        GlobalDaoTreasury::<T>::get().saturating_sub(ReservedDaoTreasury::<T>::get())
+    }
+
+    pub fn get_treasury_streams() -> Vec<TreasuryStream<T>> {
+        TreasuryStreams::<T>::iter_values().collect()
+    }
+
+    // Stream ids are never reused, not even the ones of cancelled or finished streams
+    fn take_next_stream_id() -> u64 {
+        NextStreamId::<T>::mutate(|next_id| {
+            let id = *next_id;
+            *next_id = id.saturating_add(1);
+            id
+        })
+    }
+
+    /// Starts streaming `value` to `dest`, reserving it in the DAO treasury.
+    pub(crate) fn start_treasury_stream(
+        dest: T::AccountId,
+        value: u64,
+        schedule: StreamSchedule,
+        block_number: u64,
+    ) -> DispatchResult {
+        ensure!(
+            Self::get_available_dao_treasury() >= value,
+            Error::<T>::InsufficientDaoTreasuryFunds
+        );
+        ReservedDaoTreasury::<T>::mutate(|reserved| *reserved = reserved.saturating_add(value));
+
+        let stream_id = Self::take_next_stream_id();
+        TreasuryStreams::<T>::insert(
+            stream_id,
+            TreasuryStream {
+                id: stream_id,
+                dest: dest.clone(),
+                value,
+                released: 0,
+                start_block: block_number,
+                schedule,
+            },
+        );
+
+        Self::deposit_event(Event::TreasuryStreamStarted(stream_id, dest, value));
+        Ok(())
+    }
+
+    /// Pays out what the stream released so far, and frees the rest of its reserve.
+    pub(crate) fn cancel_treasury_stream(stream_id: u64, block_number: u64) -> DispatchResult {
+        let stream =
+            TreasuryStreams::<T>::get(stream_id).ok_or(Error::<T>::TreasuryStreamNotFound)?;
+        let stream = Self::release_treasury_stream(stream, block_number)?;
+
+        let remaining = stream.value.saturating_sub(stream.released);
+        ReservedDaoTreasury::<T>::mutate(|reserved| *reserved = reserved.saturating_sub(remaining));
+        TreasuryStreams::<T>::remove(stream_id);
+
+        Self::deposit_event(Event::TreasuryStreamCancelled(stream_id, remaining));
+        Ok(())
+    }
+
+    /// Pays out what the streams released since the last payout, streams fully paid out are
+    /// removed. Returns the weight it consumed.
+    pub(crate) fn release_treasury_streams(block_number: u64) -> Weight {
+        let streams: Vec<_> = TreasuryStreams::<T>::iter_values().collect();
+        let count = streams.len() as u64;
+
+        for stream in streams {
+            let stream_id = stream.id;
+            if let Err(err) = Self::release_treasury_stream(stream, block_number) {
+                log::error!("failed to release treasury stream {stream_id}: {err:?}");
+            }
+        }
+
+        T::DbWeight::get().reads_writes(1 + count * 3, count * 4)
+    }
+
+    fn release_treasury_stream(
+        mut stream: TreasuryStream<T>,
+        block_number: u64,
+    ) -> Result<TreasuryStream<T>, DispatchError> {
+        let amount = stream.releasable(block_number);
+        if amount > 0 {
+            let balance =
+                Self::u64_to_balance(amount).ok_or(Error::<T>::CouldNotConvertToBalance)?;
+
+            GlobalDaoTreasury::<T>::mutate(|treasury| *treasury = treasury.saturating_sub(amount));
+            ReservedDaoTreasury::<T>::mutate(|reserved| {
+                *reserved = reserved.saturating_sub(amount)
+            });
+            Self::add_balance_to_account(&stream.dest, balance);
+
+            stream.released = stream.released.saturating_add(amount);
+            Self::deposit_event(Event::TreasuryStreamReleased(stream.id, amount));
+        }
+
+        if stream.released >= stream.value {
+            TreasuryStreams::<T>::remove(stream.id);
+        } else if amount > 0 {
+            TreasuryStreams::<T>::insert(stream.id, &stream);
+        }
+
+        Ok(stream)
+    }
+}
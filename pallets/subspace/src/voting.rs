@@ -1,6 +1,27 @@
 use super::*;
-use frame_support::{pallet_prelude::DispatchResult, storage::with_storage_layer};
-use sp_runtime::{DispatchError, Percent, SaturatedConversion};
+use crate::{council::CuratorAction, treasury::StreamSchedule};
+use frame_support::{
+    pallet_prelude::{DispatchResult, Weight},
+    storage::with_storage_layer,
+    traits::{Bounded, QueryPreimage},
+};
+use frame_system::{ensure_root, RawOrigin};
+use sp_runtime::{DispatchError, Perbill, Percent, SaturatedConversion};
+use sp_std::vec;
+
+/// A runtime call, stored inline or as the hash of a preimage.
+pub type BoundedCallOf<T> =
+    Bounded<<T as Config>::RuntimeCall, <T as frame_system::Config>::Hashing>;
+
+/// How many vote delegations are followed before a delegated vote is dropped.
+pub const MAX_VOTE_DELEGATION_DEPTH: usize = 16;
+
+/// Part of the proposal cost paid back when a proposal is cancelled after votes were cast.
+pub const CANCELLED_PROPOSAL_REFUND: Percent = Percent::from_percent(50);
+
+/// Part of the max block weight a runtime call proposal can use, it is dispatched together with
+/// the rest of the block initialization.
+pub const MAX_RUNTIME_CALL_WEIGHT: Perbill = Perbill::from_percent(25);
 
 #[derive(Clone, Debug, TypeInfo, Decode, Encode)]
 #[scale_info(skip_type_params(T))]
@@ -10,8 +31,8 @@ pub struct Proposal<T: Config> {
     pub expiration_block: u64,
     pub data: ProposalData<T>,
     pub status: ProposalStatus,
-    pub votes_for: BTreeSet<T::AccountId>, // account addresses
-    pub votes_against: BTreeSet<T::AccountId>, // account addresses
+    pub votes_for: BTreeMap<T::AccountId, u64>, // voting power of each account
+    pub votes_against: BTreeMap<T::AccountId, u64>, // voting power of each account
     pub proposal_cost: u64,
     pub creation_block: u64,
     pub finalization_block: Option<u64>,
@@ -34,22 +55,49 @@ impl<T: Config> Proposal<T> {
         matches!(self.status, ProposalStatus::Pending)
     }
 
+    /// Whether the proposal is approved and waiting for its enactment.
+    pub fn is_approved(&self) -> bool {
+        matches!(self.status, ProposalStatus::Approved { .. })
+    }
+
+    /// Marks a proposal as approved, queues it for execution at `enactment_block` and overrides
+    /// the storage value.
+    fn approve(mut self, enactment_block: u64) {
+        assert!(self.is_active());
+
+        self.status = ProposalStatus::Approved { enactment_block };
+        self.clear_vote_records();
+        self.deactivate();
+        EnactmentQueue::<T>::append(enactment_block, self.id);
+        Pallet::<T>::deposit_event(Event::ProposalScheduled(self.id, enactment_block));
+
+        Proposals::<T>::insert(self.id, self);
+    }
+
     /// Marks a proposal as accepted and overrides the storage value.
     fn accept(mut self, block_number: u64) {
-        assert!(self.is_active());
+        assert!(self.is_approved());
 
         self.status = ProposalStatus::Accepted;
         self.finalization_block = Some(block_number);
+        self.schedule_pruning(block_number);
 
         Proposals::<T>::insert(self.id, self);
     }
 
-    /// Marks a proposal as refused and overrides the storage value.
+    /// Marks a proposal as refused and overrides the storage value. Approved proposals are
+    /// refused when they fail to execute.
     fn refuse(mut self, block_number: u64) {
-        assert!(self.is_active());
+        assert!(self.is_active() || self.is_approved());
+
+        self.release_call();
+        self.clear_vote_records();
+        self.close_application(ApplicationStatus::Refused);
 
         self.status = ProposalStatus::Refused;
         self.finalization_block = Some(block_number);
+        self.deactivate();
+        self.schedule_pruning(block_number);
 
         Proposals::<T>::insert(self.id, self);
     }
@@ -59,14 +107,91 @@ impl<T: Config> Proposal<T> {
         assert!(self.is_active());
         assert!(block_number >= self.expiration_block);
 
+        self.release_call();
+        self.clear_vote_records();
+        self.close_application(ApplicationStatus::Expired);
         self.status = ProposalStatus::Expired;
         self.data = ProposalData::Expired;
         self.finalization_block = Some(block_number);
         self.votes_for = Default::default();
         self.votes_against = Default::default();
+        self.deactivate();
+        self.schedule_pruning(block_number);
 
         Proposals::<T>::insert(self.id, self);
     }
+
+    /// Marks a proposal cancelled by its proposer and overrides the storage value.
+    fn cancel(mut self, block_number: u64) {
+        assert!(self.is_active());
+
+        self.release_call();
+        self.release_stake_locks();
+        self.clear_vote_records();
+
+        self.status = ProposalStatus::Cancelled;
+        self.finalization_block = Some(block_number);
+        self.deactivate();
+        self.schedule_pruning(block_number);
+
+        Proposals::<T>::insert(self.id, self);
+    }
+
+    /// Removes the proposal from the proposals resolved every 100 blocks.
+    fn deactivate(&self) {
+        ActiveProposals::<T>::mutate(|proposals| proposals.remove(&self.id));
+    }
+
+    /// Queues the finalized proposal to be pruned once `ProposalRetention` blocks passed.
+    fn schedule_pruning(&self, block_number: u64) {
+        let pruning_block = block_number.saturating_add(ProposalRetention::<T>::get());
+        ProposalPruningQueue::<T>::append(pruning_block, self.id);
+    }
+
+    /// Lets go of the preimage of a runtime call that will not be dispatched.
+    fn release_call(&self) {
+        if let ProposalData::RuntimeCall(call) = &self.data {
+            T::Preimages::drop(call);
+        }
+    }
+
+    /// Closes the curator application voted on, if it is still pending. The application cost is
+    /// not paid back.
+    fn close_application(&self, status: ApplicationStatus) {
+        let ProposalData::DaoApplication { application_id } = self.data else {
+            return;
+        };
+
+        CuratorApplications::<T>::mutate(application_id, |application| {
+            if let Some(application) = application
+                .as_mut()
+                .filter(|application| application.status == ApplicationStatus::Pending)
+            {
+                application.status = status;
+            }
+        });
+    }
+
+    /// Releases the stake the voters locked with conviction votes on the proposal, for when the
+    /// votes are dropped before the proposal is decided.
+    fn release_stake_locks(&self) {
+        for voter in self.votes_for.keys().chain(self.votes_against.keys()) {
+            StakeLocks::<T>::mutate_exists(voter, |locks| {
+                if let Some(list) = locks {
+                    list.retain(|lock| lock.proposal_id != self.id);
+                    if list.is_empty() {
+                        *locks = None;
+                    }
+                }
+            });
+        }
+    }
+
+    /// Forgets the convictions, they only weigh in while the proposal is active. The stake locks
+    /// stay until they expire.
+    fn clear_vote_records(&self) {
+        let _ = VoteConvictions::<T>::clear_prefix(self.id, u32::MAX, None);
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, TypeInfo, Decode, Encode)]
@@ -76,6 +201,10 @@ pub enum ProposalStatus {
     Accepted,
     Refused,
     Expired,
+    Approved {
+        enactment_block: u64,
+    },
+    Cancelled,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, TypeInfo, Decode, Encode)]
@@ -84,6 +213,7 @@ pub enum ApplicationStatus {
     Pending,
     Accepted,
     Refused,
+    Expired,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, TypeInfo, Decode, Encode)]
@@ -92,6 +222,55 @@ pub enum VoteMode {
     Vote = 1,
 }
 
+/// How long a voter locks their stake in exchange for a multiplier on their voting power.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, TypeInfo, Decode, Encode)]
+pub enum Conviction {
+    /// The stake is not locked and counts once.
+    #[default]
+    None,
+    Locked2x,
+    Locked3x,
+    Locked4x,
+    Locked5x,
+    Locked6x,
+}
+
+impl Conviction {
+    /// The multiplier applied to the voting power.
+    pub fn multiplier(self) -> u64 {
+        match self {
+            Self::None => 1,
+            Self::Locked2x => 2,
+            Self::Locked3x => 3,
+            Self::Locked4x => 4,
+            Self::Locked5x => 5,
+            Self::Locked6x => 6,
+        }
+    }
+
+    /// How many lock periods the stake is locked for, every step doubles the previous one.
+    pub fn lock_periods(self) -> u64 {
+        match self {
+            Self::None => 0,
+            Self::Locked2x => 1,
+            Self::Locked3x => 2,
+            Self::Locked4x => 4,
+            Self::Locked5x => 8,
+            Self::Locked6x => 16,
+        }
+    }
+}
+
+/// Stake locked by a conviction vote, on a subnet or on the whole network. Locks on the same
+/// scope do not add up, the largest active one applies.
+#[derive(Clone, Debug, PartialEq, Eq, TypeInfo, Decode, Encode)]
+pub struct StakeLock {
+    pub proposal_id: u64,
+    pub netuid: Option<u16>,
+    pub amount: u64,
+    pub unlock_block: u64,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, TypeInfo, Decode, Encode)]
 #[scale_info(skip_type_params(T))]
 pub enum ProposalData<T: Config> {
@@ -111,6 +290,19 @@ pub enum ProposalData<T: Config> {
         value: u64,
         dest: T::AccountId,
     },
+    RuntimeCall(BoundedCallOf<T>),
+    DaoApplication {
+        application_id: u64,
+    },
+    TreasuryStream {
+        data: Vec<u8>,
+        value: u64,
+        dest: T::AccountId,
+        schedule: StreamSchedule,
+    },
+    CancelTreasuryStream {
+        stream_id: u64,
+    },
 }
 
 impl<T: Config> ProposalData<T> {
@@ -120,25 +312,40 @@ impl<T: Config> ProposalData<T> {
             _ => None,
         }
     }
+
+    /// Asks for the preimage of a runtime call to be kept, until the call is dispatched or
+    /// released.
+    fn request_call(&self) {
+        if let Self::RuntimeCall(call) = self {
+            if let Some(hash) = call.lookup_hash() {
+                T::Preimages::request(&hash);
+            }
+        }
+    }
 }
 
 impl<T: Config> Pallet<T> {
-    // Helper function to get the next proposal ID
-    fn get_next_proposal_id() -> u64 {
-        match Proposals::<T>::iter_keys().max() {
-            Some(id) => id + 1,
-            None => 0,
-        }
+    // Helper function to take the next proposal ID, IDs are never reused
+    fn take_next_proposal_id() -> u64 {
+        NextProposalId::<T>::mutate(|next_id| {
+            let id = *next_id;
+            *next_id = id.saturating_add(1);
+            id
+        })
     }
 
-    fn get_next_application_id() -> u64 {
-        match CuratorApplications::<T>::iter_keys().max() {
-            Some(id) => id + 1,
-            None => 0,
-        }
+    fn take_next_application_id() -> u64 {
+        NextApplicationId::<T>::mutate(|next_id| {
+            let id = *next_id;
+            *next_id = id.saturating_add(1);
+            id
+        })
     }
 
     pub fn add_proposal(key: T::AccountId, data: ProposalData<T>) -> DispatchResult {
+        // Check if the proposal data can be proposed
+        Self::check_proposal_data(&data)?;
+
         // Check if the proposer has enough balance
         let proposal_cost = ProposalCost::<T>::get();
         ensure!(
@@ -152,8 +359,20 @@ impl<T: Config> Pallet<T> {
             Error::<T>::CouldNotConvertToBalance
         );
 
+        // Burn the proposal cost from the proposer's balance
+        Self::remove_balance_from_account(&key, removed_balance_as_currency.unwrap())?;
+
+        // Keep the preimage of a runtime call around until the proposal is finalized
+        data.request_call();
+
+        Self::create_proposal(key, data, proposal_cost);
+        Ok(())
+    }
+
+    /// Stores a new proposal, the proposal cost has to be taken from the proposer already.
+    fn create_proposal(key: T::AccountId, data: ProposalData<T>, proposal_cost: u64) {
         // Get the next proposal ID
-        let proposal_id = Self::get_next_proposal_id();
+        let proposal_id = Self::take_next_proposal_id();
 
         // Get the proposal expiration value from storage
         let proposal_expiration = ProposalExpiration::<T>::get();
@@ -175,21 +394,18 @@ impl<T: Config> Pallet<T> {
             expiration_block,
             data,
             status: ProposalStatus::Pending,
-            votes_for: BTreeSet::new(),
-            votes_against: BTreeSet::new(),
+            votes_for: BTreeMap::new(),
+            votes_against: BTreeMap::new(),
             proposal_cost,
             creation_block: current_block,
             finalization_block: None,
         };
 
-        // Burn the proposal cost from the proposer's balance
-        Self::remove_balance_from_account(&key, removed_balance_as_currency.unwrap())?;
-
         // Store the proposal
         Proposals::<T>::insert(proposal_id, proposal);
+        ActiveProposals::<T>::mutate(|proposals| proposals.insert(proposal_id));
 
         Self::deposit_event(Event::<T>::ProposalCreated(proposal_id));
-        Ok(())
     }
 
     pub fn add_application(
@@ -212,7 +428,7 @@ impl<T: Config> Pallet<T> {
             Error::<T>::CouldNotConvertToBalance
         );
 
-        let application_id = Self::get_next_application_id();
+        let application_id = Self::take_next_application_id();
 
         let application = CuratorApplication {
             user_id: application_key,
@@ -229,6 +445,13 @@ impl<T: Config> Pallet<T> {
         CuratorApplications::<T>::insert(application_id, application);
 
         Self::deposit_event(Event::<T>::ApplicationCreated(application_id));
+
+        // In vote mode the stakers decide on the application, the application cost is the only
+        // cost of the proposal
+        if ApplicationVoteMode::<T>::get() == VoteMode::Vote {
+            Self::create_proposal(key, ProposalData::DaoApplication { application_id }, 0);
+        }
+
         Ok(())
     }
 
@@ -238,10 +461,15 @@ impl<T: Config> Pallet<T> {
     ) -> DispatchResult {
         let key = ensure_signed(origin)?;
 
-        // --- 2. Ensure that the key is the curator multisig.
-        ensure!(Self::get_curator() == key, Error::<T>::NotCurator);
+        // --- 2. Approve the refusal as a member of the curator council.
+        Self::approve_curator_action(key, CuratorAction::RefuseDaoApplication { application_id })
+    }
 
-        let mut application =
+    /// Returns the application, if it is still waiting for the curator council.
+    pub(crate) fn get_pending_application(
+        application_id: u64,
+    ) -> Result<CuratorApplication<T>, DispatchError> {
+        let application =
             CuratorApplications::<T>::get(application_id).ok_or(Error::<T>::ApplicationNotFound)?;
 
         ensure!(
@@ -249,6 +477,12 @@ impl<T: Config> Pallet<T> {
             Error::<T>::ApplicationNotPending
         );
 
+        Ok(application)
+    }
+
+    pub(crate) fn refuse_application(application_id: u64) -> DispatchResult {
+        let mut application = Self::get_pending_application(application_id)?;
+
         // Change the status of application to refused
         application.status = ApplicationStatus::Refused;
 
@@ -273,9 +507,6 @@ impl<T: Config> Pallet<T> {
     // Proposal with custom data
     pub fn do_add_custom_proposal(origin: T::RuntimeOrigin, data: Vec<u8>) -> DispatchResult {
         let key = ensure_signed(origin)?;
-        ensure!(!data.is_empty(), Error::<T>::ProposalCustomDataTooSmall);
-        ensure!(data.len() <= 256, Error::<T>::ProposalCustomDataTooLarge);
-        sp_std::str::from_utf8(&data).map_err(|_| Error::<T>::InvalidProposalCustomData)?;
 
         let proposal_data = ProposalData::Custom(data);
         Self::add_proposal(key, proposal_data)
@@ -288,9 +519,6 @@ impl<T: Config> Pallet<T> {
         data: Vec<u8>,
     ) -> DispatchResult {
         let key = ensure_signed(origin)?;
-        ensure!(!data.is_empty(), Error::<T>::ProposalCustomDataTooSmall);
-        ensure!(data.len() <= 256, Error::<T>::ProposalCustomDataTooLarge);
-        sp_std::str::from_utf8(&data).map_err(|_| Error::<T>::InvalidProposalCustomData)?;
 
         let proposal_data = ProposalData::SubnetCustom { netuid, data };
         Self::add_proposal(key, proposal_data)
@@ -303,18 +531,52 @@ impl<T: Config> Pallet<T> {
         dest: T::AccountId,
     ) -> DispatchResult {
         let key = ensure_signed(origin)?;
-        ensure!(!data.is_empty(), Error::<T>::ProposalCustomDataTooSmall);
-        ensure!(data.len() <= 256, Error::<T>::ProposalCustomDataTooLarge);
-        ensure!(
-            GlobalDaoTreasury::<T>::get() >= value,
-            Error::<T>::InsufficientDaoTreasuryFunds
-        );
-        sp_std::str::from_utf8(&data).map_err(|_| Error::<T>::InvalidProposalCustomData)?;
 
         let proposal_data = ProposalData::TransferDaoTreasury { data, value, dest };
         Self::add_proposal(key, proposal_data)
     }
 
+    /// Proposal to pay `value` out of the DAO treasury to `dest` over time
+    pub fn do_add_treasury_stream_proposal(
+        origin: T::RuntimeOrigin,
+        data: Vec<u8>,
+        value: u64,
+        dest: T::AccountId,
+        schedule: StreamSchedule,
+    ) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        let proposal_data = ProposalData::TreasuryStream {
+            data,
+            value,
+            dest,
+            schedule,
+        };
+        Self::add_proposal(key, proposal_data)
+    }
+
+    /// Proposal to stop a treasury stream, what it released so far is still paid out
+    pub fn do_add_cancel_treasury_stream_proposal(
+        origin: T::RuntimeOrigin,
+        stream_id: u64,
+    ) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        let proposal_data = ProposalData::CancelTreasuryStream { stream_id };
+        Self::add_proposal(key, proposal_data)
+    }
+
+    /// Proposal to dispatch a call with root origin
+    pub fn do_add_runtime_call_proposal(
+        origin: T::RuntimeOrigin,
+        call: BoundedCallOf<T>,
+    ) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        let proposal_data = ProposalData::RuntimeCall(call);
+        Self::add_proposal(key, proposal_data)
+    }
+
     /// Proposal to change the global parameters
     pub fn do_add_global_proposal(
         origin: T::RuntimeOrigin,
@@ -322,7 +584,6 @@ impl<T: Config> Pallet<T> {
     ) -> DispatchResult {
         let key = ensure_signed(origin)?;
 
-        Self::check_global_params(&params)?;
         let proposal_data = ProposalData::GlobalParams(params);
         Self::add_proposal(key, proposal_data)
     }
@@ -336,21 +597,169 @@ impl<T: Config> Pallet<T> {
     ) -> DispatchResult {
         let key = ensure_signed(origin)?;
 
-        // Make sure that the subnet is set on `Vote` mode.
-        // In Authority mode, only the founder can make changes.
-        let vote_mode = VoteModeSubnet::<T>::get(netuid);
-        ensure!(vote_mode == VoteMode::Vote, Error::<T>::NotVoteMode);
-
-        SubnetChangeset::<T>::update(netuid, params.clone())?;
         let proposal_data = ProposalData::SubnetParams { netuid, params };
         Self::add_proposal(key, proposal_data)
     }
 
-    /// Votes on proposals,
+    /// Makes sure proposers can submit the data. Applications and expired proposals are never
+    /// submitted directly.
+    fn check_proposal_data(data: &ProposalData<T>) -> DispatchResult {
+        let check_custom_data = |data: &[u8]| -> DispatchResult {
+            ensure!(!data.is_empty(), Error::<T>::ProposalCustomDataTooSmall);
+            ensure!(data.len() <= 256, Error::<T>::ProposalCustomDataTooLarge);
+            Ok(())
+        };
+        let check_utf8 = |data: &[u8]| -> DispatchResult {
+            sp_std::str::from_utf8(data).map_err(|_| Error::<T>::InvalidProposalCustomData)?;
+            Ok(())
+        };
+
+        match data {
+            ProposalData::Custom(data) | ProposalData::SubnetCustom { data, .. } => {
+                check_custom_data(data)?;
+                check_utf8(data)?;
+            }
+            ProposalData::TransferDaoTreasury { data, value, .. } => {
+                check_custom_data(data)?;
+                ensure!(
+                    Self::get_available_dao_treasury() >= *value,
+                    Error::<T>::InsufficientDaoTreasuryFunds
+                );
+                check_utf8(data)?;
+            }
+            ProposalData::TreasuryStream {
+                data,
+                value,
+                schedule,
+                ..
+            } => {
+                check_custom_data(data)?;
+                ensure!(
+                    *value > 0 && schedule.is_valid(*value),
+                    Error::<T>::InvalidTreasuryStream
+                );
+                ensure!(
+                    Self::get_available_dao_treasury() >= *value,
+                    Error::<T>::InsufficientDaoTreasuryFunds
+                );
+                check_utf8(data)?;
+            }
+            ProposalData::CancelTreasuryStream { stream_id } => {
+                ensure!(
+                    TreasuryStreams::<T>::contains_key(stream_id),
+                    Error::<T>::TreasuryStreamNotFound
+                );
+            }
+            ProposalData::RuntimeCall(call) => {
+                // Make sure the call can be dispatched once the proposal is accepted
+                let call_weight =
+                    Self::get_runtime_call_weight(call).ok_or(Error::<T>::InvalidRuntimeCall)?;
+                let max_weight = MAX_RUNTIME_CALL_WEIGHT * T::BlockWeights::get().max_block;
+                ensure!(
+                    call_weight.all_lte(max_weight),
+                    Error::<T>::RuntimeCallTooHeavy
+                );
+            }
+            ProposalData::GlobalParams(params) => {
+                Self::check_global_params(params)?;
+            }
+            ProposalData::SubnetParams { netuid, params } => {
+                // Make sure that the subnet is set on `Vote` mode.
+                // In Authority mode, only the founder can make changes.
+                let vote_mode = VoteModeSubnet::<T>::get(netuid);
+                ensure!(vote_mode == VoteMode::Vote, Error::<T>::NotVoteMode);
+
+                SubnetChangeset::<T>::update(*netuid, params.clone())?;
+            }
+            ProposalData::DaoApplication { .. } | ProposalData::Expired => {
+                return Err(Error::<T>::InvalidProposalData.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Cancels a pending proposal of the caller. The proposal cost is paid back in full while
+    /// nobody voted, and in part afterwards.
+    pub fn do_cancel_proposal(origin: T::RuntimeOrigin, proposal_id: u64) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        // --- 1. Ensure the caller proposed the pending proposal.
+        let proposal = Self::get_changeable_proposal(&key, proposal_id)?;
+
+        // --- 2. Pay back the proposal cost, or the part of it left after the votes.
+        let refund = if proposal.votes_for.is_empty() && proposal.votes_against.is_empty() {
+            proposal.proposal_cost
+        } else {
+            CANCELLED_PROPOSAL_REFUND.mul_floor(proposal.proposal_cost)
+        };
+        if refund > 0 {
+            let balance =
+                Self::u64_to_balance(refund).ok_or(Error::<T>::CouldNotConvertToBalance)?;
+            Self::add_balance_to_account(&key, balance);
+        }
+
+        // --- 3. Finalize the proposal.
+        let block_number = Self::get_current_block_number();
+        proposal.cancel(block_number);
+
+        Self::deposit_event(Event::<T>::ProposalCancelled(proposal_id, refund));
+        Ok(())
+    }
+
+    /// Replaces the data of a pending proposal of the caller. The votes cast so far are dropped
+    /// together with the stake they locked, voters have to vote again on the new data.
+    pub fn do_amend_proposal(
+        origin: T::RuntimeOrigin,
+        proposal_id: u64,
+        data: ProposalData<T>,
+    ) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        // --- 1. Ensure the caller proposed the pending proposal.
+        let mut proposal = Self::get_changeable_proposal(&key, proposal_id)?;
+
+        // --- 2. Ensure the new data can be proposed.
+        Self::check_proposal_data(&data)?;
+
+        // --- 3. Replace the data and reset the votes.
+        proposal.release_call();
+        data.request_call();
+        proposal.data = data;
+        proposal.release_stake_locks();
+        proposal.votes_for = Default::default();
+        proposal.votes_against = Default::default();
+        proposal.clear_vote_records();
+
+        Proposals::<T>::insert(proposal_id, proposal);
+
+        Self::deposit_event(Event::<T>::ProposalAmended(proposal_id));
+        Ok(())
+    }
+
+    /// Returns the proposal, if the key proposed it and it can still be cancelled or amended.
+    fn get_changeable_proposal(
+        key: &T::AccountId,
+        proposal_id: u64,
+    ) -> Result<Proposal<T>, DispatchError> {
+        let proposal = Proposals::<T>::get(proposal_id).ok_or(Error::<T>::ProposalNotFound)?;
+        ensure!(proposal.proposer == *key, Error::<T>::NotProposer);
+        ensure!(proposal.is_active(), Error::<T>::InvalidProposalStatus);
+        // applications are withdrawn through the application, not the proposal voting on it
+        ensure!(
+            !matches!(proposal.data, ProposalData::DaoApplication { .. }),
+            Error::<T>::InvalidProposalData
+        );
+
+        Ok(proposal)
+    }
+
+    /// Votes on proposals, locking the stake of the voter if they vote with conviction
     pub fn do_vote_proposal(
         origin: T::RuntimeOrigin,
         proposal_id: u64,
         agree: bool,
+        conviction: Conviction,
     ) -> DispatchResult {
         let key = ensure_signed(origin)?;
 
@@ -367,25 +776,34 @@ impl<T: Config> Pallet<T> {
 
         // Check if the voter has already voted
         ensure!(
-            !proposal.votes_for.contains(&key) && !proposal.votes_against.contains(&key),
+            !proposal.votes_for.contains_key(&key) && !proposal.votes_against.contains_key(&key),
             Error::<T>::AlreadyVoted
         );
 
         // Get the netuid from the proposal data
         let netuid = proposal.data.netuid();
 
-        // Get the voter's stake
+        // Get the voter's stake, which is the voting power recorded with the vote
         let voter_stake = Self::get_account_stake(&key, netuid);
 
-        // Check if the voter has non-zero stake
-        ensure!(voter_stake > 0, Error::<T>::InsufficientStake);
+        // Check if the voter has non-zero stake, or votes for someone who delegated to them
+        ensure!(
+            voter_stake > 0 || !Self::get_vote_delegators(&key, netuid).is_empty(),
+            Error::<T>::InsufficientStake
+        );
 
         // Update the proposal based on the vote
         match agree {
-            true => proposal.votes_for.insert(key.clone()),
-            false => proposal.votes_against.insert(key.clone()),
+            true => proposal.votes_for.insert(key.clone(), voter_stake),
+            false => proposal.votes_against.insert(key.clone(), voter_stake),
         };
 
+        // Lock the stake in exchange for the conviction multiplier
+        if conviction != Conviction::None {
+            VoteConvictions::<T>::insert(proposal_id, &key, conviction);
+            Self::lock_stake(&key, proposal_id, netuid, voter_stake, conviction);
+        }
+
         // Update the proposal in storage
         Proposals::<T>::insert(proposal_id, proposal);
         Self::deposit_event(Event::<T>::ProposalVoted(proposal_id, key, agree));
@@ -407,44 +825,74 @@ impl<T: Config> Pallet<T> {
             Error::<T>::InvalidProposalStatus
         );
 
-        let removed = proposal.votes_for.remove(&key) || proposal.votes_against.remove(&key);
+        let removed = proposal.votes_for.remove(&key).is_some()
+            || proposal.votes_against.remove(&key).is_some();
 
         // Check if the voter has actually voted on the proposal
         ensure!(removed, Error::<T>::VoteNotFound);
 
+        // The conviction goes away with the vote, the stake lock does not
+        VoteConvictions::<T>::remove(proposal_id, &key);
+
         // Update the proposal in storage
         Proposals::<T>::insert(proposal.id, proposal);
         Self::deposit_event(Event::<T>::ProposalVoteUnregistered(proposal_id, key));
         Ok(())
     }
 
-    pub(crate) fn resolve_proposals(block_number: u64) {
-        for proposal in Proposals::<T>::iter_values() {
-            let proposal_id = proposal.id;
+    /// Approves, refuses or expires the pending proposals. Returns the weight it consumed.
+    pub(crate) fn resolve_proposals(block_number: u64) -> Weight {
+        let mut proposals = 0u32;
+        let mut votes = 0u32;
+
+        for proposal_id in ActiveProposals::<T>::get() {
+            proposals = proposals.saturating_add(1);
 
-            if !matches!(proposal.status, ProposalStatus::Pending) {
+            let Some(mut proposal) = Proposals::<T>::get(proposal_id) else {
+                continue;
+            };
+            if !proposal.is_active() {
                 continue;
             }
 
+            let proposal_votes = proposal.votes_for.len() + proposal.votes_against.len();
+            votes = votes.saturating_add(proposal_votes as u32);
+
             let netuid = proposal.data.netuid();
 
-            let votes_for: u64 =
-                proposal.votes_for.iter().map(|id| Self::get_account_stake(id, netuid)).sum();
-            let votes_against: u64 = proposal
-                .votes_against
-                .iter()
-                .map(|id| Self::get_account_stake(id, netuid))
-                .sum();
+            // stake moved away since the vote no longer counts, so it can not be used to vote
+            // again from another account
+            let lowered_for = Self::lower_voting_power(&mut proposal.votes_for, netuid);
+            let lowered_against = Self::lower_voting_power(&mut proposal.votes_against, netuid);
+            if lowered_for || lowered_against {
+                Proposals::<T>::insert(proposal_id, &proposal);
+            }
+
+            // delegators that did not vote count with the voter they delegated to, following the
+            // delegations as they are now
+            let (delegated_for, delegated_against, delegated_votes) =
+                Self::tally_delegated_votes(&proposal);
+            votes = votes.saturating_add(delegated_votes);
+
+            // participation counts the stake once, the outcome weighs it by conviction
+            let direct_stake: u64 =
+                proposal.votes_for.values().chain(proposal.votes_against.values()).sum();
+            let total_stake =
+                direct_stake.saturating_add(delegated_for).saturating_add(delegated_against);
+            let votes_for =
+                Self::weigh_votes(proposal_id, &proposal.votes_for).saturating_add(delegated_for);
+            let votes_against = Self::weigh_votes(proposal_id, &proposal.votes_against)
+                .saturating_add(delegated_against);
 
-            let total_stake = votes_for + votes_against;
-            let minimal_stake_to_execute = Self::get_minimal_stake_to_execute(netuid);
+            let minimal_stake_to_execute = Self::get_minimal_stake_to_execute(&proposal.data);
 
             let res: DispatchResult = with_storage_layer(|| {
                 if total_stake >= minimal_stake_to_execute {
                     if votes_against > votes_for {
                         proposal.refuse(block_number);
                     } else {
-                        Self::execute_proposal(proposal, block_number)?;
+                        let delay = Self::get_enactment_delay(&proposal.data);
+                        proposal.approve(block_number.saturating_add(delay));
                     }
                 } else if block_number >= proposal.expiration_block {
                     proposal.expire(block_number);
@@ -457,15 +905,452 @@ impl<T: Config> Pallet<T> {
                 log::error!("failed to resolve proposal {proposal_id}: {err:?}");
             }
         }
+
+        // delegated votes cost about the same as direct ones, they are read with the stake of
+        // the delegator
+        T::WeightInfo::resolve_proposals(proposals, votes)
+    }
+
+    /// Executes the approved proposals whose enactment is due at this block. Proposals that fail
+    /// to execute are refused. Returns the weight it consumed.
+    pub(crate) fn enact_proposals(block_number: u64) -> Weight {
+        let proposal_ids = EnactmentQueue::<T>::take(block_number);
+        let db_weight = T::DbWeight::get();
+        let mut weight = db_weight.reads_writes(1, 1);
+
+        for proposal_id in &proposal_ids {
+            let Some(proposal) = Proposals::<T>::get(proposal_id) else {
+                continue;
+            };
+            let ProposalStatus::Approved { enactment_block } = proposal.status else {
+                continue;
+            };
+            // fast-tracked proposals are left behind in their original slot
+            if enactment_block != block_number {
+                continue;
+            }
+
+            weight = weight.saturating_add(Self::get_enactment_weight(&proposal.data));
+
+            let res = with_storage_layer(|| Self::execute_proposal(proposal.clone(), block_number));
+            if let Err(err) = res {
+                log::error!("failed to enact proposal {proposal_id}: {err:?}");
+                proposal.refuse(block_number);
+            }
+        }
+
+        // every proposal is read, finalized and pays back its proposer
+        let proposals = proposal_ids.len() as u64;
+        weight.saturating_add(db_weight.reads_writes(proposals * 2, proposals * 3))
+    }
+
+    /// Returns the weight of executing the data of an approved proposal, from the storage it
+    /// touches. Runtime calls are charged the weight they declare.
+    fn get_enactment_weight(data: &ProposalData<T>) -> Weight {
+        let db_weight = T::DbWeight::get();
+        match data {
+            ProposalData::Custom(_) | ProposalData::SubnetCustom { .. } | ProposalData::Expired => {
+                Weight::zero()
+            }
+            // every parameter is written
+            ProposalData::GlobalParams(_) => db_weight.reads_writes(2, 30),
+            ProposalData::SubnetParams { .. } => db_weight.reads_writes(4, 24),
+            // the treasury, its reserve and the destination account
+            ProposalData::TransferDaoTreasury { .. } => db_weight.reads_writes(3, 2),
+            ProposalData::RuntimeCall(call) => db_weight
+                .reads_writes(1, 1)
+                .saturating_add(Self::get_runtime_call_weight(call).unwrap_or_default()),
+            // the application and the account paying for it
+            ProposalData::DaoApplication { .. } => db_weight.reads_writes(2, 2),
+            // the stream, its counter and the treasury reserve
+            ProposalData::TreasuryStream { .. } => db_weight.reads_writes(3, 3),
+            ProposalData::CancelTreasuryStream { .. } => db_weight.reads_writes(4, 4),
+        }
+    }
+
+    /// Returns the weight the runtime call declares, or `None` if it can not be decoded.
+    fn get_runtime_call_weight(call: &BoundedCallOf<T>) -> Option<Weight> {
+        let (call, _) = T::Preimages::peek(call).ok()?;
+        Some(call.get_dispatch_info().weight)
+    }
+
+    /// Removes the finalized proposals whose retention ends at this block. Returns the weight it
+    /// consumed.
+    pub(crate) fn prune_proposals(block_number: u64) -> Weight {
+        let proposal_ids = ProposalPruningQueue::<T>::take(block_number);
+        for proposal_id in &proposal_ids {
+            Proposals::<T>::remove(proposal_id);
+            Self::deposit_event(Event::<T>::ProposalPruned(*proposal_id));
+        }
+
+        let proposals = proposal_ids.len() as u64;
+        T::DbWeight::get().reads_writes(1, 1 + proposals)
+    }
+
+    /// Returns how many blocks an approved proposal waits before it is executed. Runtime calls
+    /// wait at least their own enactment delay.
+    pub fn get_enactment_delay(data: &ProposalData<T>) -> u64 {
+        let delay = ProposalEnactmentDelay::<T>::get();
+        match data {
+            ProposalData::RuntimeCall(_) => delay.max(RuntimeCallEnactmentDelay::<T>::get()),
+            _ => delay,
+        }
+    }
+
+    /// Moves the enactment of an approved proposal to the next block. Root does it right away,
+    /// the curator council through a motion.
+    pub fn do_fast_track_proposal(origin: T::RuntimeOrigin, proposal_id: u64) -> DispatchResult {
+        if ensure_root(origin.clone()).is_ok() {
+            return Self::fast_track(proposal_id);
+        }
+
+        let key = ensure_signed(origin)?;
+        Self::approve_curator_action(key, CuratorAction::FastTrackProposal { proposal_id })
+    }
+
+    /// Returns the proposal and its enactment block, if it is waiting for its enactment and is
+    /// allowed to skip the wait.
+    pub(crate) fn get_fast_trackable_proposal(
+        proposal_id: u64,
+    ) -> Result<(Proposal<T>, u64), DispatchError> {
+        let proposal = Proposals::<T>::get(proposal_id).ok_or(Error::<T>::ProposalNotFound)?;
+        let ProposalStatus::Approved { enactment_block } = proposal.status else {
+            return Err(Error::<T>::ProposalNotApproved.into());
+        };
+        ensure!(
+            !matches!(proposal.data, ProposalData::RuntimeCall(_)),
+            Error::<T>::CannotFastTrackRuntimeCall
+        );
+
+        Ok((proposal, enactment_block))
+    }
+
+    pub(crate) fn fast_track(proposal_id: u64) -> DispatchResult {
+        // --- 1. Ensure that the proposal is waiting for its enactment.
+        let (mut proposal, enactment_block) = Self::get_fast_trackable_proposal(proposal_id)?;
+
+        // --- 2. Queue the proposal for the next block.
+        let next_block = Self::get_current_block_number().saturating_add(1);
+        if enactment_block <= next_block {
+            return Ok(());
+        }
+
+        EnactmentQueue::<T>::append(next_block, proposal_id);
+        proposal.status = ProposalStatus::Approved {
+            enactment_block: next_block,
+        };
+        Proposals::<T>::insert(proposal_id, proposal);
+
+        Self::deposit_event(Event::<T>::ProposalScheduled(proposal_id, next_block));
+        Ok(())
+    }
+
+    /// Lowers the voting power recorded for each voter to their current stake, if it is below.
+    /// Voting power never goes up after the vote. Returns whether any voting power was lowered.
+    fn lower_voting_power(votes: &mut BTreeMap<T::AccountId, u64>, netuid: Option<u16>) -> bool {
+        let mut lowered = false;
+        for (voter, power) in votes.iter_mut() {
+            let stake = Self::get_account_stake(voter, netuid);
+            if stake < *power {
+                *power = stake;
+                lowered = true;
+            }
+        }
+        lowered
+    }
+
+    /// Sums the voting power of the votes, multiplied by the conviction of each voter.
+    fn weigh_votes(proposal_id: u64, votes: &BTreeMap<T::AccountId, u64>) -> u64 {
+        votes.iter().fold(0u64, |total, (voter, power)| {
+            let conviction = VoteConvictions::<T>::get(proposal_id, voter);
+            total.saturating_add(power.saturating_mul(conviction.multiplier()))
+        })
+    }
+
+    /// Delegates the governance vote of the caller on a subnet, or on the whole network. This is
+    /// independent from stake delegation.
+    pub fn do_delegate_vote(
+        origin: T::RuntimeOrigin,
+        delegatee: T::AccountId,
+        netuid: Option<u16>,
+    ) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        ensure!(key != delegatee, Error::<T>::CannotDelegateVoteToSelf);
+        if let Some(netuid) = netuid {
+            ensure!(
+                Self::if_subnet_exist(netuid),
+                Error::<T>::NetworkDoesNotExist
+            );
+        }
+
+        if let Some(previous) = VoteDelegations::<T>::get(&key, netuid) {
+            Self::remove_vote_delegator(&previous, netuid, &key);
+        }
+        VoteDelegations::<T>::insert(&key, netuid, &delegatee);
+        VoteDelegators::<T>::mutate(&delegatee, netuid, |delegators| {
+            delegators.insert(key.clone())
+        });
+
+        Self::deposit_event(Event::<T>::VoteDelegated(key, delegatee, netuid));
+        Ok(())
+    }
+
+    /// Takes back the governance vote the caller delegated on a subnet, or on the whole network.
+    pub fn do_undelegate_vote(origin: T::RuntimeOrigin, netuid: Option<u16>) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        let delegatee =
+            VoteDelegations::<T>::take(&key, netuid).ok_or(Error::<T>::VoteNotDelegated)?;
+        Self::remove_vote_delegator(&delegatee, netuid, &key);
+
+        Self::deposit_event(Event::<T>::VoteUndelegated(key, netuid));
+        Ok(())
+    }
+
+    fn remove_vote_delegator(delegatee: &T::AccountId, netuid: Option<u16>, key: &T::AccountId) {
+        VoteDelegators::<T>::mutate_exists(delegatee, netuid, |delegators| {
+            if let Some(set) = delegators {
+                set.remove(key);
+                if set.is_empty() {
+                    *delegators = None;
+                }
+            }
+        });
+    }
+
+    /// Returns who the key delegated its vote to for proposals on `netuid`. The delegation on the
+    /// subnet takes precedence over the one on the whole network.
+    pub fn get_vote_delegatee(key: &T::AccountId, netuid: Option<u16>) -> Option<T::AccountId> {
+        netuid
+            .and_then(|netuid| VoteDelegations::<T>::get(key, Some(netuid)))
+            .or_else(|| VoteDelegations::<T>::get(key, None))
+    }
+
+    /// Returns the accounts whose vote on proposals on `netuid` goes to the key. Accounts that
+    /// delegated on the subnet to someone else are left out of the network wide delegators.
+    pub fn get_vote_delegators(key: &T::AccountId, netuid: Option<u16>) -> BTreeSet<T::AccountId> {
+        let mut delegators = VoteDelegators::<T>::get(key, None);
+        if let Some(netuid) = netuid {
+            delegators.retain(|delegator| {
+                VoteDelegations::<T>::get(delegator, Some(netuid)).map_or(true, |d| d == *key)
+            });
+            delegators.extend(VoteDelegators::<T>::get(key, Some(netuid)));
+        }
+        delegators
+    }
+
+    /// Moves the governance state of a swapped module key to the new key: its stake locks, the
+    /// vote delegations from and to it, and its votes on the active proposals. The new key keeps
+    /// its own delegations and votes where both keys have one.
+    pub(crate) fn swap_governance_key(old_key: &T::AccountId, new_key: &T::AccountId) {
+        // the locks keep guarding the stake, which now belongs to the new key
+        let locks = StakeLocks::<T>::take(old_key);
+        if !locks.is_empty() {
+            StakeLocks::<T>::mutate(new_key, |new_locks| new_locks.extend(locks));
+        }
+
+        // the delegations of the old key
+        let delegations: Vec<_> = VoteDelegations::<T>::drain_prefix(old_key).collect();
+        for (netuid, delegatee) in delegations {
+            Self::remove_vote_delegator(&delegatee, netuid, old_key);
+            if delegatee != *new_key && !VoteDelegations::<T>::contains_key(new_key, netuid) {
+                VoteDelegations::<T>::insert(new_key, netuid, &delegatee);
+                VoteDelegators::<T>::mutate(&delegatee, netuid, |delegators| {
+                    delegators.insert(new_key.clone())
+                });
+            }
+        }
+
+        // the delegations to the old key
+        let delegators: Vec<_> = VoteDelegators::<T>::drain_prefix(old_key).collect();
+        for (netuid, delegators) in delegators {
+            for delegator in delegators {
+                if delegator == *new_key {
+                    VoteDelegations::<T>::remove(&delegator, netuid);
+                    continue;
+                }
+                VoteDelegations::<T>::insert(&delegator, netuid, new_key);
+                VoteDelegators::<T>::mutate(new_key, netuid, |set| set.insert(delegator));
+            }
+        }
+
+        // the votes on the proposals still open
+        for proposal_id in ActiveProposals::<T>::get() {
+            let Some(mut proposal) = Proposals::<T>::get(proposal_id) else {
+                continue;
+            };
+
+            let new_key_voted = proposal.votes_for.contains_key(new_key)
+                || proposal.votes_against.contains_key(new_key);
+            let mut changed = false;
+            for votes in [&mut proposal.votes_for, &mut proposal.votes_against] {
+                if let Some(power) = votes.remove(old_key) {
+                    changed = true;
+                    if !new_key_voted {
+                        votes.insert(new_key.clone(), power);
+                        let conviction = VoteConvictions::<T>::take(proposal_id, old_key);
+                        VoteConvictions::<T>::insert(proposal_id, new_key, conviction);
+                    }
+                }
+            }
+            VoteConvictions::<T>::remove(proposal_id, old_key);
+
+            if changed {
+                Proposals::<T>::insert(proposal_id, proposal);
+            }
+        }
     }
 
-    pub fn execute_application(user_id: &T::AccountId) -> DispatchResult {
+    /// Sums the voting power delegated to the voters of the proposal, as (for, against), and
+    /// returns the number of delegated votes. The delegations are followed as they are when the
+    /// proposal is resolved, from each voter down to the accounts that did not vote themselves and
+    /// at most `MAX_VOTE_DELEGATION_DEPTH` levels deep. Delegators count with their current stake.
+    fn tally_delegated_votes(proposal: &Proposal<T>) -> (u64, u64, u32) {
+        let netuid = proposal.data.netuid();
+        let has_voted = |key: &T::AccountId| {
+            proposal.votes_for.contains_key(key) || proposal.votes_against.contains_key(key)
+        };
+
+        let mut votes = 0u32;
+        let mut visited = BTreeSet::new();
+        let mut tally = |voters: &BTreeMap<T::AccountId, u64>| {
+            let mut power = 0u64;
+            for voter in voters.keys() {
+                let mut pending = vec![(voter.clone(), 0)];
+                while let Some((account, depth)) = pending.pop() {
+                    if depth >= MAX_VOTE_DELEGATION_DEPTH {
+                        continue;
+                    }
+                    for delegator in Self::get_vote_delegators(&account, netuid) {
+                        // accounts that voted count with their own vote, and so do their delegators
+                        if has_voted(&delegator) || !visited.insert(delegator.clone()) {
+                            continue;
+                        }
+                        votes = votes.saturating_add(1);
+                        let stake = Self::get_account_stake(&delegator, netuid);
+                        power = power.saturating_add(stake);
+                        pending.push((delegator, depth + 1));
+                    }
+                }
+            }
+            power
+        };
+
+        let delegated_for = tally(&proposal.votes_for);
+        let delegated_against = tally(&proposal.votes_against);
+        (delegated_for, delegated_against, votes)
+    }
+
+    /// Locks the stake of the voter for the lock periods of the conviction. A lock period is
+    /// `ConvictionLockEpochs` epochs of the proposal subnet, or of the general subnet for
+    /// proposals on the whole network.
+    fn lock_stake(
+        key: &T::AccountId,
+        proposal_id: u64,
+        netuid: Option<u16>,
+        amount: u64,
+        conviction: Conviction,
+    ) {
+        let epoch = Tempo::<T>::get(netuid.unwrap_or(0)) as u64;
+        let lock_blocks = conviction
+            .lock_periods()
+            .saturating_mul(ConvictionLockEpochs::<T>::get())
+            .saturating_mul(epoch);
+
+        let current_block = Self::get_current_block_number();
+        let unlock_block = current_block.saturating_add(lock_blocks);
+
+        StakeLocks::<T>::mutate(key, |locks| {
+            locks.retain(|lock| lock.unlock_block > current_block);
+            locks.push(StakeLock {
+                proposal_id,
+                netuid,
+                amount,
+                unlock_block,
+            });
+        });
+
+        Self::deposit_event(Event::<T>::StakeLocked(key.clone(), amount, unlock_block));
+    }
+
+    /// Returns the stake locks of the key that did not expire yet.
+    pub fn get_stake_locks(key: &T::AccountId) -> Vec<StakeLock> {
+        let current_block = Self::get_current_block_number();
+        StakeLocks::<T>::get(key)
+            .into_iter()
+            .filter(|lock| lock.unlock_block > current_block)
+            .collect()
+    }
+
+    /// Returns how much stake of the key is locked on the subnet, or on the whole network.
+    pub fn get_locked_stake(key: &T::AccountId, netuid: Option<u16>) -> u64 {
+        Self::get_stake_locks(key)
+            .into_iter()
+            .filter(|lock| lock.netuid == netuid)
+            .map(|lock| lock.amount)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Makes sure taking `amount` of stake away from the subnet leaves the locked stake in place,
+    /// both the stake locked on the subnet and on the whole network.
+    pub fn ensure_stake_unlocked(key: &T::AccountId, netuid: u16, amount: u64) -> DispatchResult {
+        let (locked, _) = Self::get_locked_part(key, netuid, amount);
+        ensure!(locked == 0, Error::<T>::StakeIsLocked);
+        Ok(())
+    }
+
+    /// Makes sure taking `amount` of stake away from the subnet leaves the stake locked on the
+    /// subnet in place. Stake locked on the whole network is not checked, for moves that keep
+    /// the stake on the network.
+    pub fn ensure_subnet_stake_unlocked(
+        key: &T::AccountId,
+        netuid: u16,
+        amount: u64,
+    ) -> DispatchResult {
+        let (locked, _) = Self::get_scope_locked_part(key, Some(netuid), amount);
+        ensure!(locked == 0, Error::<T>::StakeIsLocked);
+        Ok(())
+    }
+
+    /// Returns how much of `amount` taken away from the subnet cuts into the locked stake, and
+    /// the block the locks involved expire at.
+    pub(crate) fn get_locked_part(key: &T::AccountId, netuid: u16, amount: u64) -> (u64, u64) {
+        let (subnet_part, subnet_unlock) = Self::get_scope_locked_part(key, Some(netuid), amount);
+        let (network_part, network_unlock) = Self::get_scope_locked_part(key, None, amount);
+        (
+            subnet_part.max(network_part),
+            subnet_unlock.max(network_unlock),
+        )
+    }
+
+    /// Returns how much of `amount` cuts into the stake locked on the subnet, or on the whole
+    /// network, and the block those locks expire at.
+    fn get_scope_locked_part(key: &T::AccountId, netuid: Option<u16>, amount: u64) -> (u64, u64) {
+        let locks: Vec<_> = Self::get_stake_locks(key)
+            .into_iter()
+            .filter(|lock| lock.netuid == netuid)
+            .collect();
+        let Some(locked) = locks.iter().map(|lock| lock.amount).max() else {
+            return (0, 0);
+        };
+
+        let free = Self::get_account_stake(key, netuid).saturating_sub(locked);
+        if amount <= free {
+            return (0, 0);
+        }
+
+        let unlock_block = locks.iter().map(|lock| lock.unlock_block).max();
+        (amount - free, unlock_block.unwrap_or_default())
+    }
+
+    /// Accepts the pending application, paying back its cost.
+    pub fn execute_application(application_id: u64) -> DispatchResult {
         // Perform actions based on the application data type
         // The owners will handle the off-chain logic
 
-        let mut application = CuratorApplications::<T>::iter_values()
-            .find(|app| app.user_id == *user_id)
-            .ok_or(Error::<T>::ApplicationNotFound)?;
+        let mut application = Self::get_pending_application(application_id)?;
 
         // Give the proposer back his tokens, if the application passed
         Self::add_balance_to_account(
@@ -479,6 +1364,13 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
+    /// Returns the id of the pending application of the key, if any.
+    pub(crate) fn get_pending_application_id(user_id: &T::AccountId) -> Option<u64> {
+        CuratorApplications::<T>::iter_values()
+            .find(|app| app.user_id == *user_id && app.status == ApplicationStatus::Pending)
+            .map(|app| app.id)
+    }
+
     fn execute_proposal(proposal: Proposal<T>, block_number: u64) -> DispatchResult {
         // Perform actions based on the proposal data type
         match &proposal.data {
@@ -487,6 +1379,10 @@ impl<T: Config> Pallet<T> {
                 // The owners will handle the off-chain logic
             }
             ProposalData::GlobalParams(params) => {
+                // The params were valid when proposed, but some rules depend on the chain state,
+                // like the max supply that has to stay above the issuance.
+                Self::check_global_params(params)?;
+
                 // Update the global parameters
                 Self::set_global_params(params.clone());
                 // Emit the GlobalParamsUpdated event
@@ -504,6 +1400,11 @@ impl<T: Config> Pallet<T> {
                 value,
                 dest,
             } => {
+                // the funds reserved by streams can not be transferred
+                ensure!(
+                    Self::get_available_dao_treasury() >= *value,
+                    Error::<T>::InsufficientDaoTreasuryFunds
+                );
                 GlobalDaoTreasury::<T>::try_mutate::<(), DispatchError, _>(|treasury| {
                     *treasury =
                         treasury.checked_sub(*value).ok_or(Error::<T>::BalanceCouldNotBeRemoved)?;
@@ -514,6 +1415,28 @@ impl<T: Config> Pallet<T> {
                     Self::u64_to_balance(*value).ok_or(Error::<T>::CouldNotConvertToBalance)?;
                 Self::add_balance_to_account(dest, amount);
             }
+            ProposalData::RuntimeCall(call) => {
+                let (call, _) = T::Preimages::realize(call)?;
+                call.dispatch(RawOrigin::Root.into()).map_err(|err| err.error)?;
+
+                Self::deposit_event(Event::RuntimeCallProposalAccepted(proposal.id));
+            }
+            ProposalData::TreasuryStream {
+                data: _,
+                value,
+                dest,
+                schedule,
+            } => {
+                Self::start_treasury_stream(dest.clone(), *value, schedule.clone(), block_number)?;
+            }
+            ProposalData::CancelTreasuryStream { stream_id } => {
+                Self::cancel_treasury_stream(*stream_id, block_number)?;
+            }
+            ProposalData::DaoApplication { application_id } => {
+                Self::execute_application(*application_id)?;
+
+                Self::deposit_event(Event::DaoApplicationAccepted(*application_id));
+            }
             ProposalData::Expired => {
                 unreachable!("Expired data is illegal at this point")
             }
@@ -531,10 +1454,13 @@ impl<T: Config> Pallet<T> {
     }
 
     /// Returns how much stake is needed to execute a proposal
-    pub fn get_minimal_stake_to_execute(netuid: Option<u16>) -> u64 {
-        let threshold: Percent = Self::get_proposal_participation_threshold();
+    pub fn get_minimal_stake_to_execute(data: &ProposalData<T>) -> u64 {
+        let threshold: Percent = match data {
+            ProposalData::RuntimeCall(_) => RuntimeCallParticipationThreshold::<T>::get(),
+            _ => Self::get_proposal_participation_threshold(),
+        };
 
-        let stake = match netuid {
+        let stake = match data.netuid() {
             Some(specific_netuid) => TotalStake::<T>::get(specific_netuid),
             None => Self::total_stake(),
         };
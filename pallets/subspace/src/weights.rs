@@ -1,4 +1,5 @@
 use super::*;
+use sp_runtime::traits::Hash;
 
 impl<T: Config> Pallet<T> {
     // Returns true if the items contain duplicates.
@@ -13,9 +14,141 @@ impl<T: Config> Pallet<T> {
         uids: Vec<u16>,
         values: Vec<u16>,
     ) -> dispatch::DispatchResult {
-        // --- 1. Check the caller's signature. This is the key of a registered account.
-        let key = ensure_signed(origin)?;
+        // --- 1. Check the caller's signature. This is the key of a registered account, or the
+        // controller of one.
+        let caller = ensure_signed(origin)?;
+        let key = Self::get_module_key_for_caller(netuid, caller);
 
+        // --- 1.1 Subnets using commit-reveal only accept revealed weights.
+        ensure!(
+            !UseWeightsCommitReveal::<T>::get(netuid),
+            Error::<T>::WeightsCommitRevealEnabled
+        );
+
+        Self::apply_weights(netuid, key, uids, values)
+    }
+
+    /// Commits the hash of the weights the module will reveal once the current epoch has ended.
+    pub fn do_commit_weights(
+        origin: T::RuntimeOrigin,
+        netuid: u16,
+        commit_hash: T::Hash,
+    ) -> dispatch::DispatchResult {
+        // --- 1. Check the caller's signature. This is the key of a registered account, or the
+        // controller of one.
+        let caller = ensure_signed(origin)?;
+        let key = Self::get_module_key_for_caller(netuid, caller);
+
+        // --- 2. Check that the subnet uses commit-reveal and the key is registered on it.
+        ensure!(
+            UseWeightsCommitReveal::<T>::get(netuid),
+            Error::<T>::WeightsCommitRevealDisabled
+        );
+        ensure!(Self::is_registered(netuid, &key), Error::<T>::NotRegistered);
+
+        // --- 3. Store the commit, replacing any previous one. It can be revealed starting with
+        // the first block of the next epoch.
+        let reveal_block = Self::get_next_epoch_block(netuid, Self::get_current_block_number());
+        WeightCommits::<T>::insert(netuid, &key, (commit_hash, reveal_block));
+
+        // --- 4. Emit the tracking event.
+        let uid = Self::get_uid_for_key(netuid, &key);
+        Self::deposit_event(Event::WeightsCommitted(netuid, uid));
+
+        Ok(())
+    }
+
+    /// Reveals previously committed weights, which are then set as if `set_weights` was called.
+    pub fn do_reveal_weights(
+        origin: T::RuntimeOrigin,
+        netuid: u16,
+        uids: Vec<u16>,
+        values: Vec<u16>,
+        salt: Vec<u8>,
+    ) -> dispatch::DispatchResult {
+        // --- 1. Check the caller's signature. This is the key of a registered account, or the
+        // controller of one.
+        let caller = ensure_signed(origin)?;
+        let key = Self::get_module_key_for_caller(netuid, caller);
+
+        // --- 2. Check that the subnet uses commit-reveal.
+        ensure!(
+            UseWeightsCommitReveal::<T>::get(netuid),
+            Error::<T>::WeightsCommitRevealDisabled
+        );
+
+        // --- 3. Check that the reveal happens within the reveal window.
+        let (commit_hash, reveal_block) =
+            WeightCommits::<T>::get(netuid, &key).ok_or(Error::<T>::NoWeightCommit)?;
+        let current_block = Self::get_current_block_number();
+        ensure!(current_block >= reveal_block, Error::<T>::RevealTooEarly);
+        ensure!(
+            current_block < Self::get_reveal_deadline(netuid, reveal_block),
+            Error::<T>::RevealTooLate
+        );
+
+        // --- 4. Check that the weights match the commit.
+        ensure!(
+            Self::hash_weights(
+                netuid,
+                Self::get_uid_for_key(netuid, &key),
+                &uids,
+                &values,
+                &salt
+            ) == commit_hash,
+            Error::<T>::InvalidReveal
+        );
+
+        // --- 5. Consume the commit and set the weights.
+        WeightCommits::<T>::remove(netuid, &key);
+        Self::apply_weights(netuid, key, uids, values)
+    }
+
+    /// Returns the hash a module has to commit for the given weights. The hash is bound to the
+    /// uid rather than the key, so a commit stays revealable across a key swap.
+    pub fn hash_weights(
+        netuid: u16,
+        uid: u16,
+        uids: &[u16],
+        values: &[u16],
+        salt: &[u8],
+    ) -> T::Hash {
+        T::Hashing::hash_of(&(netuid, uid, uids, values, salt))
+    }
+
+    /// Returns the first block of the epoch following the given block.
+    pub fn get_next_epoch_block(netuid: u16, block_number: u64) -> u64 {
+        let tempo = Self::get_tempo(netuid).max(1) as u64;
+        let into_epoch = (block_number + Self::get_epoch_offset(netuid)) % tempo;
+        block_number + tempo - into_epoch
+    }
+
+    /// Returns the block at which commits revealable at `reveal_block` expire.
+    fn get_reveal_deadline(netuid: u16, reveal_block: u64) -> u64 {
+        reveal_block.saturating_add(WeightsRevealWindow::<T>::get(netuid) as u64)
+    }
+
+    /// Removes the commits of the subnet that were not revealed in time.
+    pub fn expire_weight_commits(netuid: u16, block_number: u64) {
+        let expired: Vec<_> = WeightCommits::<T>::iter_prefix(netuid)
+            .filter(|(_, (_, reveal_block))| {
+                block_number >= Self::get_reveal_deadline(netuid, *reveal_block)
+            })
+            .map(|(key, _)| key)
+            .collect();
+
+        for key in expired {
+            WeightCommits::<T>::remove(netuid, &key);
+            Self::deposit_event(Event::WeightCommitExpired(netuid, key));
+        }
+    }
+
+    fn apply_weights(
+        netuid: u16,
+        key: T::AccountId,
+        uids: Vec<u16>,
+        values: Vec<u16>,
+    ) -> dispatch::DispatchResult {
         // --- 2. Check that the length of uid list and value list are equal for this network.
         ensure!(
             uids.len() == values.len(),
@@ -21,6 +21,7 @@ fn test_local_subnet_burn() {
         SubspaceModule::set_max_registrations_per_block(5);
 
         // register the general subnet
+        whitelist([U256::from(0)]);
         assert_ok!(register_module(0, U256::from(0), to_nano(20)));
         // Adjust max registrations per block to a high number.
         // We will be doing "registration raid"
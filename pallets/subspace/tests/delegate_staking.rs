@@ -1,9 +1,9 @@
 mod mock;
 
-use frame_support::assert_ok;
+use frame_support::{assert_err, assert_ok};
 use log::info;
 use mock::*;
-use pallet_subspace::Tempo;
+use pallet_subspace::{DelegationCount, Error, Tempo, UnsettledDelegationRewards};
 use sp_core::U256;
 use substrate_fixed::types::I64F64;
 
@@ -20,6 +20,7 @@ fn test_ownership_ratio() {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
 
+        whitelist((0..num_modules).map(U256::from));
         register_n_modules(netuid, num_modules, stake_per_module);
         Tempo::<Test>::insert(netuid, tempo);
 
@@ -187,7 +188,14 @@ fn test_ownership_ratio() {
         let total_new_tokens = total_tokens_after - total_tokens_before;
         info!("total_tokens_after: {total_tokens_before:?}");
         info!("total_new_tokens: {total_new_tokens:?}");
-        assert_eq!(total_new_tokens, total_emissions);
+
+        // the rounding dust of the delegator rewards stays in the stake of the module
+        let delegate_pending: u64 = delegate_keys
+            .iter()
+            .map(|k| SubspaceModule::get_pending_delegation_rewards(netuid, k, &voter_key))
+            .sum();
+        let dust = UnsettledDelegationRewards::<Test>::get(netuid, voter_key) - delegate_pending;
+        assert_eq!(total_new_tokens + dust, total_emissions);
 
         let stake_from_vector = SubspaceModule::get_stake_from_vector(netuid, &voter_key);
         let _stake: u64 = SubspaceModule::get_stake(netuid, &voter_key);
@@ -196,3 +204,111 @@ fn test_ownership_ratio() {
         info!("stake_from_vector: {stake_from_vector:?}");
     });
 }
+
+#[test]
+fn test_delegation_rewards_are_settled_lazily() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        let stake_per_module: u64 = 1_000_000_000;
+        SubspaceModule::set_min_burn(0);
+
+        whitelist((0..3u16).map(U256::from));
+        register_n_modules(netuid, 3, stake_per_module);
+        Tempo::<Test>::insert(netuid, 1);
+
+        let keys = SubspaceModule::get_keys(netuid);
+        let voter_key = keys[0];
+        let delegate_key = U256::from(100);
+        add_balance(delegate_key, stake_per_module + 1);
+        assert_ok!(SubspaceModule::add_stake(
+            get_origin(delegate_key),
+            netuid,
+            voter_key,
+            stake_per_module
+        ));
+
+        assert_ok!(SubspaceModule::set_weights(
+            get_origin(voter_key),
+            netuid,
+            vec![1, 2],
+            vec![1, 1],
+        ));
+        step_epoch(netuid);
+
+        // the epoch adds the rewards to the module stake, the delegation is untouched
+        let pending =
+            SubspaceModule::get_pending_delegation_rewards(netuid, &delegate_key, &voter_key);
+        assert!(pending > 0);
+        assert!(UnsettledDelegationRewards::<Test>::get(netuid, voter_key) >= pending);
+        assert_eq!(
+            SubspaceModule::get_stake_to_vector(netuid, &delegate_key)[&voter_key],
+            stake_per_module
+        );
+        assert_eq!(
+            SubspaceModule::get_stake_to_module(netuid, &delegate_key, &voter_key),
+            stake_per_module + pending
+        );
+
+        // claiming moves them into the delegation, the module and the subnet already had them
+        let module_stake = SubspaceModule::get_stake(netuid, &voter_key);
+        let total_stake = SubspaceModule::get_total_subnet_stake(netuid);
+        assert_ok!(SubspaceModule::claim_delegation_rewards(
+            get_origin(delegate_key),
+            netuid,
+            voter_key
+        ));
+        assert_eq!(
+            SubspaceModule::get_stake_to_vector(netuid, &delegate_key)[&voter_key],
+            stake_per_module + pending
+        );
+        assert_eq!(SubspaceModule::get_stake(netuid, &voter_key), module_stake);
+        assert_eq!(SubspaceModule::get_total_subnet_stake(netuid), total_stake);
+        assert_err!(
+            SubspaceModule::claim_delegation_rewards(get_origin(delegate_key), netuid, voter_key),
+            Error::<Test>::NoDelegationRewards
+        );
+    });
+}
+
+#[test]
+fn test_delegation_count_follows_the_delegations() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        let stake_per_module: u64 = 1_000_000_000;
+        SubspaceModule::set_min_burn(0);
+
+        whitelist((0..2u16).map(U256::from));
+        register_n_modules(netuid, 2, stake_per_module);
+        let count = DelegationCount::<Test>::get(netuid);
+
+        let keys = SubspaceModule::get_keys(netuid);
+        let delegate_key = U256::from(100);
+        add_balance(delegate_key, stake_per_module * 3);
+        for module_key in &keys {
+            assert_ok!(SubspaceModule::add_stake(
+                get_origin(delegate_key),
+                netuid,
+                *module_key,
+                stake_per_module
+            ));
+        }
+        assert_eq!(DelegationCount::<Test>::get(netuid), count + 2);
+
+        // adding to an existing delegation does not count it again
+        assert_ok!(SubspaceModule::add_stake(
+            get_origin(delegate_key),
+            netuid,
+            keys[0],
+            1
+        ));
+        assert_eq!(DelegationCount::<Test>::get(netuid), count + 2);
+
+        assert_ok!(SubspaceModule::remove_stake(
+            get_origin(delegate_key),
+            netuid,
+            keys[0],
+            stake_per_module + 1
+        ));
+        assert_eq!(DelegationCount::<Test>::get(netuid), count + 1);
+    });
+}
@@ -5,6 +5,7 @@ use frame_support::{
     traits::{Everything, Hooks},
 };
 use frame_system as system;
+use frame_system::EnsureRoot;
 use pallet_subspace::{Address, MaxRegistrationsPerInterval, Name};
 use sp_core::{H256, U256};
 use sp_runtime::{
@@ -22,6 +23,7 @@ frame_support::construct_runtime!(
         System: frame_system,
         Balances: pallet_balances,
         SubspaceModule: pallet_subspace,
+        Preimage: pallet_preimage,
     }
 );
 
@@ -104,10 +106,20 @@ impl system::Config for Test {
     type PostTransactions = ();
 }
 
+impl pallet_preimage::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type WeightInfo = ();
+    type Currency = Balances;
+    type ManagerOrigin = EnsureRoot<U256>;
+    type Consideration = ();
+}
+
 impl pallet_subspace::Config for Test {
     type RuntimeEvent = RuntimeEvent;
     type Currency = Balances;
     type WeightInfo = ();
+    type RuntimeCall = RuntimeCall;
+    type Preimages = Preimage;
 }
 
 #[allow(dead_code)]
@@ -177,6 +189,14 @@ pub fn get_origin(key: U256) -> RuntimeOrigin {
     <<Test as frame_system::Config>::RuntimeOrigin>::signed(key)
 }
 
+/// The general subnet only takes whitelisted modules.
+#[allow(dead_code)]
+pub fn whitelist(keys: impl IntoIterator<Item = U256>) {
+    for key in keys {
+        SubspaceModule::insert_to_whitelist(key, 1);
+    }
+}
+
 #[allow(dead_code)]
 pub fn register_n_modules(netuid: u16, n: u16, stake: u64) {
     for i in 0..n {
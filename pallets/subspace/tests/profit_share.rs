@@ -18,6 +18,7 @@ fn test_add_profit_share() {
         SubspaceModule::set_min_burn(0);
         FloorFounderShare::<Test>::put(0);
 
+        whitelist([miner_key, voter_key]);
         register_module(netuid, miner_key, 1_000_000_000).expect("register miner module failed");
         register_module(netuid, voter_key, 1_000_000_000).expect("register voter module failed");
         let miner_uid = SubspaceModule::get_uid_for_key(netuid, &miner_key);
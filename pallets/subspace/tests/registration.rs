@@ -1,6 +1,6 @@
 mod mock;
 
-use std::collections::BTreeSet;
+use std::{array::from_fn, collections::BTreeSet};
 
 use frame_support::{assert_err, assert_noop, assert_ok};
 use mock::*;
@@ -8,8 +8,11 @@ use sp_core::U256;
 
 use log::info;
 use pallet_subspace::{
-    voting::ApplicationStatus, CuratorApplications, Emission, Error, MaxAllowedModules,
-    MaxAllowedUids, MinStake, RegistrationsPerBlock, Stake, SubnetGaps, SubnetNames, TotalSubnets,
+    council::CURATOR_MOTION_DURATION,
+    voting::{ApplicationStatus, Conviction},
+    CuratorApplications, CuratorMotions, Emission, Error, MaxAllowedModules, MaxAllowedUids,
+    MinStake, PendingDelegationFee, ProposalCost, Proposals, RegistrationsPerBlock, Stake,
+    SubnetGaps, SubnetNames, TotalSubnets, UseWeightsCommitReveal, VoteConvictions, WeightCommits,
     N,
 };
 use sp_runtime::{DispatchResult, Percent};
@@ -42,6 +45,7 @@ fn test_min_stake() {
             .map(U256::from)
             .collect();
 
+        whitelist(keys_list.clone());
         let min_stake_to_register = MinStake::<Test>::get(netuid);
 
         for key in keys_list {
@@ -69,6 +73,7 @@ fn test_max_registration() {
         assert_eq!(RegistrationsPerBlock::<Test>::get(), 0);
 
         SubspaceModule::set_max_registrations_per_block(1000);
+        whitelist((1..max_registrations_per_block * rounds).map(U256::from));
         for i in 1..(max_registrations_per_block * rounds) {
             let key = U256::from(i);
             assert_ok!(register_module(netuid, U256::from(i), to_nano(100)));
@@ -93,6 +98,7 @@ fn test_delegate_register() {
         SubspaceModule::set_min_burn(0);
 
         SubspaceModule::add_balance_to_account(&key, stake_amount * n as u64);
+        whitelist(module_keys.clone());
         for module_key in module_keys {
             delegate_register_module(netuid, key, module_key, stake_amount)
                 .expect("delegate register module failed");
@@ -116,6 +122,7 @@ fn test_registration_ok() {
 
         // make sure there is some balance
         add_balance(key, 2);
+        whitelist([key]);
         register_module(netuid, key, 1)
             .unwrap_or_else(|_| panic!("register module failed for key {key:?}"));
 
@@ -144,6 +151,7 @@ fn test_many_registrations() {
         SubspaceModule::set_min_burn(0);
 
         SubspaceModule::set_max_registrations_per_block(n);
+        whitelist((0..n).map(U256::from));
         for i in 0..n {
             register_module(netuid, U256::from(i), stake).unwrap_or_else(|_| {
                 panic!("Failed to register module with key: {i:?} and stake: {stake:?}",)
@@ -166,6 +174,7 @@ fn test_registration_with_stake() {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
 
+        whitelist((0..stake_vector.len()).map(U256::from));
         for (i, stake) in stake_vector.iter().enumerate() {
             let uid: u16 = i as u16;
             let stake_value: u64 = *stake;
@@ -193,6 +202,7 @@ fn register_same_key_twice() {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
 
+        whitelist([key]);
         assert_ok!(register_module(netuid, key, stake));
         assert_err!(
             register_module(netuid, key, stake),
@@ -207,7 +217,7 @@ fn test_whitelist() {
         let key = U256::from(0);
         let adding_key = U256::from(1);
         let mut params = SubspaceModule::global_params();
-        params.curator = key;
+        params.curator_council = BTreeSet::from([key]);
         SubspaceModule::set_global_params(params);
 
         let proposal_cost = SubspaceModule::get_general_subnet_application_cost();
@@ -294,6 +304,7 @@ fn validates_module_on_registration() {
     new_test_ext().execute_with(|| {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
+        whitelist([U256::from(0), U256::from(1)]);
         test_validation_cases(|name, addr| register_custom(0, 0.into(), name, addr));
 
         assert_err!(
@@ -312,6 +323,7 @@ fn validates_module_on_update() {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
 
+        whitelist([key_0]);
         assert_ok!(register_custom(subnet, key_0, b"test", b"0.0.0.0:1"));
 
         test_validation_cases(|name, addr| {
@@ -327,6 +339,7 @@ fn validates_module_on_update() {
 
         let key_1: U256 = 1.into();
         let origin_1 = get_origin(key_1);
+        whitelist([key_1]);
         assert_ok!(register_custom(0, key_1, b"test2", b"0.0.0.0:2"));
 
         let update_module = |name: &[u8], addr: &[u8]| {
@@ -359,10 +372,207 @@ fn validates_module_on_update() {
     });
 }
 
+#[test]
+fn delegation_fee_increase_is_timelocked() {
+    new_test_ext().execute_with(|| {
+        let netuid = 0;
+        let key: U256 = 0.into();
+        // make sure that the results won´t get affected by burn
+        SubspaceModule::set_min_burn(0);
+
+        whitelist([key]);
+        assert_ok!(register_custom(netuid, key, b"test", b"0.0.0.0:1"));
+
+        let update_fee = |fee: u8| {
+            SubspaceModule::update_module(
+                get_origin(key),
+                netuid,
+                b"test".to_vec(),
+                b"0.0.0.0:1".to_vec(),
+                Some(Percent::from_percent(fee)),
+                None,
+            )
+        };
+        let delegation_fee = || SubspaceModule::module_params(netuid, &key).delegation_fee;
+
+        // the fee starts at 20%, jumping to 100% is above the cap
+        assert_err!(
+            update_fee(100),
+            Error::<Test>::DelegationFeeIncreaseTooLarge
+        );
+
+        // increases are only announced
+        assert_ok!(update_fee(30));
+        assert_eq!(delegation_fee(), Percent::from_percent(20));
+        let (pending_fee, activation_block) =
+            PendingDelegationFee::<Test>::get(netuid, key).expect("increase was not announced");
+        assert_eq!(pending_fee, Percent::from_percent(30));
+
+        run_to_block(activation_block - 1);
+        assert_eq!(delegation_fee(), Percent::from_percent(20));
+
+        // and become active on the first epoch after the delay
+        step_block(SubspaceModule::get_tempo(netuid));
+        assert_eq!(delegation_fee(), Percent::from_percent(30));
+        assert!(PendingDelegationFee::<Test>::get(netuid, key).is_none());
+
+        // decreases apply right away
+        assert_ok!(update_fee(25));
+        assert_eq!(delegation_fee(), Percent::from_percent(25));
+
+        // setting the current fee again withdraws an announced increase
+        assert_ok!(update_fee(30));
+        assert!(PendingDelegationFee::<Test>::get(netuid, key).is_some());
+        assert_ok!(update_fee(25));
+        assert!(PendingDelegationFee::<Test>::get(netuid, key).is_none());
+        assert_eq!(delegation_fee(), Percent::from_percent(25));
+    });
+}
+
+#[test]
+fn swap_module_key_keeps_uid_and_stake() {
+    new_test_ext().execute_with(|| {
+        let old_key: U256 = 0.into();
+        let new_key: U256 = 10.into();
+        let delegator: U256 = 5.into();
+        let stake = to_nano(100);
+        // make sure that the results won´t get affected by burn
+        SubspaceModule::set_min_burn(0);
+
+        whitelist([old_key, U256::from(1)]);
+        assert_ok!(register_module(0, old_key, stake));
+        assert_ok!(register_module(1, old_key, stake));
+        assert_ok!(register_module(0, 1.into(), stake));
+
+        add_balance(delegator, stake + 1);
+        assert_ok!(SubspaceModule::add_stake(
+            get_origin(delegator),
+            0,
+            old_key,
+            stake
+        ));
+
+        let uids: Vec<u16> = [0, 1]
+            .iter()
+            .map(|netuid| SubspaceModule::get_uid_for_key(*netuid, &old_key))
+            .collect();
+
+        // the new key can not be an already registered module
+        assert_err!(
+            SubspaceModule::swap_module_key(get_origin(old_key), 0, 1.into()),
+            Error::<Test>::KeyAlreadyRegistered
+        );
+        assert_ok!(SubspaceModule::swap_module_key(
+            get_origin(old_key),
+            0,
+            new_key
+        ));
+
+        for (netuid, uid) in [0, 1].into_iter().zip(uids) {
+            assert!(!SubspaceModule::is_registered(netuid, &old_key));
+            assert_eq!(SubspaceModule::get_uid_for_key(netuid, &new_key), uid);
+            assert_eq!(SubspaceModule::get_key_for_uid(netuid, uid), Some(new_key));
+            assert_eq!(
+                SubspaceModule::get_stake_to_module(netuid, &new_key, &new_key),
+                stake
+            );
+            assert_eq!(SubspaceModule::get_stake(netuid, &old_key), 0);
+        }
+
+        assert_eq!(SubspaceModule::get_stake(0, &new_key), stake * 2);
+        assert_eq!(SubspaceModule::get_stake(1, &new_key), stake);
+        assert_eq!(
+            SubspaceModule::get_stake_to_module(0, &delegator, &new_key),
+            stake
+        );
+        assert_eq!(
+            SubspaceModule::get_stake_to_module(0, &delegator, &old_key),
+            0
+        );
+        assert_eq!(SubspaceModule::get_stake_from_vector(0, &new_key).len(), 2);
+    });
+}
+
+#[test]
+fn swap_module_key_moves_whitelist_commits_and_votes() {
+    new_test_ext().execute_with(|| {
+        let old_key: U256 = 0.into();
+        let new_key: U256 = 10.into();
+        let delegator: U256 = 1.into();
+        let stake = to_nano(100);
+        SubspaceModule::set_min_burn(0);
+        ProposalCost::<Test>::set(0);
+
+        whitelist([old_key, delegator]);
+        assert_ok!(register_module(0, old_key, stake));
+        assert_ok!(register_module(0, delegator, stake));
+        assert!(SubspaceModule::is_in_legit_whitelist(&old_key));
+
+        UseWeightsCommitReveal::<Test>::insert(0, true);
+        assert_ok!(SubspaceModule::commit_weights(
+            get_origin(old_key),
+            0,
+            Default::default()
+        ));
+
+        assert_ok!(SubspaceModule::delegate_vote(
+            get_origin(delegator),
+            old_key,
+            None
+        ));
+        assert_ok!(SubspaceModule::add_custom_proposal(
+            get_origin(old_key),
+            b"test".to_vec()
+        ));
+        assert_ok!(SubspaceModule::vote_proposal_with_conviction(
+            get_origin(old_key),
+            0,
+            true,
+            Conviction::Locked2x
+        ));
+        let locks = SubspaceModule::get_stake_locks(&old_key);
+        assert_eq!(locks.len(), 1);
+
+        assert_ok!(SubspaceModule::swap_module_key(
+            get_origin(old_key),
+            0,
+            new_key
+        ));
+
+        assert!(!SubspaceModule::is_in_legit_whitelist(&old_key));
+        assert!(SubspaceModule::is_in_legit_whitelist(&new_key));
+
+        assert!(WeightCommits::<Test>::get(0, old_key).is_none());
+        assert!(WeightCommits::<Test>::get(0, new_key).is_some());
+
+        assert!(SubspaceModule::get_stake_locks(&old_key).is_empty());
+        assert_eq!(SubspaceModule::get_stake_locks(&new_key), locks);
+
+        assert_eq!(
+            SubspaceModule::get_vote_delegatee(&delegator, None),
+            Some(new_key)
+        );
+        assert_eq!(
+            SubspaceModule::get_vote_delegators(&new_key, None),
+            BTreeSet::from([delegator])
+        );
+        assert!(SubspaceModule::get_vote_delegators(&old_key, None).is_empty());
+
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
+        assert!(!proposal.votes_for.contains_key(&old_key));
+        assert!(proposal.votes_for.contains_key(&new_key));
+        assert_eq!(
+            VoteConvictions::<Test>::get(0, new_key),
+            Conviction::Locked2x
+        );
+    });
+}
+
 #[test]
 fn deregister_within_subnet_when_limit_is_reached() {
     new_test_ext().execute_with(|| {
         MaxAllowedModules::<Test>::set(3);
+        whitelist([U256::from(0), U256::from(2)]);
         assert_ok!(register_module(0, 0.into(), to_nano(10_000)));
         assert_ok!(register_module(1, 1.into(), to_nano(5_000)));
 
@@ -387,6 +597,7 @@ fn deregister_globally_when_global_limit_is_reached() {
     new_test_ext().execute_with(|| {
         MaxAllowedModules::<Test>::set(2);
 
+        whitelist([U256::from(0), U256::from(2)]);
         assert_ok!(register_module(0, 0.into(), to_nano(10_000)));
         assert_ok!(register_module(1, 1.into(), to_nano(5_000)));
 
@@ -418,6 +629,9 @@ fn test_register_invalid_name() {
         // make registrations free
         SubspaceModule::set_min_burn(0);
 
+        // the modules register on the general subnet
+        whitelist((0..5).map(U256::from));
+
         // set min name lenght
         SubspaceModule::set_global_min_name_length(2);
 
@@ -517,6 +731,9 @@ fn test_register_invalid_subnet_name() {
         // Make registrations free
         SubspaceModule::set_min_burn(0);
 
+        // the first subnet created is the general subnet
+        whitelist((0..6).map(U256::from));
+
         // Set min name length
         SubspaceModule::set_global_min_name_length(2);
 
@@ -627,7 +844,7 @@ fn test_remove_from_whitelist() {
     new_test_ext().execute_with(|| {
         let whitelist_key = U256::from(0);
         let module_key = U256::from(1);
-        SubspaceModule::set_curator(whitelist_key);
+        SubspaceModule::set_curator_council(BTreeSet::from([whitelist_key]), 1);
 
         let proposal_cost = SubspaceModule::get_proposal_cost();
         let data = "test".as_bytes().to_vec();
@@ -664,7 +881,7 @@ fn test_invalid_curator() {
         let whitelist_key = U256::from(0);
         let invalid_key = U256::from(1);
         let module_key = U256::from(2);
-        SubspaceModule::set_curator(whitelist_key);
+        SubspaceModule::set_curator_council(BTreeSet::from([whitelist_key]), 1);
 
         // Try to add to whitelist with an invalid curator key
         assert_noop!(
@@ -675,6 +892,177 @@ fn test_invalid_curator() {
     });
 }
 
+#[test]
+fn test_curator_council_motion_needs_threshold() {
+    new_test_ext().execute_with(|| {
+        let curators: [_; 3] = from_fn(|i| U256::from(i + 10));
+        let module_key = U256::from(1);
+        SubspaceModule::set_curator_council(BTreeSet::from(curators), 2);
+
+        let application_cost = SubspaceModule::get_general_subnet_application_cost();
+        add_balance(module_key, application_cost + 1);
+        assert_ok!(SubspaceModule::add_dao_application(
+            get_origin(module_key),
+            module_key,
+            b"test".to_vec(),
+        ));
+
+        // one approval is not enough, and every member approves once
+        assert_ok!(SubspaceModule::add_to_whitelist(
+            get_origin(curators[0]),
+            module_key,
+            1
+        ));
+        assert!(!SubspaceModule::is_in_legit_whitelist(&module_key));
+        assert_noop!(
+            SubspaceModule::add_to_whitelist(get_origin(curators[0]), module_key, 1),
+            Error::<Test>::AlreadyApprovedMotion
+        );
+        assert_noop!(
+            SubspaceModule::add_to_whitelist(get_origin(module_key), module_key, 1),
+            Error::<Test>::NotCurator
+        );
+
+        // approving a different weight is a different motion
+        assert_ok!(SubspaceModule::add_to_whitelist(
+            get_origin(curators[1]),
+            module_key,
+            2
+        ));
+        assert!(!SubspaceModule::is_in_legit_whitelist(&module_key));
+
+        assert_ok!(SubspaceModule::add_to_whitelist(
+            get_origin(curators[2]),
+            module_key,
+            1
+        ));
+        assert!(SubspaceModule::is_in_legit_whitelist(&module_key));
+        assert_eq!(
+            CuratorApplications::<Test>::get(0).unwrap().status,
+            ApplicationStatus::Accepted
+        );
+    });
+}
+
+#[test]
+fn curator_motions_expire_and_reset_with_the_threshold() {
+    new_test_ext().execute_with(|| {
+        let curators: [_; 3] = from_fn(|i| U256::from(i + 10));
+        let module_key = U256::from(1);
+        SubspaceModule::set_curator_council(BTreeSet::from(curators), 2);
+
+        let application_cost = SubspaceModule::get_general_subnet_application_cost();
+        add_balance(module_key, application_cost + 1);
+        assert_ok!(SubspaceModule::add_dao_application(
+            get_origin(module_key),
+            module_key,
+            b"test".to_vec(),
+        ));
+
+        // the approval is dropped once the motion expires
+        assert_ok!(SubspaceModule::add_to_whitelist(
+            get_origin(curators[0]),
+            module_key,
+            1
+        ));
+        let motion = CuratorMotions::<Test>::iter_values().next().unwrap();
+        assert_eq!(
+            motion.expiration_block,
+            block_number() + CURATOR_MOTION_DURATION
+        );
+        run_to_block(motion.expiration_block.next_multiple_of(100));
+        assert_eq!(CuratorMotions::<Test>::iter().count(), 0);
+
+        assert_ok!(SubspaceModule::add_to_whitelist(
+            get_origin(curators[1]),
+            module_key,
+            1
+        ));
+        assert!(!SubspaceModule::is_in_legit_whitelist(&module_key));
+
+        // changing the threshold drops the motions approved under the previous one
+        SubspaceModule::set_curator_council(BTreeSet::from(curators), 3);
+        assert_eq!(CuratorMotions::<Test>::iter().count(), 0);
+    });
+}
+
+#[test]
+fn whitelisting_does_not_pay_back_refused_application() {
+    new_test_ext().execute_with(|| {
+        let curator = U256::from(10);
+        let module_key = U256::from(1);
+        SubspaceModule::set_curator_council(BTreeSet::from([curator]), 1);
+
+        let application_cost = SubspaceModule::get_general_subnet_application_cost();
+        add_balance(module_key, application_cost + 1);
+        assert_ok!(SubspaceModule::add_dao_application(
+            get_origin(module_key),
+            module_key,
+            b"test".to_vec(),
+        ));
+        assert_ok!(SubspaceModule::refuse_dao_application(
+            get_origin(curator),
+            0
+        ));
+        let balance = SubspaceModule::get_balance_u64(&module_key);
+
+        assert_ok!(SubspaceModule::add_to_whitelist(
+            get_origin(curator),
+            module_key,
+            1
+        ));
+        assert!(SubspaceModule::is_in_legit_whitelist(&module_key));
+        assert_eq!(SubspaceModule::get_balance_u64(&module_key), balance);
+        assert_eq!(
+            CuratorApplications::<Test>::get(0).unwrap().status,
+            ApplicationStatus::Refused
+        );
+    });
+}
+
+#[test]
+fn test_general_subnet_requires_whitelist() {
+    new_test_ext().execute_with(|| {
+        SubspaceModule::set_min_burn(0);
+        whitelist([U256::from(0)]);
+        assert_ok!(register_module(0, 0.into(), to_nano(1)));
+
+        let key = U256::from(1);
+        let register = || {
+            SubspaceModule::register(
+                get_origin(key),
+                b"test0".to_vec(),
+                b"module1".to_vec(),
+                b"0.0.0.0:30333".to_vec(),
+                to_nano(1),
+                key,
+                None,
+            )
+        };
+
+        add_balance(key, to_nano(2));
+        assert_noop!(register(), Error::<Test>::NotWhitelisted);
+
+        SubspaceModule::insert_to_whitelist(key, 1);
+        assert_ok!(register());
+        assert!(SubspaceModule::is_registered(0, &key));
+
+        // other subnets do not need the whitelist
+        assert_ok!(register_custom(1, 2.into(), b"module2", b"0.0.0.0:1"));
+        let key = U256::from(3);
+        add_balance(key, to_nano(2));
+        assert_ok!(SubspaceModule::register(
+            get_origin(key),
+            b"test1".to_vec(),
+            b"module3".to_vec(),
+            b"0.0.0.0:30333".to_vec(),
+            to_nano(1),
+            key,
+            None,
+        ));
+    });
+}
+
 #[test]
 fn new_subnet_reutilized_removed_netuid_if_total_is_bigger_than_removed() {
     new_test_ext().execute_with(|| {
@@ -717,6 +1105,7 @@ fn new_subnets_on_removed_uids_register_modules_to_the_correct_netuids() {
         SubspaceModule::set_min_burn(0);
         SubspaceModule::set_global_max_allowed_subnets(3);
 
+        whitelist([U256::from(0)]);
         assert_ok!(register_module(0, 0.into(), to_nano(10)));
         assert_ok!(register_module(1, 1.into(), to_nano(5)));
         assert_ok!(register_module(2, 2.into(), to_nano(1)));
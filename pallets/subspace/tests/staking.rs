@@ -1,9 +1,11 @@
 mod mock;
 
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{assert_err, assert_noop, assert_ok};
 use log::info;
 use mock::*;
-use pallet_subspace::Error;
+use pallet_subspace::{
+    voting::StakeLock, Error, StakeLocks, UnbondingPeriod, UnsettledDelegationRewards,
+};
 use sp_core::U256;
 use substrate_fixed::types::I64F64;
 
@@ -21,6 +23,8 @@ fn test_stake() {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
         SubspaceModule::set_max_registrations_per_block(1000);
+        // the first subnet is the general subnet
+        whitelist((0..max_uids).map(U256::from));
 
         for netuid in netuids {
             info!("NETUID: {}", netuid);
@@ -97,6 +101,7 @@ fn test_multiple_stake() {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
 
+        whitelist((0..n).map(U256::from));
         register_n_modules(netuid, n, 10);
         let controler_key = U256::from(n + 1);
         let og_staker_balance: u64 = total_stake + 1;
@@ -168,6 +173,7 @@ fn test_transfer_stake() {
         let netuid: u16 = 0;
         SubspaceModule::set_min_burn(0);
 
+        whitelist((0..n).map(U256::from));
         register_n_modules(netuid, n, stake_amount);
 
         let keys: Vec<U256> = SubspaceModule::get_keys(netuid);
@@ -200,6 +206,149 @@ fn test_transfer_stake() {
     });
 }
 
+#[test]
+fn test_transfer_stake_across_subnets() {
+    new_test_ext().execute_with(|| {
+        let stake_amount: u64 = to_nano(1_000);
+        let from_netuid: u16 = 0;
+        let to_netuid: u16 = 1;
+        let from_key = U256::from(0);
+        let to_key = U256::from(1);
+        SubspaceModule::set_min_burn(0);
+
+        whitelist([from_key]);
+        assert_ok!(register_module(from_netuid, from_key, stake_amount));
+        assert_ok!(register_module(to_netuid, to_key, stake_amount));
+
+        assert_ok!(SubspaceModule::transfer_stake_across_subnets(
+            get_origin(from_key),
+            from_netuid,
+            from_key,
+            to_netuid,
+            to_key,
+            stake_amount / 2
+        ));
+
+        assert_eq!(
+            SubspaceModule::get_stake(from_netuid, &from_key),
+            stake_amount / 2
+        );
+        assert_eq!(
+            SubspaceModule::get_stake(to_netuid, &to_key),
+            stake_amount + stake_amount / 2
+        );
+        assert_eq!(
+            SubspaceModule::get_total_subnet_stake(from_netuid),
+            stake_amount / 2
+        );
+        assert_eq!(
+            SubspaceModule::get_total_subnet_stake(to_netuid),
+            stake_amount + stake_amount / 2
+        );
+        assert_eq!(
+            SubspaceModule::get_stake_to_module(to_netuid, &from_key, &to_key),
+            stake_amount / 2
+        );
+        // the stake never touches the balance
+        assert_eq!(SubspaceModule::get_balance(&from_key), 1);
+
+        assert_noop!(
+            SubspaceModule::transfer_stake_across_subnets(
+                get_origin(from_key),
+                from_netuid,
+                from_key,
+                to_netuid,
+                to_key,
+                stake_amount
+            ),
+            Error::<Test>::NotEnoughStakeToWithdraw
+        );
+
+        assert_noop!(
+            SubspaceModule::transfer_stake_across_subnets(
+                get_origin(from_key),
+                from_netuid,
+                from_key,
+                from_netuid,
+                from_key,
+                1
+            ),
+            Error::<Test>::TransferToSameSubnet
+        );
+
+        // the unbonding period of the source can not be skipped by moving the stake first
+        UnbondingPeriod::<Test>::insert(from_netuid, 10);
+        assert_noop!(
+            SubspaceModule::transfer_stake_across_subnets(
+                get_origin(from_key),
+                from_netuid,
+                from_key,
+                to_netuid,
+                to_key,
+                1
+            ),
+            Error::<Test>::UnbondingPeriodTooShort
+        );
+    });
+}
+
+#[test]
+fn test_transfer_stake_across_subnets_checks_only_the_source_subnet_locks() {
+    new_test_ext().execute_with(|| {
+        let stake_amount: u64 = to_nano(1_000);
+        let from_netuid: u16 = 0;
+        let to_netuid: u16 = 1;
+        let from_key = U256::from(0);
+        let to_key = U256::from(1);
+        SubspaceModule::set_min_burn(0);
+
+        whitelist([from_key]);
+        assert_ok!(register_module(from_netuid, from_key, stake_amount));
+        assert_ok!(register_module(to_netuid, to_key, stake_amount));
+
+        let lock = |netuid| StakeLock {
+            proposal_id: 0,
+            netuid,
+            amount: stake_amount,
+            unlock_block: 1_000,
+        };
+
+        // stake locked on the whole network can not leave it, but it stays on it when it moves
+        // to another subnet
+        StakeLocks::<Test>::insert(from_key, vec![lock(None)]);
+        assert_err!(
+            SubspaceModule::remove_stake(get_origin(from_key), from_netuid, from_key, 1),
+            Error::<Test>::StakeIsLocked
+        );
+        assert_ok!(SubspaceModule::transfer_stake_across_subnets(
+            get_origin(from_key),
+            from_netuid,
+            from_key,
+            to_netuid,
+            to_key,
+            stake_amount / 2
+        ));
+        assert_eq!(
+            SubspaceModule::get_stake_to_module(to_netuid, &from_key, &to_key),
+            stake_amount / 2
+        );
+
+        // stake locked on the source subnet has to stay there
+        StakeLocks::<Test>::insert(from_key, vec![lock(Some(from_netuid))]);
+        assert_noop!(
+            SubspaceModule::transfer_stake_across_subnets(
+                get_origin(from_key),
+                from_netuid,
+                from_key,
+                to_netuid,
+                to_key,
+                1
+            ),
+            Error::<Test>::StakeIsLocked
+        );
+    });
+}
+
 #[test]
 fn test_delegate_stake() {
     new_test_ext().execute_with(|| {
@@ -211,6 +360,8 @@ fn test_delegate_stake() {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
         SubspaceModule::set_max_registrations_per_block(1000);
+        // the first subnet is the general subnet
+        whitelist((0..max_uids).map(U256::from));
 
         for i in netuids.iter() {
             let netuid = *i;
@@ -308,6 +459,7 @@ fn test_ownership_ratio() {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
 
+        whitelist((0..num_modules).map(U256::from));
         register_n_modules(netuid, num_modules, 10);
 
         let keys = SubspaceModule::get_keys(netuid);
@@ -354,7 +506,9 @@ fn test_ownership_ratio() {
             info!("SUMED STAKE: {sumed_stake}");
             info!("TOTAL STAKE: {total_stake}");
 
-            assert_eq!(stake, sumed_stake);
+            // the rewards of the delegators are in the module stake before they are settled
+            let unsettled = UnsettledDelegationRewards::<Test>::get(netuid, k);
+            assert_eq!(stake, sumed_stake + unsettled);
 
             // for (d_a, o) in ownership_ratios.iter() {
             //     info!("OWNERSHIP RATIO: {}", o);
@@ -373,6 +527,7 @@ fn test_min_stake() {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
 
+        whitelist((0..num_modules).map(U256::from));
         register_n_modules(netuid, num_modules, min_stake);
         let keys = SubspaceModule::get_keys(netuid);
 
@@ -398,6 +553,7 @@ fn test_stake_zero() {
         // Make sure registration cost is not affected
         SubspaceModule::set_min_burn(0);
 
+        whitelist([key]);
         assert_ok!(register_module(netuid, key, stake_amount));
 
         // try to stake zero
@@ -421,6 +577,7 @@ fn test_unstake_zero() {
         // Make sure registration cost is not affected
         SubspaceModule::set_min_burn(0);
 
+        whitelist([key]);
         assert_ok!(register_module(netuid, key, stake_amount));
 
         // try to unstake zero
@@ -432,3 +589,161 @@ fn test_unstake_zero() {
         );
     });
 }
+
+#[test]
+fn test_unbonding_period() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        let key = U256::from(0);
+        let stake_amount: u64 = to_nano(1_000);
+        let unbonding_period: u64 = 10;
+
+        // Make sure registration cost is not affected
+        SubspaceModule::set_min_burn(0);
+        UnbondingPeriod::<Test>::insert(netuid, unbonding_period);
+
+        whitelist([key]);
+        assert_ok!(register_module(netuid, key, stake_amount));
+        assert_eq!(SubspaceModule::get_balance(&key), 1);
+
+        // removing stake only queues it, removals unlocking at the same block share a chunk
+        for _ in 0..2 {
+            assert_ok!(SubspaceModule::remove_stake(
+                get_origin(key),
+                netuid,
+                key,
+                stake_amount / 2
+            ));
+        }
+        assert_eq!(SubspaceModule::get_stake(netuid, &key), 0);
+        assert_eq!(SubspaceModule::get_balance(&key), 1);
+
+        let chunks = SubspaceModule::get_unlocking_chunks(&key);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].amount, stake_amount);
+        assert_eq!(chunks[0].unlock_block, block_number() + unbonding_period);
+        assert_eq!(
+            SubspaceModule::get_unbonding_from(netuid, &key).get(&key),
+            Some(&stake_amount)
+        );
+
+        // nothing can be withdrawn before the period is over
+        assert_noop!(
+            SubspaceModule::withdraw_unbonded(get_origin(key)),
+            Error::<Test>::NothingToWithdraw
+        );
+
+        step_block(unbonding_period as u16);
+
+        assert_ok!(SubspaceModule::withdraw_unbonded(get_origin(key)));
+        assert_eq!(SubspaceModule::get_balance(&key), stake_amount + 1);
+        assert!(SubspaceModule::get_unlocking_chunks(&key).is_empty());
+        assert!(SubspaceModule::get_unbonding_from(netuid, &key).is_empty());
+    });
+}
+
+#[test]
+fn test_deregistration_unbonds_the_stake() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        let module_key = U256::from(0);
+        let delegator_key = U256::from(1);
+        let stake_amount: u64 = to_nano(1_000);
+        let unbonding_period: u64 = 10;
+
+        // Make sure registration cost is not affected
+        SubspaceModule::set_min_burn(0);
+        UnbondingPeriod::<Test>::insert(netuid, unbonding_period);
+
+        whitelist([module_key]);
+        assert_ok!(register_module(netuid, module_key, stake_amount));
+        add_balance(delegator_key, stake_amount + 1);
+        assert_ok!(SubspaceModule::add_stake(
+            get_origin(delegator_key),
+            netuid,
+            module_key,
+            stake_amount
+        ));
+
+        // the module and its delegators only get their stake back after the unbonding period
+        assert_ok!(SubspaceModule::deregister(get_origin(module_key), netuid));
+        let unlock_block = block_number() + unbonding_period;
+        for key in [module_key, delegator_key] {
+            assert_eq!(SubspaceModule::get_balance(&key), 1);
+
+            let chunks = SubspaceModule::get_unlocking_chunks(&key);
+            assert_eq!(chunks.len(), 1);
+            assert_eq!(chunks[0].amount, stake_amount);
+            assert_eq!(chunks[0].unlock_block, unlock_block);
+        }
+
+        step_block(unbonding_period as u16);
+
+        for key in [module_key, delegator_key] {
+            assert_ok!(SubspaceModule::withdraw_unbonded(get_origin(key)));
+            assert_eq!(SubspaceModule::get_balance(&key), stake_amount + 1);
+        }
+    });
+}
+
+#[test]
+fn test_deregistration_never_releases_stake_early_when_the_queue_is_full() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        let module_keys = [U256::from(0), U256::from(1)];
+        let delegator_key = U256::from(2);
+        let stake_amount: u64 = to_nano(1_000);
+        let unbonding_period: u64 = 10;
+
+        // Make sure registration cost is not affected
+        SubspaceModule::set_min_burn(0);
+        UnbondingPeriod::<Test>::insert(netuid, unbonding_period);
+
+        whitelist(module_keys);
+        for module_key in module_keys {
+            assert_ok!(register_module(netuid, module_key, stake_amount));
+        }
+        add_balance(delegator_key, stake_amount * 2 + 1);
+        for module_key in module_keys {
+            assert_ok!(SubspaceModule::add_stake(
+                get_origin(delegator_key),
+                netuid,
+                module_key,
+                stake_amount
+            ));
+        }
+
+        // fill the queue of 32 chunks with removals unlocking at different blocks
+        for _ in 0..32 {
+            assert_ok!(SubspaceModule::remove_stake(
+                get_origin(delegator_key),
+                netuid,
+                module_keys[0],
+                1
+            ));
+            step_block(1);
+        }
+        assert_err!(
+            SubspaceModule::remove_stake(get_origin(delegator_key), netuid, module_keys[0], 1),
+            Error::<Test>::TooManyUnlockingChunks
+        );
+
+        // the stake of the removed module joins the chunk that unlocks last
+        let balance = SubspaceModule::get_balance(&delegator_key);
+        let stake = SubspaceModule::get_stake_to_module(netuid, &delegator_key, &module_keys[1]);
+        let last_chunk = SubspaceModule::get_unlocking_chunks(&delegator_key)
+            .pop()
+            .expect("the queue is full");
+        assert_ok!(SubspaceModule::deregister(
+            get_origin(module_keys[1]),
+            netuid
+        ));
+        assert_eq!(SubspaceModule::get_balance(&delegator_key), balance);
+
+        let chunks = SubspaceModule::get_unlocking_chunks(&delegator_key);
+        assert_eq!(chunks.len(), 32);
+        let merged = chunks.last().expect("the queue is full");
+        assert_eq!(merged.amount, last_chunk.amount + stake);
+        assert_eq!(merged.unlock_block, block_number() + unbonding_period);
+    });
+}
@@ -1,11 +1,13 @@
 mod mock;
 
-use frame_support::assert_ok;
+use frame_support::{assert_ok, weights::Weight};
 use log::info;
 use mock::*;
 use pallet_subspace::{
-    DaoTreasuryDistribution, GlobalDaoTreasury, MaxAllowedWeights, MinAllowedWeights, MinBurn,
-    SubnetStakeThreshold, Tempo, Trust,
+    subnet::ConsensusMode, ConsensusModeSubnet, CuratorWeightRatio, DaoTreasuryDistribution,
+    DeferredEpochs, EpochOffset, EpochsRecorded, GlobalDaoTreasury, MaxAllowedWeights,
+    MaxEpochWeightPerBlock, MinAllowedWeights, MinBurn, PendingEmission, SubnetStakeThreshold,
+    Tempo, Trust,
 };
 use sp_core::U256;
 use sp_runtime::Percent;
@@ -48,6 +50,7 @@ fn test_stale_weights() {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
 
+        whitelist((0..10u16).map(U256::from));
         register_n_modules(0, 10, 1000);
         let _subnet_params = SubspaceModule::subnet_params(netuid);
         let _keys = SubspaceModule::get_keys(netuid);
@@ -63,6 +66,7 @@ fn test_no_weights() {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
 
+        whitelist((0..10u16).map(U256::from));
         register_n_modules(0, 10, 1000);
         Tempo::<Test>::insert(netuid, 1);
         let _keys = SubspaceModule::get_keys(netuid);
@@ -91,6 +95,7 @@ fn test_dividends_same_stake() {
         SubspaceModule::set_min_burn(0);
 
         // SETUP NETWORK
+        whitelist((0..n).map(U256::from));
         register_n_modules(netuid, n, stake_per_module);
         update_params(netuid, 1, n, 0);
 
@@ -178,6 +183,7 @@ fn test_dividends_diff_stake() {
         SubspaceModule::set_min_burn(0);
 
         // SETUP NETWORK
+        whitelist((0..n).map(U256::from));
         for i in 0..n {
             let mut stake = stake_per_module;
             if i == 0 {
@@ -261,6 +267,7 @@ fn test_pruning() {
         SubspaceModule::set_max_registrations_per_block(1000);
 
         // SETUP NETWORK
+        whitelist((0..n).map(U256::from));
         register_n_modules(netuid, n, stake_per_module);
         SubspaceModule::set_max_allowed_modules(n);
         update_params(netuid, 1, n, 0);
@@ -295,6 +302,7 @@ fn test_pruning() {
 
         let new_key: U256 = U256::from(n + 1);
 
+        whitelist([new_key]);
         assert_ok!(register_module(netuid, new_key, stake_per_module));
 
         let is_registered: bool = SubspaceModule::key_registered(netuid, &new_key);
@@ -328,6 +336,7 @@ fn test_lowest_priority_mechanism() {
         SubspaceModule::set_min_burn(0);
         SubspaceModule::set_max_registrations_per_block(1000);
         // SETUP NETWORK
+        whitelist((0..n).map(U256::from));
         register_n_modules(netuid, n, stake_per_module);
 
         update_params(netuid, tempo, n, 0);
@@ -517,6 +526,7 @@ fn test_incentives() {
         SubspaceModule::set_min_burn(0);
 
         // SETUP NETWORK
+        whitelist((0..n).map(U256::from));
         register_n_modules(netuid, n, stake_per_module);
         let mut params = SubspaceModule::subnet_params(netuid);
         params.min_allowed_weights = 0;
@@ -579,6 +589,7 @@ fn test_trust() {
         SubspaceModule::set_min_burn(0);
 
         // SETUP NETWORK
+        whitelist((0..n).map(U256::from));
         register_n_modules(netuid, n, stake_per_module);
         let mut params = SubspaceModule::subnet_params(netuid);
         params.min_allowed_weights = 1;
@@ -618,6 +629,42 @@ fn test_trust() {
     });
 }
 
+#[test]
+fn test_curator_weights_blend_into_incentive() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        let n: u16 = 10;
+        SubspaceModule::set_min_burn(0);
+
+        whitelist((0..n).map(U256::from));
+        register_n_modules(netuid, n, 10_000);
+        let mut params = SubspaceModule::subnet_params(netuid);
+        params.min_allowed_weights = 1;
+        params.max_allowed_weights = n;
+        params.tempo = 100;
+        update_params!(netuid => params.clone());
+
+        // the validators only weigh module 1, the curator only recommends module 2
+        let keys = SubspaceModule::get_keys(netuid);
+        for key in keys.iter() {
+            SubspaceModule::insert_to_whitelist(*key, 0);
+        }
+        SubspaceModule::insert_to_whitelist(keys[2], 100);
+        set_weights(netuid, keys[0], vec![1], vec![1]);
+
+        step_block(params.tempo);
+        let incentives = SubspaceModule::get_incentives(netuid);
+        assert!(incentives[1] > 0);
+        assert_eq!(incentives[2], 0);
+
+        CuratorWeightRatio::<Test>::put(Percent::from_percent(50));
+        step_block(params.tempo);
+        let incentives = SubspaceModule::get_incentives(netuid);
+        assert!(incentives[2] > 0);
+        assert!(incentives[1].abs_diff(incentives[2]) <= 1);
+    });
+}
+
 #[test]
 fn test_founder_share() {
     new_test_ext().execute_with(|| {
@@ -627,6 +674,7 @@ fn test_founder_share() {
         let keys: Vec<U256> = (0..n).map(U256::from).collect();
         let stakes: Vec<u64> = (0..n).map(|_x| initial_stake * 1_000_000_000).collect();
 
+        whitelist(keys.clone());
         let founder_key = keys[0];
         SubspaceModule::set_max_registrations_per_block(1000);
         for i in 0..n {
@@ -692,6 +740,7 @@ fn test_dynamic_burn() {
 
         // Create the subnet
         let subnet_key = U256::from(2050);
+        whitelist([subnet_key]);
         assert_ok!(register_module(netuid, subnet_key, initial_stake));
         // Using the default GlobalParameters:
         // - registration target interval = 2 * tempo (200 blocks)
@@ -718,6 +767,7 @@ fn test_dynamic_burn() {
         let registrations_per_block = 5;
         let n: usize = 1000;
         let stakes: Vec<u64> = (0..n).map(|_| initial_stake * 1_000_000_000).collect();
+        whitelist((0..n).map(U256::from));
         for (i, stake) in stakes.iter().enumerate() {
             let key = U256::from(i);
             assert_ok!(register_module(netuid, key, *stake));
@@ -736,6 +786,7 @@ fn test_dynamic_burn() {
         SubspaceModule::set_max_registrations_per_block(1000);
         // Register only 50 of the target
         let amount: usize = 50;
+        whitelist((n..n + amount).map(U256::from));
         for (i, &stake) in stakes.iter().enumerate().take(amount) {
             let key = U256::from(n + i);
             assert_ok!(register_module(netuid, key, stake));
@@ -764,6 +815,7 @@ fn test_dao_treasury_distribution_for_subnet_owners() {
 
         MinBurn::<Test>::set(0);
 
+        whitelist([general.1]);
         assert_ok!(register_module(general.0, general.1, general.2));
         assert_ok!(register_module(yuma_1.0, yuma_1.1, yuma_1.2));
         assert_ok!(register_module(yuma_2.0, yuma_2.1, yuma_2.2));
@@ -798,3 +850,128 @@ fn test_dao_treasury_distribution_for_subnet_owners() {
         );
     });
 }
+
+#[test]
+fn test_linear_consensus_on_other_subnet() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 1;
+        let n: u16 = 10;
+        let stake_per_module: u64 = 10_000;
+
+        // make sure that the results won´t get affected by burn
+        SubspaceModule::set_min_burn(0);
+        SubspaceModule::set_max_registrations_per_block(1000);
+
+        whitelist((0..n).map(U256::from));
+        register_n_modules(0, n, stake_per_module);
+        for i in 0..n {
+            assert_ok!(register_module(
+                netuid,
+                U256::from(100 + i),
+                stake_per_module
+            ));
+        }
+
+        // the general subnet starts out linear, every other subnet runs yuma
+        assert_eq!(
+            SubspaceModule::subnet_params(0).consensus_mode,
+            ConsensusMode::Linear
+        );
+        assert_eq!(
+            SubspaceModule::subnet_params(netuid).consensus_mode,
+            ConsensusMode::Yuma
+        );
+
+        // yuma would never run with this threshold, linear ignores it
+        SubnetStakeThreshold::<Test>::set(Percent::from_percent(100));
+        update_params!(netuid => { consensus_mode: ConsensusMode::Linear });
+        update_params(netuid, 1, n, 0);
+
+        let keys = SubspaceModule::get_keys(netuid);
+        set_weights(netuid, keys[0], vec![2, 3], vec![2, 1]);
+
+        step_epoch(netuid);
+
+        let incentives: Vec<u16> = SubspaceModule::get_incentives(netuid);
+        assert!(incentives[2] > incentives[3]);
+        assert!(incentives[3] > 0);
+    });
+}
+
+#[test]
+fn test_epoch_history_is_bounded() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+
+        SubspaceModule::set_min_burn(0);
+        SubspaceModule::set_max_registrations_per_block(1000);
+        whitelist((0..5u16).map(U256::from));
+        register_n_modules(netuid, 5, 10_000);
+        update_params!(netuid => { max_epoch_history: 2 });
+
+        for _ in 0..3 {
+            step_epoch(netuid);
+        }
+
+        // only the last two epochs are kept, oldest first
+        let history = SubspaceModule::get_epoch_history(netuid);
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history[1].block - history[0].block,
+            SubspaceModule::get_tempo(netuid) as u64
+        );
+        assert_eq!(history[1].incentive, SubspaceModule::get_incentives(netuid));
+        assert_eq!(history[1].emission, SubspaceModule::get_emissions(netuid));
+        assert_eq!(history[1].keys, SubspaceModule::get_keys(netuid));
+
+        // shrinking the history prunes it right away
+        update_params!(netuid => { max_epoch_history: 1 });
+        assert_eq!(
+            SubspaceModule::get_epoch_history(netuid),
+            vec![history[1].clone()]
+        );
+    });
+}
+
+#[test]
+fn test_epoch_offsets_are_staggered() {
+    new_test_ext().execute_with(|| {
+        SubspaceModule::set_min_burn(0);
+        whitelist([U256::from(0)]);
+        for netuid in 0..3u16 {
+            assert_ok!(register_module(netuid, U256::from(netuid), 10_000));
+        }
+
+        let offsets: Vec<u64> = (0..3u16).map(SubspaceModule::get_epoch_offset).collect();
+        assert_eq!(offsets, vec![0, 1, 2]);
+    });
+}
+
+#[test]
+fn test_epochs_over_weight_limit_are_deferred() {
+    new_test_ext().execute_with(|| {
+        SubspaceModule::set_min_burn(0);
+        whitelist([U256::from(0)]);
+        for netuid in 0..2u16 {
+            assert_ok!(register_module(netuid, U256::from(netuid), 10_000));
+            ConsensusModeSubnet::<Test>::insert(netuid, ConsensusMode::Linear);
+            EpochOffset::<Test>::insert(netuid, 0);
+        }
+        MaxEpochWeightPerBlock::<Test>::put(Weight::from_parts(1, u64::MAX));
+
+        let tempo = SubspaceModule::get_tempo(0) as u64;
+        run_to_block((block_number() / tempo + 1) * tempo);
+
+        // only one of the epochs fits into the block, the other one keeps its emission
+        let deferred = DeferredEpochs::<Test>::get();
+        assert_eq!(deferred.len(), 1);
+        let netuid = deferred[0];
+        assert_eq!(EpochsRecorded::<Test>::get(netuid), 0);
+        assert_eq!(EpochsRecorded::<Test>::get(1 - netuid), 1);
+
+        step_block(1);
+        assert!(DeferredEpochs::<Test>::get().is_empty());
+        assert_eq!(EpochsRecorded::<Test>::get(netuid), 1);
+        assert_eq!(PendingEmission::<Test>::get(netuid), 0);
+    });
+}
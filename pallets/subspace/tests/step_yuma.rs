@@ -2,7 +2,7 @@ use crate::mock::*;
 use frame_support::assert_ok;
 use pallet_subspace::{
     yuma::{AccountKey, EmissionMap, ModuleKey, YumaCalc},
-    FloorFounderShare,
+    EpochOverrides, FloorFounderShare, Kappa,
 };
 use sp_core::U256;
 use std::collections::BTreeMap;
@@ -50,6 +50,7 @@ fn test_1_graph() {
         FloorFounderShare::<Test>::put(0);
 
         // Register general subnet
+        whitelist([U256::from(10)]);
         assert_ok!(register_module(0, 10.into(), 1));
 
         log::info!("test_1_graph:");
@@ -121,6 +122,7 @@ fn test_10_graph() {
         FloorFounderShare::<Test>::put(0);
         SubspaceModule::set_max_registrations_per_block(1000);
         // Register general subnet
+        whitelist([U256::from(10_000)]);
         assert_ok!(register_module(0, 10_000.into(), 1));
 
         log::info!("test_10_graph");
@@ -192,6 +194,7 @@ fn yuma_weights_older_than_max_age_are_discarded() {
         let key = U256::from(0);
         let stake_amount: u64 = to_nano(1_000);
 
+        whitelist([key]);
         assert_ok!(register_module(netuid, key, stake_amount));
 
         // Register the yuma subnet.
@@ -286,6 +289,7 @@ fn test_emission_exploit() {
         // Make sure registration cost is not affected
         SubspaceModule::set_min_burn(0);
 
+        whitelist([key]);
         assert_ok!(register_module(netuid, key, stake_amount));
 
         // Register the yuma subnet.
@@ -385,6 +389,7 @@ fn test_tempo_compound() {
         // Make sure registration cost is not affected
         SubspaceModule::set_min_burn(0);
 
+        whitelist([key]);
         assert_ok!(register_module(netuid, key, stake_amount));
 
         // Register the yuma subnets, the important part of the tests starts here:
@@ -414,3 +419,63 @@ fn test_tempo_compound() {
         assert!(fast > slow);
     });
 }
+
+#[test]
+fn test_simulate_epoch_does_not_persist() {
+    new_test_ext().execute_with(|| {
+        SubspaceModule::set_unit_emission(23148148148);
+        SubspaceModule::set_min_burn(0);
+        FloorFounderShare::<Test>::put(0);
+
+        whitelist([U256::from(10)]);
+        assert_ok!(register_module(0, 10.into(), 1));
+
+        let netuid: u16 = 1;
+        let key = U256::from(0);
+        let stake_amount: u64 = to_nano(100);
+
+        assert_ok!(register_module(netuid, key, stake_amount));
+        update_params!(netuid => {
+            max_allowed_uids: 2
+        });
+        assert_ok!(register_module(netuid, key + 1, 1));
+
+        run_to_block(1);
+
+        assert_ok!(SubspaceModule::set_weights(
+            RuntimeOrigin::signed(U256::from(1)),
+            netuid,
+            vec![0],
+            vec![u16::MAX],
+        ));
+
+        let overrides = EpochOverrides {
+            emission: Some(ONE),
+            ..Default::default()
+        };
+        let simulation = SubspaceModule::simulate_epoch(netuid, overrides).unwrap();
+
+        // Same graph as `test_1_graph`, one token is lost to rounding.
+        assert_eq!(
+            simulation.emissions,
+            [(key, [(key, ONE - 1)].into())].into()
+        );
+        assert_eq!(simulation.active_stake.len(), 2);
+        assert_eq!(simulation.bonds.len(), 2);
+
+        // Nothing the simulation touched was kept.
+        assert_eq!(
+            SubspaceModule::get_total_stake_to(netuid, &key),
+            stake_amount
+        );
+
+        let overrides = EpochOverrides {
+            kappa: Some(u16::MAX),
+            ..Default::default()
+        };
+        assert_ok!(SubspaceModule::simulate_epoch(netuid, overrides));
+        assert_eq!(Kappa::<Test>::get(), 32_767);
+
+        assert!(SubspaceModule::simulate_epoch(99, Default::default()).is_err());
+    });
+}
@@ -4,10 +4,13 @@ use frame_support::{assert_err, assert_ok};
 use log::info;
 use mock::*;
 use pallet_subspace::{
-    Dividends, Error, FounderShare, MaximumSetWeightCallsPerEpoch, SubnetNames, Tempo, N,
+    emission::{EmissionAllocation, EmissionCurve, EmissionSchedule},
+    Dividends, EmissionAllocationGlobal, EmissionScheduleGlobal, Error, FounderShare,
+    GlobalDaoTreasury, MaximumSetWeightCallsPerEpoch, SubnetEmission, SubnetNames,
+    SubnetStakeThreshold, Tempo, TotalIssued, N,
 };
 use sp_core::U256;
-use sp_runtime::Percent;
+use sp_runtime::{Perbill, Percent};
 use sp_std::vec;
 
 #[test]
@@ -24,6 +27,8 @@ fn test_add_subnets() {
         SubspaceModule::set_min_burn(0);
         SubspaceModule::set_max_registrations_per_block(1000);
 
+        // the first subnet is the general subnet
+        whitelist((0..n).map(U256::from));
         for i in 0..num_subnets {
             assert_ok!(register_module(i, U256::from(i), stake_per_module));
             for j in 0..n {
@@ -89,6 +94,7 @@ fn test_emission_ratio() {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
 
+        whitelist([U256::from(0)]);
         for i in 0..netuids.len() {
             let _key = U256::from(netuids[i]);
             let netuid = netuids[i];
@@ -121,6 +127,183 @@ fn test_emission_ratio() {
     });
 }
 
+#[test]
+fn test_emission_schedule_follows_issuance() {
+    new_test_ext().execute_with(|| {
+        SubspaceModule::set_unit_emission(100);
+        EmissionScheduleGlobal::<Test>::put(EmissionSchedule {
+            curve: EmissionCurve::Halving { interval: 1_000 },
+            max_supply: 2_500,
+        });
+
+        for (issued, emission) in [(0, 100), (999, 100), (1_000, 50), (2_000, 25), (2_480, 20)] {
+            TotalIssued::<Test>::put(issued);
+            assert_eq!(SubspaceModule::get_total_emission_per_block(), emission);
+        }
+
+        // nothing is emitted past the max supply, no matter the stake
+        TotalIssued::<Test>::put(2_500);
+        assert_eq!(SubspaceModule::get_total_emission_per_block(), 0);
+
+        EmissionScheduleGlobal::<Test>::put(EmissionSchedule {
+            curve: EmissionCurve::Decay {
+                interval: 1_000,
+                rate: Percent::from_percent(10),
+            },
+            max_supply: 10_000,
+        });
+        TotalIssued::<Test>::put(2_000);
+        assert_eq!(SubspaceModule::get_total_emission_per_block(), 81);
+
+        // the projection follows the halvings as the tokens get issued
+        EmissionScheduleGlobal::<Test>::put(EmissionSchedule {
+            curve: EmissionCurve::Halving { interval: 1_000 },
+            max_supply: 2_500,
+        });
+        TotalIssued::<Test>::put(0);
+        let projection: Vec<_> = SubspaceModule::project_emission(30, 10)
+            .into_iter()
+            .map(|point| (point.emission_per_block, point.total_issued))
+            .collect();
+        assert_eq!(
+            projection,
+            vec![(100, 0), (50, 1_000), (50, 1_500), (25, 2_000)]
+        );
+    });
+}
+
+fn allocate(allocation: EmissionAllocation, stakes: &[(u16, u64)]) -> Vec<(u16, f64)> {
+    allocation
+        .allocate(&stakes.iter().copied().collect())
+        .into_iter()
+        .map(|(netuid, share)| (netuid, share.to_num::<f64>()))
+        .collect()
+}
+
+fn assert_shares(shares: Vec<(u16, f64)>, expected: &[(u16, f64)]) {
+    assert_eq!(shares.len(), expected.len());
+    for ((netuid, share), (expected_netuid, expected_share)) in shares.iter().zip(expected) {
+        assert_eq!(netuid, expected_netuid);
+        assert!(
+            (share - expected_share).abs() < 1e-9,
+            "subnet {netuid} got {share}, expected {expected_share}"
+        );
+    }
+}
+
+#[test]
+fn test_stake_emission_allocation() {
+    let shares = allocate(EmissionAllocation::Stake, &[(0, 100), (1, 300)]);
+    assert_shares(shares, &[(0, 0.25), (1, 0.75)]);
+
+    // nothing to split without stake
+    let shares = allocate(EmissionAllocation::Stake, &[(0, 0), (1, 0)]);
+    assert_shares(shares, &[(0, 0.0), (1, 0.0)]);
+}
+
+#[test]
+fn test_sqrt_stake_emission_allocation() {
+    let shares = allocate(EmissionAllocation::SqrtStake, &[(0, 100), (1, 400)]);
+    assert_shares(shares, &[(0, 1.0 / 3.0), (1, 2.0 / 3.0)]);
+}
+
+#[test]
+fn test_capped_stake_emission_allocation() {
+    let capped = |max_share| EmissionAllocation::CappedStake {
+        max_share: Percent::from_percent(max_share),
+    };
+
+    // the excess of the capped subnet goes to the others in proportion to their stake
+    let shares = allocate(capped(50), &[(0, 100), (1, 300), (2, 1_600)]);
+    assert_shares(shares, &[(0, 0.125), (1, 0.375), (2, 0.5)]);
+
+    // spreading the excess can push another subnet over the cap
+    let shares = allocate(capped(40), &[(0, 100), (1, 300), (2, 600)]);
+    assert_shares(shares, &[(0, 0.2), (1, 0.4), (2, 0.4)]);
+
+    // subnets under the cap are left as they are
+    let shares = allocate(capped(50), &[(0, 100), (1, 100)]);
+    assert_shares(shares, &[(0, 0.5), (1, 0.5)]);
+}
+
+#[test]
+fn test_general_subnet_floor_emission_allocation() {
+    let floor = |floor| EmissionAllocation::GeneralSubnetFloor {
+        floor: Percent::from_percent(floor),
+    };
+
+    let shares = allocate(floor(20), &[(0, 100), (1, 300), (2, 600)]);
+    assert_shares(shares, &[(0, 0.2), (1, 0.8 / 3.0), (2, 1.6 / 3.0)]);
+
+    // the floor does not lower the share of the general subnet
+    let shares = allocate(floor(20), &[(0, 500), (1, 500)]);
+    assert_shares(shares, &[(0, 0.5), (1, 0.5)]);
+}
+
+#[test]
+fn test_subnet_emission_follows_allocation() {
+    new_test_ext().execute_with(|| {
+        SubspaceModule::set_min_burn(0);
+        SubnetStakeThreshold::<Test>::set(Percent::from_percent(0));
+        whitelist([U256::from(0)]);
+        assert_ok!(register_module(0, U256::from(0), 1_000_000_000));
+        assert_ok!(register_module(1, U256::from(1), 9_000_000_000));
+
+        EmissionAllocationGlobal::<Test>::put(EmissionAllocation::GeneralSubnetFloor {
+            floor: Percent::from_percent(50),
+        });
+
+        let emission_per_block = SubspaceModule::get_total_emission_per_block();
+        let projection = SubspaceModule::project_subnet_emissions();
+        assert_eq!(projection.len(), 2);
+        for share in projection {
+            assert_eq!(share.share, Perbill::from_percent(50));
+            assert_eq!(share.emission_per_block, emission_per_block / 2);
+            assert_eq!(
+                SubspaceModule::calculate_network_emission(share.netuid, Percent::from_percent(0)),
+                share.emission_per_block
+            );
+        }
+    });
+}
+
+#[test]
+fn test_capped_emission_excess_goes_to_treasury() {
+    new_test_ext().execute_with(|| {
+        SubspaceModule::set_min_burn(0);
+        SubnetStakeThreshold::<Test>::set(Percent::from_percent(0));
+        whitelist([U256::from(0)]);
+        assert_ok!(register_module(0, U256::from(0), 1_000_000_000));
+        assert_ok!(register_module(1, U256::from(1), 9_000_000_000));
+        // no epoch runs, the treasury only gets the unallocated emission
+        Tempo::<Test>::insert(0, 0);
+        Tempo::<Test>::insert(1, 0);
+
+        // both subnets are capped, 40% of the emission is left to none of them
+        EmissionAllocationGlobal::<Test>::put(EmissionAllocation::CappedStake {
+            max_share: Percent::from_percent(30),
+        });
+
+        let emission_per_block = SubspaceModule::get_total_emission_per_block();
+        let unallocated = SubspaceModule::get_unallocated_emission(Percent::from_percent(0));
+        assert!(unallocated.abs_diff(emission_per_block * 2 / 5) <= 1);
+
+        let treasury_before = GlobalDaoTreasury::<Test>::get();
+        let issued_before = TotalIssued::<Test>::get();
+        step_block(1);
+
+        assert_eq!(
+            GlobalDaoTreasury::<Test>::get(),
+            treasury_before + unallocated
+        );
+        let subnet_emission = SubnetEmission::<Test>::get(0) + SubnetEmission::<Test>::get(1);
+        assert_eq!(
+            TotalIssued::<Test>::get(),
+            issued_before + subnet_emission + unallocated
+        );
+    });
+}
+
 #[test]
 fn test_set_max_allowed_uids_growing() {
     new_test_ext().execute_with(|| {
@@ -133,6 +316,7 @@ fn test_set_max_allowed_uids_growing() {
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
 
+        whitelist((0..max_uids + extra_uids * rounds).map(U256::from));
         assert_ok!(register_module(netuid, U256::from(0), stake));
         SubspaceModule::set_max_registrations_per_block(max_uids + extra_uids * rounds);
         for i in 1..max_uids {
@@ -188,6 +372,7 @@ fn test_set_max_allowed_uids_shrinking() {
 
         let mut n = SubspaceModule::get_subnet_n(netuid);
         info!("registering module {}", n);
+        whitelist((0..max_uids + extra_uids).map(U256::from));
         assert_ok!(register_module(netuid, U256::from(0), stake));
         update_params!(netuid => {
             max_allowed_uids: max_uids + extra_uids
@@ -235,6 +420,11 @@ fn test_set_max_allowed_uids_shrinking() {
             params.target_registrations_interval,
             params.target_registrations_per_interval,
             params.max_registrations_per_interval,
+            params.unbonding_period,
+            params.use_weights_commit_reveal,
+            params.weights_reveal_window,
+            params.consensus_mode,
+            params.max_epoch_history,
         );
         let global_params = SubspaceModule::global_params();
         info!("global params {:?}", global_params);
@@ -277,6 +467,7 @@ fn test_set_max_allowed_modules() {
         SubspaceModule::set_min_burn(0);
         SubspaceModule::set_max_registrations_per_block(1000);
         SubspaceModule::set_max_allowed_modules(max_allowed_modules);
+        whitelist((1..2 * max_allowed_modules).map(U256::from));
         // set max_total modules
         for i in 1..(2 * max_allowed_modules) {
             assert_ok!(register_module(netuid, U256::from(i), stake));
@@ -311,6 +502,8 @@ fn test_deregister_subnet_when_overflows_max_allowed_subnets() {
             9_000_000_000,
         ];
 
+        // the last subnet replaces the general subnet, which has the least stake
+        whitelist([U256::from(0), U256::from(3)]);
         for netuid in 0..params.max_allowed_subnets + extra {
             let stake: u64 = stakes[netuid as usize];
             assert_ok!(register_module(netuid, U256::from(netuid), stake));
@@ -351,6 +544,7 @@ fn test_emission_distribution_novote() {
         let expected_stake_change_below = 0;
         let change_tolerance = to_nano(22) as i64; // we tolerate 22 token difference (due to rounding)
 
+        whitelist([U256::from(0)]);
         // first register the general subnet
         assert_ok!(register_module(
             netuid_general,
@@ -442,6 +636,7 @@ fn test_yuma_self_vote() {
         SubspaceModule::set_unit_emission(23148148148);
         SubspaceModule::set_min_burn(0);
 
+        whitelist([U256::from(0)]);
         assert_ok!(register_module(
             netuid_general,
             U256::from(0),
@@ -543,6 +738,7 @@ fn test_emission_activation() {
         SubspaceModule::set_subnet_stake_threshold(Percent::from_percent(5));
         SubspaceModule::set_min_burn(0);
 
+        whitelist([U256::from(0)]);
         // Register the subnets
         for (i, (name, stake, _)) in subnet_stakes.iter().enumerate() {
             assert_ok!(register_module(i as u16, U256::from(i as u64), *stake));
@@ -587,6 +783,7 @@ fn test_parasite_subnet_registrations() {
         let parasite_subnet_stake = to_nano(1_000);
         let parasite_subnet_key = U256::from(1);
 
+        whitelist([main_subnet_key, U256::from(2)]);
         // Register the honest subnet.
         assert_ok!(register_module(
             main_subnet_netuid,
@@ -649,6 +846,7 @@ fn test_subnet_replacing() {
 
         let random_keys = [U256::from(4), U256::from(5)];
 
+        whitelist([subnets[0].0, random_keys[0]]);
         // Register all subnets
         for (i, (subnet_key, subnet_stake)) in subnets.iter().enumerate() {
             assert_ok!(register_module(i as u16, *subnet_key, *subnet_stake));
@@ -688,6 +886,7 @@ fn test_active_stake() {
 
         let general_subnet_stake = to_nano(65_000_000);
         let general_subnet_key = U256::from(0);
+        whitelist([general_subnet_key]);
         assert_ok!(register_module(0, general_subnet_key, general_subnet_stake));
         step_block(1);
         // register 9 subnets reaching the subnet limit,
@@ -785,6 +984,7 @@ fn test_update_same_name() {
         let key = U256::from(0);
         let stake = to_nano(100_000);
 
+        whitelist([key]);
         assert_ok!(register_module(netuid, key, stake));
 
         assert_eq!(N::<Test>::get(0), 1);
@@ -814,6 +1014,11 @@ fn test_update_same_name() {
             params.target_registrations_interval,
             params.target_registrations_per_interval,
             params.max_registrations_per_interval,
+            params.unbonding_period,
+            params.use_weights_commit_reveal,
+            params.weights_reveal_window,
+            params.consensus_mode,
+            params.max_epoch_history,
         );
 
         dbg!(SubnetNames::<Test>::get(netuid));
@@ -830,6 +1035,7 @@ fn test_set_weight_rate_limiting() {
         let key = U256::from(0);
         let stake = to_nano(100_000);
 
+        whitelist([key, U256::from(1)]);
         assert_ok!(register_module(netuid, key, stake));
         assert_ok!(register_module(netuid, 1.into(), stake));
 
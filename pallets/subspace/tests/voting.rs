@@ -1,15 +1,26 @@
 mod mock;
 
-use std::{array::from_fn, collections::BTreeSet};
+use std::{
+    array::from_fn,
+    collections::{BTreeMap, BTreeSet},
+};
 
-use frame_support::{assert_err, assert_ok};
+use frame_support::{
+    assert_err, assert_ok,
+    traits::{OnRuntimeUpgrade, StorageVersion, StorePreimage},
+};
 use mock::*;
 use pallet_subspace::{
-    voting::{ProposalData, ProposalStatus, VoteMode},
-    Error, FloorFounderShare, GlobalDaoTreasury, GlobalParams, MinBurn, ProposalCost,
-    ProposalExpiration, Proposals, SubnetParams, Tempo, VoteModeSubnet,
+    migrations::v15::{MigrateToV15, OldGlobalParams, OldProposal, OldProposalData},
+    treasury::StreamSchedule,
+    voting::{ApplicationStatus, Conviction, ProposalData, ProposalStatus, VoteMode},
+    ActiveProposals, ApplicationVoteMode, ConvictionLockEpochs, CuratorApplications, Error,
+    FloorFounderShare, GlobalDaoTreasury, GlobalParams, MinBurn, ProposalCost,
+    ProposalEnactmentDelay, ProposalExpiration, ProposalRetention, Proposals, ReservedDaoTreasury,
+    RuntimeCallEnactmentDelay, SubnetParams, Tempo, TotalIssued, TreasuryStreams, VoteModeSubnet,
 };
 use sp_core::U256;
+use sp_runtime::Percent;
 
 #[test]
 fn creates_global_params_proposal_correctly_and_expires() {
@@ -26,6 +37,7 @@ fn creates_global_params_proposal_correctly_and_expires() {
 
         let key = U256::from(0);
         add_balance(key, COST + 1);
+        whitelist([U256::from(1), U256::from(2)]);
         assert_ok!(register_module(0, U256::from(1), 1_000_000_000));
         assert_ok!(register_module(0, U256::from(2), 1_000_000_100));
 
@@ -49,12 +61,24 @@ fn creates_global_params_proposal_correctly_and_expires() {
             min_weight_stake,
             adjustment_alpha,
             unit_emission,
-            curator,
+            curator_council,
+            curator_council_threshold,
             subnet_stake_threshold,
             proposal_cost,
             proposal_expiration,
             proposal_participation_threshold,
             general_subnet_application_cost,
+            delegation_fee_increase_delay,
+            max_delegation_fee_increase,
+            emission_schedule,
+            emission_allocation,
+            curator_weight_ratio,
+            runtime_call_participation_threshold,
+            runtime_call_enactment_delay,
+            proposal_enactment_delay,
+            conviction_lock_epochs,
+            application_vote_mode,
+            proposal_retention,
         } = params.clone();
 
         SubspaceModule::add_global_proposal(
@@ -72,12 +96,24 @@ fn creates_global_params_proposal_correctly_and_expires() {
             min_weight_stake,
             adjustment_alpha,
             unit_emission,
-            curator,
+            curator_council,
+            curator_council_threshold,
             subnet_stake_threshold,
             proposal_cost,
             proposal_expiration,
             proposal_participation_threshold,
             general_subnet_application_cost,
+            delegation_fee_increase_delay,
+            max_delegation_fee_increase,
+            emission_schedule,
+            emission_allocation,
+            curator_weight_ratio,
+            runtime_call_participation_threshold,
+            runtime_call_enactment_delay,
+            proposal_enactment_delay,
+            conviction_lock_epochs,
+            application_vote_mode,
+            proposal_retention,
         )
         .expect("failed to create proposal");
 
@@ -113,10 +149,13 @@ fn creates_global_params_proposal_correctly_and_is_approved() {
         const COST: u64 = to_nano(10);
 
         MinBurn::<Test>::set(0);
+        // execute the proposal in the block it is approved
+        ProposalEnactmentDelay::<Test>::set(0);
 
         let keys: [_; 3] = from_fn(U256::from);
         let stakes = [1_000_000_000, 1_000_000_000, 1_000_000_000];
 
+        whitelist(keys);
         for (key, balance) in keys.iter().zip(stakes) {
             assert_ok!(register_module(0, *key, balance));
         }
@@ -144,12 +183,24 @@ fn creates_global_params_proposal_correctly_and_is_approved() {
             min_weight_stake,
             adjustment_alpha,
             unit_emission,
-            curator,
+            curator_council,
+            curator_council_threshold,
             subnet_stake_threshold,
             proposal_cost,
             proposal_expiration,
             proposal_participation_threshold,
             general_subnet_application_cost,
+            delegation_fee_increase_delay,
+            max_delegation_fee_increase,
+            emission_schedule,
+            emission_allocation,
+            curator_weight_ratio,
+            runtime_call_participation_threshold,
+            runtime_call_enactment_delay,
+            proposal_enactment_delay,
+            conviction_lock_epochs,
+            application_vote_mode,
+            proposal_retention,
         } = params.clone();
 
         SubspaceModule::add_global_proposal(
@@ -167,12 +218,24 @@ fn creates_global_params_proposal_correctly_and_is_approved() {
             min_weight_stake,
             adjustment_alpha,
             unit_emission,
-            curator,
+            curator_council,
+            curator_council_threshold,
             subnet_stake_threshold,
             proposal_cost,
             proposal_expiration,
             proposal_participation_threshold,
             general_subnet_application_cost,
+            delegation_fee_increase_delay,
+            max_delegation_fee_increase,
+            emission_schedule,
+            emission_allocation,
+            curator_weight_ratio,
+            runtime_call_participation_threshold,
+            runtime_call_enactment_delay,
+            proposal_enactment_delay,
+            conviction_lock_epochs,
+            application_vote_mode,
+            proposal_retention,
         )
         .expect("failed to create proposal");
 
@@ -209,6 +272,7 @@ fn creates_global_params_proposal_correctly_and_is_refused() {
         let keys: [_; 3] = from_fn(U256::from);
         let stakes = [1_000_000_000, 1_000_000_000, 1_000_000_000];
 
+        whitelist(keys);
         for (key, balance) in keys.iter().zip(stakes) {
             assert_ok!(register_module(0, *key, balance));
         }
@@ -232,12 +296,24 @@ fn creates_global_params_proposal_correctly_and_is_refused() {
             min_weight_stake,
             adjustment_alpha,
             unit_emission,
-            curator,
+            curator_council,
+            curator_council_threshold,
             subnet_stake_threshold,
             proposal_cost,
             proposal_expiration,
             proposal_participation_threshold,
             general_subnet_application_cost,
+            delegation_fee_increase_delay,
+            max_delegation_fee_increase,
+            emission_schedule,
+            emission_allocation,
+            curator_weight_ratio,
+            runtime_call_participation_threshold,
+            runtime_call_enactment_delay,
+            proposal_enactment_delay,
+            conviction_lock_epochs,
+            application_vote_mode,
+            proposal_retention,
         } = GlobalParams {
             min_burn: 100_000_000,
             ..original.clone()
@@ -258,12 +334,24 @@ fn creates_global_params_proposal_correctly_and_is_refused() {
             min_weight_stake,
             adjustment_alpha,
             unit_emission,
-            curator,
+            curator_council,
+            curator_council_threshold,
             subnet_stake_threshold,
             proposal_cost,
             proposal_expiration,
             proposal_participation_threshold,
             general_subnet_application_cost,
+            delegation_fee_increase_delay,
+            max_delegation_fee_increase,
+            emission_schedule,
+            emission_allocation,
+            curator_weight_ratio,
+            runtime_call_participation_threshold,
+            runtime_call_enactment_delay,
+            proposal_enactment_delay,
+            conviction_lock_epochs,
+            application_vote_mode,
+            proposal_retention,
         )
         .expect("failed to create proposal");
 
@@ -293,10 +381,13 @@ fn creates_subnet_params_proposal_correctly_and_is_approved() {
         const COST: u64 = to_nano(10);
 
         MinBurn::<Test>::set(0);
+        // execute the proposal in the block it is approved
+        ProposalEnactmentDelay::<Test>::set(0);
 
         let keys: [_; 3] = from_fn(U256::from);
         let stakes = [1_000_000_000, 1_000_000_000, 1_000_000_000];
 
+        whitelist(keys);
         for (key, balance) in keys.iter().zip(stakes) {
             assert_ok!(register_module(0, *key, balance));
         }
@@ -330,6 +421,11 @@ fn creates_subnet_params_proposal_correctly_and_is_approved() {
             target_registrations_interval,
             target_registrations_per_interval,
             max_registrations_per_interval,
+            unbonding_period,
+            use_weights_commit_reveal,
+            weights_reveal_window,
+            consensus_mode,
+            max_epoch_history,
         } = params.clone();
 
         SubspaceModule::add_subnet_proposal(
@@ -353,6 +449,11 @@ fn creates_subnet_params_proposal_correctly_and_is_approved() {
             target_registrations_interval,
             target_registrations_per_interval,
             max_registrations_per_interval,
+            unbonding_period,
+            use_weights_commit_reveal,
+            weights_reveal_window,
+            consensus_mode,
+            max_epoch_history,
         )
         .expect("failed to create proposal");
 
@@ -389,6 +490,7 @@ fn unregister_vote_from_pending_proposal() {
         MinBurn::<Test>::set(0);
 
         let key = U256::from(0);
+        whitelist([key]);
         assert_ok!(register_module(0, key, 1_000_000_000));
         add_balance(key, COST);
 
@@ -399,11 +501,638 @@ fn unregister_vote_from_pending_proposal() {
 
         SubspaceModule::vote_proposal(get_origin(key), 0, true).unwrap();
         let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
-        assert_eq!(proposal.votes_for, BTreeSet::from([key]));
+        assert_eq!(proposal.votes_for, BTreeMap::from([(key, 1_000_000_000)]));
 
         SubspaceModule::unvote_proposal(get_origin(key), 0).unwrap();
         let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
-        assert_eq!(proposal.votes_for, BTreeSet::from([]));
+        assert_eq!(proposal.votes_for, BTreeMap::from([]));
+    });
+}
+
+#[test]
+fn voting_power_is_recorded_with_the_vote() {
+    new_test_ext().execute_with(|| {
+        const COST: u64 = to_nano(10);
+        const STAKE: u64 = 1_000_000_000;
+
+        MinBurn::<Test>::set(0);
+        ProposalCost::<Test>::set(COST);
+        // keep the proposal pending, and the stake free of rewards
+        SubspaceModule::set_proposal_participation_threshold(Percent::from_percent(100));
+        SubspaceModule::set_unit_emission(0);
+
+        let keys: [_; 2] = from_fn(U256::from);
+        whitelist(keys);
+        for key in keys {
+            assert_ok!(register_module(0, key, STAKE));
+        }
+        add_balance(keys[0], COST);
+
+        SubspaceModule::add_custom_proposal(get_origin(keys[0]), b"test".to_vec())
+            .expect("failed to create proposal");
+        SubspaceModule::vote_proposal(get_origin(keys[0]), 0, true).unwrap();
+
+        // the stake is moved to another account, which votes with it again
+        decrease_stake(0, keys[0], STAKE / 2);
+        increase_stake(0, keys[1], STAKE / 2);
+        SubspaceModule::vote_proposal(get_origin(keys[1]), 0, false).unwrap();
+
+        step_block(100);
+
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
+        assert_eq!(proposal.votes_for, BTreeMap::from([(keys[0], STAKE / 2)]));
+        assert_eq!(
+            proposal.votes_against,
+            BTreeMap::from([(keys[1], STAKE + STAKE / 2)])
+        );
+
+        // staking again does not raise the recorded voting power
+        increase_stake(0, keys[0], STAKE);
+        step_block(100);
+
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
+        assert_eq!(proposal.status, ProposalStatus::Pending);
+        assert_eq!(proposal.votes_for, BTreeMap::from([(keys[0], STAKE / 2)]));
+    });
+}
+
+#[test]
+fn conviction_multiplies_voting_power_and_locks_stake() {
+    new_test_ext().execute_with(|| {
+        const COST: u64 = to_nano(10);
+        const STAKE: u64 = 1_000_000_000;
+
+        MinBurn::<Test>::set(0);
+        ProposalCost::<Test>::set(COST);
+        ProposalEnactmentDelay::<Test>::set(0);
+        ConvictionLockEpochs::<Test>::set(1);
+        SubspaceModule::set_proposal_participation_threshold(Percent::from_percent(50));
+        SubspaceModule::set_unit_emission(0);
+
+        let keys: [_; 2] = from_fn(U256::from);
+        whitelist(keys);
+        assert_ok!(register_module(0, keys[0], STAKE));
+        assert_ok!(register_module(0, keys[1], STAKE * 2));
+        add_balance(keys[0], COST);
+
+        SubspaceModule::add_custom_proposal(get_origin(keys[0]), b"test".to_vec())
+            .expect("failed to create proposal");
+        assert_ok!(SubspaceModule::vote_proposal_with_conviction(
+            get_origin(keys[0]),
+            0,
+            true,
+            Conviction::Locked3x
+        ));
+        assert_ok!(SubspaceModule::vote_proposal(get_origin(keys[1]), 0, false));
+
+        // the locked stake can not be removed, moving it to another module of the subnet keeps
+        // it staked
+        assert_eq!(SubspaceModule::get_locked_stake(&keys[0], None), STAKE);
+        assert_err!(
+            SubspaceModule::remove_stake(get_origin(keys[0]), 0, keys[0], 1),
+            Error::<Test>::StakeIsLocked
+        );
+        assert_ok!(SubspaceModule::transfer_stake(
+            get_origin(keys[0]),
+            0,
+            keys[0],
+            keys[1],
+            1
+        ));
+
+        // three times the stake of the voter outweighs twice the stake against
+        step_block(100);
+
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
+        assert_eq!(proposal.status, ProposalStatus::Accepted);
+
+        // two lock periods of one epoch each
+        let locks = SubspaceModule::get_stake_locks(&keys[0]);
+        assert_eq!(locks.len(), 1);
+        let unlock_block = locks[0].unlock_block;
+        assert_eq!(
+            unlock_block,
+            proposal.creation_block + 2 * SubspaceModule::get_tempo(0) as u64
+        );
+
+        // deregistering does not release the locked stake before the lock expires
+        let balance = SubspaceModule::get_balance_u64(&keys[0]);
+        assert_ok!(SubspaceModule::deregister(get_origin(keys[0]), 0));
+        assert_eq!(SubspaceModule::get_balance_u64(&keys[0]), balance);
+        let chunks = SubspaceModule::get_unlocking_chunks(&keys[0]);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].amount, STAKE - 1);
+        assert_eq!(chunks[0].unlock_block, unlock_block);
+
+        step_block((unlock_block - SubspaceModule::get_current_block_number()) as u16);
+
+        assert!(SubspaceModule::get_stake_locks(&keys[0]).is_empty());
+        assert_ok!(SubspaceModule::remove_stake(
+            get_origin(keys[0]),
+            0,
+            keys[1],
+            1
+        ));
+        assert_ok!(SubspaceModule::withdraw_unbonded(get_origin(keys[0])));
+    });
+}
+
+#[test]
+fn delegated_votes_follow_the_chain_to_a_voter() {
+    new_test_ext().execute_with(|| {
+        const COST: u64 = to_nano(10);
+        const STAKE: u64 = 1_000_000_000;
+
+        MinBurn::<Test>::set(0);
+        ProposalCost::<Test>::set(COST);
+        ProposalEnactmentDelay::<Test>::set(0);
+        SubspaceModule::set_proposal_participation_threshold(Percent::from_percent(50));
+        SubspaceModule::set_unit_emission(0);
+
+        let keys: [_; 5] = from_fn(U256::from);
+        whitelist(keys);
+        for key in keys {
+            assert_ok!(register_module(0, key, STAKE));
+        }
+        add_balance(keys[0], COST * 2);
+
+        assert_err!(
+            SubspaceModule::delegate_vote(get_origin(keys[1]), keys[1], None),
+            Error::<Test>::CannotDelegateVoteToSelf
+        );
+        assert_err!(
+            SubspaceModule::undelegate_vote(get_origin(keys[1]), None),
+            Error::<Test>::VoteNotDelegated
+        );
+
+        // 1 -> 2 -> 0, and a loop between 3 and 4
+        for (delegator, delegatee) in [(1, 2), (2, 0), (3, 4), (4, 3)] {
+            let origin = get_origin(keys[delegator]);
+            assert_ok!(SubspaceModule::delegate_vote(origin, keys[delegatee], None));
+        }
+
+        for id in 0..2 {
+            SubspaceModule::add_custom_proposal(get_origin(keys[0]), b"test".to_vec())
+                .expect("failed to create proposal");
+            assert_ok!(SubspaceModule::vote_proposal(get_origin(keys[0]), id, true));
+        }
+
+        // on the second proposal 2 votes directly against, and 0 no longer gets its vote
+        assert_ok!(SubspaceModule::vote_proposal(get_origin(keys[2]), 1, false));
+
+        // the delegations count as they are when the proposals are resolved: 1 takes its vote
+        // back, and 3 now goes to 2 with 4 along. With only 1 taking its vote back the second
+        // proposal would be tied below the participation threshold
+        assert_ok!(SubspaceModule::undelegate_vote(get_origin(keys[1]), None));
+        assert_ok!(SubspaceModule::delegate_vote(
+            get_origin(keys[3]),
+            keys[2],
+            None
+        ));
+
+        step_block(100);
+
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
+        assert_eq!(proposal.status, ProposalStatus::Accepted);
+
+        let proposal = Proposals::<Test>::get(1).expect("proposal was not created");
+        assert_eq!(proposal.status, ProposalStatus::Refused);
+    });
+}
+
+#[test]
+fn applications_are_voted_on_in_vote_mode() {
+    new_test_ext().execute_with(|| {
+        ProposalEnactmentDelay::<Test>::set(0);
+        ApplicationVoteMode::<Test>::set(VoteMode::Vote);
+        let application_cost = SubspaceModule::get_general_subnet_application_cost();
+
+        let keys: [_; 2] = from_fn(U256::from);
+        whitelist(keys);
+        for key in keys {
+            assert_ok!(register_module(0, key, 1_000_000_000));
+        }
+        add_balance(keys[0], application_cost * 2);
+
+        for application_key in [U256::from(5), U256::from(6)] {
+            assert_ok!(SubspaceModule::add_dao_application(
+                get_origin(keys[0]),
+                application_key,
+                b"test".to_vec()
+            ));
+        }
+
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
+        assert_eq!(
+            proposal.data,
+            ProposalData::DaoApplication { application_id: 0 }
+        );
+        assert_eq!(proposal.proposal_cost, 0);
+
+        for key in keys {
+            assert_ok!(SubspaceModule::vote_proposal(get_origin(key), 0, true));
+            assert_ok!(SubspaceModule::vote_proposal(get_origin(key), 1, false));
+        }
+
+        step_block(100);
+
+        assert_eq!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Accepted
+        );
+        assert_eq!(
+            CuratorApplications::<Test>::get(0).unwrap().status,
+            ApplicationStatus::Accepted
+        );
+        assert_eq!(
+            CuratorApplications::<Test>::get(1).unwrap().status,
+            ApplicationStatus::Refused
+        );
+
+        // only the cost of the accepted application is paid back
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&keys[0]),
+            application_cost + 1
+        );
+    });
+}
+
+#[test]
+fn treasury_stream_releases_over_time_until_cancelled() {
+    new_test_ext().execute_with(|| {
+        const COST: u64 = to_nano(10);
+
+        MinBurn::<Test>::set(0);
+        ProposalCost::<Test>::set(COST);
+        ProposalEnactmentDelay::<Test>::set(0);
+        SubspaceModule::set_unit_emission(0);
+        GlobalDaoTreasury::<Test>::set(1_000);
+
+        let keys: [_; 2] = from_fn(U256::from);
+        whitelist(keys);
+        for key in keys {
+            assert_ok!(register_module(0, key, 1_000_000_000));
+        }
+        add_balance(keys[0], COST * 2);
+        let dest = U256::from(9);
+
+        assert_ok!(SubspaceModule::add_treasury_stream_proposal(
+            get_origin(keys[0]),
+            b"stream".to_vec(),
+            600,
+            dest,
+            StreamSchedule::Linear { duration: 300 }
+        ));
+        for key in keys {
+            assert_ok!(SubspaceModule::vote_proposal(get_origin(key), 0, true));
+        }
+
+        // the stream starts at block 100, and reserves its whole value
+        step_block(100);
+        assert_eq!(ReservedDaoTreasury::<Test>::get(), 600);
+        assert_err!(
+            SubspaceModule::add_transfer_dao_treasury_proposal(
+                get_origin(keys[0]),
+                b"transfer".to_vec(),
+                500,
+                dest
+            ),
+            Error::<Test>::InsufficientDaoTreasuryFunds
+        );
+
+        step_block(100);
+        assert_eq!(SubspaceModule::get_balance_u64(&dest), 200);
+        assert_eq!(SubspaceModule::get_treasury_streams()[0].released, 200);
+
+        assert_ok!(SubspaceModule::add_cancel_treasury_stream_proposal(
+            get_origin(keys[0]),
+            0
+        ));
+        for key in keys {
+            assert_ok!(SubspaceModule::vote_proposal(get_origin(key), 1, true));
+        }
+
+        // what was released until the cancellation is still paid out
+        step_block(100);
+        assert_eq!(SubspaceModule::get_balance_u64(&dest), 400);
+        assert!(TreasuryStreams::<Test>::get(0).is_none());
+        assert_eq!(ReservedDaoTreasury::<Test>::get(), 0);
+        assert_eq!(GlobalDaoTreasury::<Test>::get(), 600);
+
+        // the id of the cancelled stream is not given out again
+        assert_ok!(SubspaceModule::add_treasury_stream_proposal(
+            get_origin(keys[0]),
+            b"tranches".to_vec(),
+            100,
+            dest,
+            StreamSchedule::Tranches(vec![(10, 50), (20, 50)].try_into().unwrap())
+        ));
+        for key in keys {
+            assert_ok!(SubspaceModule::vote_proposal(get_origin(key), 2, true));
+        }
+
+        step_block(100);
+        assert!(TreasuryStreams::<Test>::get(0).is_none());
+        assert_eq!(SubspaceModule::get_treasury_streams()[0].id, 1);
+    });
+}
+
+#[test]
+fn runtime_call_proposal_is_dispatched_after_enactment_delay() {
+    new_test_ext().execute_with(|| {
+        const COST: u64 = to_nano(10);
+
+        MinBurn::<Test>::set(0);
+        ProposalCost::<Test>::set(COST);
+        ProposalExpiration::<Test>::set(100);
+        ProposalEnactmentDelay::<Test>::set(0);
+        RuntimeCallEnactmentDelay::<Test>::set(200);
+
+        let keys: [_; 2] = from_fn(U256::from);
+        whitelist(keys);
+        for key in keys {
+            assert_ok!(register_module(0, key, 1_000_000_000));
+        }
+        add_balance(keys[0], COST);
+
+        // calls that would take up most of the block are rejected right away
+        let call = RuntimeCall::System(frame_system::Call::kill_prefix {
+            prefix: b"key".to_vec(),
+            subkeys: u32::MAX,
+        });
+        let call = <Preimage as StorePreimage>::bound(call).unwrap();
+        assert_err!(
+            SubspaceModule::add_runtime_call_proposal(get_origin(keys[0]), call),
+            Error::<Test>::RuntimeCallTooHeavy
+        );
+
+        let call = RuntimeCall::System(frame_system::Call::set_storage {
+            items: vec![(b"key".to_vec(), b"value".to_vec())],
+        });
+        let call = <Preimage as StorePreimage>::bound(call).unwrap();
+        assert_ok!(SubspaceModule::add_runtime_call_proposal(
+            get_origin(keys[0]),
+            call
+        ));
+
+        for key in keys {
+            SubspaceModule::vote_proposal(get_origin(key), 0, true).unwrap();
+        }
+
+        let stored = || sp_io::storage::get(b"key").map(|value| value.to_vec());
+
+        step_block(200);
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
+        assert_eq!(
+            proposal.status,
+            ProposalStatus::Approved {
+                enactment_block: 300
+            }
+        );
+        assert_eq!(stored(), None);
+
+        // runtime calls can not skip their delay
+        SubspaceModule::set_curator_council(BTreeSet::from([keys[0]]), 1);
+        assert_err!(
+            SubspaceModule::fast_track_proposal(get_origin(keys[0]), 0),
+            Error::<Test>::CannotFastTrackRuntimeCall
+        );
+
+        step_block(100);
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
+        assert_eq!(proposal.status, ProposalStatus::Accepted);
+        assert_eq!(stored(), Some(b"value".to_vec()));
+    });
+}
+
+#[test]
+fn approved_proposal_waits_for_enactment_and_can_be_fast_tracked() {
+    new_test_ext().execute_with(|| {
+        const COST: u64 = to_nano(10);
+
+        MinBurn::<Test>::set(0);
+        ProposalCost::<Test>::set(COST);
+        ProposalEnactmentDelay::<Test>::set(1_000);
+
+        let keys: [_; 2] = from_fn(U256::from);
+        whitelist(keys);
+        for key in keys {
+            assert_ok!(register_module(0, key, 1_000_000_000));
+        }
+        add_balance(keys[0], COST);
+
+        SubspaceModule::add_custom_proposal(get_origin(keys[0]), b"test".to_vec())
+            .expect("failed to create proposal");
+        for key in keys {
+            SubspaceModule::vote_proposal(get_origin(key), 0, true).unwrap();
+        }
+
+        step_block(100);
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
+        assert_eq!(
+            proposal.status,
+            ProposalStatus::Approved {
+                enactment_block: 1_100
+            }
+        );
+        assert_eq!(proposal.finalization_block, None);
+
+        SubspaceModule::set_curator_council(BTreeSet::from([keys[1]]), 1);
+        assert_err!(
+            SubspaceModule::fast_track_proposal(get_origin(keys[0]), 0),
+            Error::<Test>::NotCurator
+        );
+        assert_ok!(SubspaceModule::fast_track_proposal(get_origin(keys[1]), 0));
+
+        step_block(1);
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
+        assert_eq!(proposal.status, ProposalStatus::Accepted);
+        assert_eq!(proposal.finalization_block, Some(101));
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&keys[0]),
+            proposal.proposal_cost + 1
+        );
+    });
+}
+
+#[test]
+fn stale_global_params_proposal_is_refused_at_enactment() {
+    new_test_ext().execute_with(|| {
+        const COST: u64 = to_nano(10);
+
+        MinBurn::<Test>::set(0);
+        FloorFounderShare::<Test>::set(0);
+        ProposalCost::<Test>::set(COST);
+        ProposalEnactmentDelay::<Test>::set(1_000);
+
+        let keys: [_; 2] = from_fn(U256::from);
+        whitelist(keys);
+        for key in keys {
+            assert_ok!(register_module(0, key, 1_000_000_000));
+        }
+        add_balance(keys[0], COST);
+
+        let original = SubspaceModule::global_params();
+        let mut params = original.clone();
+        params.emission_schedule.max_supply = TotalIssued::<Test>::get() + 1_000;
+
+        assert_ok!(SubspaceModule::do_add_global_proposal(
+            get_origin(keys[0]),
+            params.clone()
+        ));
+        for key in keys {
+            SubspaceModule::vote_proposal(get_origin(key), 0, true).unwrap();
+        }
+
+        step_block(100);
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
+        assert_eq!(
+            proposal.status,
+            ProposalStatus::Approved {
+                enactment_block: 1_100
+            }
+        );
+
+        // the issuance passes the proposed max supply while the proposal waits
+        TotalIssued::<Test>::put(params.emission_schedule.max_supply + 1);
+
+        step_block(1_000);
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
+        assert_eq!(proposal.status, ProposalStatus::Refused);
+        assert_eq!(proposal.finalization_block, Some(1_100));
+        assert_eq!(
+            SubspaceModule::global_params().emission_schedule,
+            original.emission_schedule
+        );
+    });
+}
+
+#[test]
+fn finalized_proposals_are_pruned_after_retention() {
+    new_test_ext().execute_with(|| {
+        const COST: u64 = to_nano(10);
+
+        MinBurn::<Test>::set(0);
+        ProposalCost::<Test>::set(COST);
+        ProposalEnactmentDelay::<Test>::set(0);
+        ProposalRetention::<Test>::set(200);
+
+        let keys: [_; 2] = from_fn(U256::from);
+        whitelist(keys);
+        for key in keys {
+            assert_ok!(register_module(0, key, 1_000_000_000));
+        }
+        add_balance(keys[0], COST);
+
+        SubspaceModule::add_custom_proposal(get_origin(keys[0]), b"test".to_vec())
+            .expect("failed to create proposal");
+        assert_eq!(ActiveProposals::<Test>::get(), BTreeSet::from([0]));
+        for key in keys {
+            SubspaceModule::vote_proposal(get_origin(key), 0, true).unwrap();
+        }
+
+        step_block(100);
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not created");
+        assert_eq!(proposal.status, ProposalStatus::Accepted);
+        assert!(ActiveProposals::<Test>::get().is_empty());
+
+        step_block(199);
+        assert!(Proposals::<Test>::get(0).is_some());
+        step_block(1);
+        assert!(Proposals::<Test>::get(0).is_none());
+
+        // ids keep increasing after the proposals are gone
+        SubspaceModule::add_custom_proposal(get_origin(keys[0]), b"test".to_vec())
+            .expect("failed to create proposal");
+        assert!(Proposals::<Test>::get(1).is_some());
+        assert_eq!(ActiveProposals::<Test>::get(), BTreeSet::from([1]));
+    });
+}
+
+#[test]
+fn proposer_can_cancel_or_amend_pending_proposal() {
+    new_test_ext().execute_with(|| {
+        const COST: u64 = to_nano(10);
+
+        MinBurn::<Test>::set(0);
+        ProposalCost::<Test>::set(COST);
+
+        let keys: [_; 2] = from_fn(U256::from);
+        whitelist(keys);
+        for key in keys {
+            assert_ok!(register_module(0, key, 1_000_000_000));
+        }
+        add_balance(keys[0], COST * 2);
+        let balance = SubspaceModule::get_balance_u64(&keys[0]);
+
+        // nobody voted yet, the whole cost is paid back
+        assert_ok!(SubspaceModule::add_custom_proposal(
+            get_origin(keys[0]),
+            b"test".to_vec()
+        ));
+        assert_err!(
+            SubspaceModule::cancel_proposal(get_origin(keys[1]), 0),
+            Error::<Test>::NotProposer
+        );
+        assert_ok!(SubspaceModule::cancel_proposal(get_origin(keys[0]), 0));
+        assert_eq!(SubspaceModule::get_balance_u64(&keys[0]), balance);
+        assert_eq!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Cancelled
+        );
+        assert!(ActiveProposals::<Test>::get().is_empty());
+        assert_err!(
+            SubspaceModule::cancel_proposal(get_origin(keys[0]), 0),
+            Error::<Test>::InvalidProposalStatus
+        );
+        assert_err!(
+            SubspaceModule::amend_proposal(
+                get_origin(keys[0]),
+                0,
+                ProposalData::Custom(b"fixed".to_vec())
+            ),
+            Error::<Test>::InvalidProposalStatus
+        );
+
+        // amending drops the votes and the stake they locked
+        assert_ok!(SubspaceModule::add_custom_proposal(
+            get_origin(keys[0]),
+            b"test".to_vec()
+        ));
+        assert_ok!(SubspaceModule::vote_proposal_with_conviction(
+            get_origin(keys[1]),
+            1,
+            true,
+            Conviction::Locked2x
+        ));
+        assert_eq!(SubspaceModule::get_stake_locks(&keys[1]).len(), 1);
+        assert_err!(
+            SubspaceModule::amend_proposal(get_origin(keys[0]), 1, ProposalData::Expired),
+            Error::<Test>::InvalidProposalData
+        );
+        assert_ok!(SubspaceModule::amend_proposal(
+            get_origin(keys[0]),
+            1,
+            ProposalData::Custom(b"fixed".to_vec())
+        ));
+        let proposal = Proposals::<Test>::get(1).expect("proposal was not created");
+        assert_eq!(proposal.data, ProposalData::Custom(b"fixed".to_vec()));
+        assert!(proposal.votes_for.is_empty());
+        assert!(SubspaceModule::get_stake_locks(&keys[1]).is_empty());
+
+        // once votes were cast, only part of the cost is paid back, the locks are released
+        assert_ok!(SubspaceModule::vote_proposal_with_conviction(
+            get_origin(keys[1]),
+            1,
+            true,
+            Conviction::Locked2x
+        ));
+        assert_ok!(SubspaceModule::cancel_proposal(get_origin(keys[0]), 1));
+        assert!(SubspaceModule::get_stake_locks(&keys[1]).is_empty());
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&keys[0]),
+            balance - COST + COST / 2
+        );
     });
 }
 
@@ -416,6 +1145,7 @@ fn fails_if_insufficient_dao_treasury_fund() {
         GlobalDaoTreasury::<Test>::set(10);
 
         let key = U256::from(0);
+        whitelist([key]);
         assert_ok!(register_module(0, key, 1_000_000_000));
         add_balance(key, COST);
 
@@ -426,3 +1156,65 @@ fn fails_if_insufficient_dao_treasury_fund() {
         )
     });
 }
+
+#[test]
+fn global_params_proposal_survives_v15_migration() {
+    new_test_ext().execute_with(|| {
+        const STAKE: u64 = 1_000_000_000;
+
+        MinBurn::<Test>::set(0);
+        let voter = U256::from(1);
+        let curator = U256::from(9);
+        whitelist([voter]);
+        assert_ok!(register_module(0, voter, STAKE));
+
+        // a proposal as it was encoded before V15, carrying the params of that time
+        let old = OldProposal::<Test> {
+            id: 0,
+            proposer: voter,
+            expiration_block: 1_000,
+            data: OldProposalData::GlobalParams(OldGlobalParams {
+                max_name_length: 32,
+                min_name_length: 2,
+                max_allowed_subnets: 256,
+                max_allowed_modules: 10_000,
+                max_registrations_per_block: 10,
+                max_allowed_weights: 420,
+                min_burn: 0,
+                max_burn: 1_000,
+                floor_delegation_fee: Percent::from_percent(5),
+                min_weight_stake: 0,
+                adjustment_alpha: 0,
+                unit_emission: 23_148_148,
+                curator,
+                subnet_stake_threshold: Percent::from_percent(10),
+                proposal_cost: 1_000,
+                proposal_expiration: 130_000,
+                proposal_participation_threshold: Percent::from_percent(50),
+                general_subnet_application_cost: 1_000,
+                floor_founder_share: 8,
+            }),
+            status: ProposalStatus::Pending,
+            votes_for: BTreeSet::from([voter]),
+            votes_against: BTreeSet::new(),
+            proposal_cost: 1_000,
+            creation_block: 0,
+            finalization_block: None,
+        };
+        frame_support::storage::unhashed::put(&Proposals::<Test>::hashed_key_for(0), &old);
+        StorageVersion::new(14).put::<SubspaceModule>();
+
+        MigrateToV15::<Test>::on_runtime_upgrade();
+
+        let proposal = Proposals::<Test>::get(0).expect("proposal was dropped");
+        let ProposalData::GlobalParams(params) = proposal.data else {
+            panic!("proposal data changed");
+        };
+        assert_eq!(params.max_allowed_weights, 420);
+        assert_eq!(params.unit_emission, 23_148_148);
+        assert_eq!(params.floor_founder_share, 8);
+        assert_eq!(params.curator_council, BTreeSet::from([curator]));
+        assert_eq!(params.curator_council_threshold, 1);
+        assert_eq!(proposal.votes_for, BTreeMap::from([(voter, STAKE)]));
+    });
+}
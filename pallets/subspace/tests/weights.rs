@@ -1,7 +1,7 @@
 mod mock;
 use frame_support::{assert_err, assert_ok};
 
-use pallet_subspace::{Error, FloorFounderShare};
+use pallet_subspace::{Error, FloorFounderShare, WeightCommits, Weights};
 use sp_core::U256;
 use sp_runtime::DispatchError;
 
@@ -19,6 +19,7 @@ fn test_weights_err_weights_vec_not_equal_size() {
         let key_account_id = U256::from(55);
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
+        whitelist([key_account_id]);
         assert_ok!(register_module(netuid, key_account_id, 1_000_000_000));
         let weights_keys: Vec<u16> = vec![1, 2, 3, 4, 5, 6];
         let weight_values: Vec<u16> = vec![1, 2, 3, 4, 5]; // Uneven sizes
@@ -42,6 +43,7 @@ fn test_weights_err_has_duplicate_ids() {
         SubspaceModule::set_min_burn(0);
         SubspaceModule::set_max_registrations_per_block(100);
 
+        whitelist([key_account_id, U256::from(1), U256::from(2), U256::from(3)]);
         assert_ok!(register_module(netuid, key_account_id, 10));
         update_params!(netuid => { max_allowed_uids: 100 });
 
@@ -90,6 +92,7 @@ fn test_set_weights_err_invalid_uid() {
         let netuid: u16 = 0;
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
+        whitelist([key_account_id]);
         assert_ok!(register_module(netuid, key_account_id, 1_000_000_000));
         let weight_keys: Vec<u16> = vec![9999]; // Does not exist
         let weight_values: Vec<u16> = vec![88]; // random value
@@ -113,6 +116,7 @@ fn test_set_weight_not_enough_values() {
         let account_id = U256::from(0);
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
+        whitelist((0..n).map(U256::from));
         assert_ok!(register_module(netuid, account_id, 1_000_000_000));
 
         for i in 1..n {
@@ -167,6 +171,7 @@ fn test_set_max_allowed_uids() {
         let account_id = U256::from(0);
         // make sure that the results won´t get affected by burn
         SubspaceModule::set_min_burn(0);
+        whitelist((0..n).map(U256::from));
         assert_ok!(register_module(netuid, account_id, 1_000_000_000));
         for i in 1..n {
             assert_ok!(register_module(netuid, U256::from(i), 1_000_000_000));
@@ -229,6 +234,7 @@ fn test_min_weight_stake() {
         let module_count: u16 = 16;
         let voter_idx: u16 = 0;
 
+        whitelist((0..module_count).map(U256::from));
         // registers the modules
         for i in 0..module_count {
             assert_ok!(register_module(netuid, U256::from(i), to_nano(10)));
@@ -269,6 +275,7 @@ fn test_weight_age() {
         SubspaceModule::set_max_registrations_per_block(1000);
         FloorFounderShare::<Test>::put(0);
         // Register modules
+        whitelist((0..MODULE_COUNT).map(U256::from));
         (0..MODULE_COUNT).for_each(|i| {
             assert_ok!(register_module(NETUID, U256::from(i), to_nano(10)));
         });
@@ -337,3 +344,158 @@ fn test_weight_age() {
         );
     });
 }
+
+#[test]
+fn test_controller_sets_weights() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        let module_key = U256::from(0);
+        let controller = U256::from(100);
+        // make sure that the results won´t get affected by burn
+        SubspaceModule::set_min_burn(0);
+        SubspaceModule::set_max_registrations_per_block(100);
+
+        whitelist((0..3).map(U256::from));
+        for i in 0..3 {
+            assert_ok!(register_module(netuid, U256::from(i), 1_000_000_000));
+        }
+        update_params!(netuid => { min_allowed_weights: 1 });
+
+        // registered modules can not be controllers
+        assert_err!(
+            SubspaceModule::set_controller(get_origin(module_key), netuid, U256::from(1)),
+            Error::<Test>::InvalidController
+        );
+
+        assert_ok!(SubspaceModule::set_controller(
+            get_origin(module_key),
+            netuid,
+            controller
+        ));
+        assert_eq!(
+            SubspaceModule::module_params(netuid, &module_key).controller,
+            controller
+        );
+
+        // the controller sets weights on behalf of the module
+        assert_ok!(SubspaceModule::set_weights(
+            get_origin(controller),
+            netuid,
+            vec![1, 2],
+            vec![10, 10]
+        ));
+        let weighted_uids: Vec<u16> =
+            Weights::<Test>::get(netuid, 0).into_iter().map(|(uid, _)| uid).collect();
+        assert_eq!(weighted_uids, vec![1, 2]);
+
+        // but has no access to the stake of the module
+        assert_err!(
+            SubspaceModule::remove_stake(get_origin(controller), netuid, module_key, 1),
+            Error::<Test>::NotEnoughStakeToWithdraw
+        );
+
+        assert_ok!(SubspaceModule::clear_controller(
+            get_origin(module_key),
+            netuid
+        ));
+        assert_err!(
+            SubspaceModule::set_weights(get_origin(controller), netuid, vec![1], vec![10]),
+            Error::<Test>::NotRegistered
+        );
+    });
+}
+
+#[test]
+fn test_commit_reveal_weights() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        let key = U256::from(0);
+        let uids: Vec<u16> = vec![1, 2];
+        let values: Vec<u16> = vec![10, 10];
+        let salt: Vec<u8> = b"salt".to_vec();
+        // make sure that the results won´t get affected by burn
+        SubspaceModule::set_min_burn(0);
+        SubspaceModule::set_max_registrations_per_block(100);
+
+        whitelist((0..3).map(U256::from));
+        for i in 0..3 {
+            assert_ok!(register_module(netuid, U256::from(i), 1_000_000_000));
+        }
+        update_params!(netuid => {
+            min_allowed_weights: 1,
+            use_weights_commit_reveal: true,
+            weights_reveal_window: 10
+        });
+
+        // plain weights are rejected
+        assert_err!(
+            SubspaceModule::set_weights(get_origin(key), netuid, uids.clone(), values.clone()),
+            Error::<Test>::WeightsCommitRevealEnabled
+        );
+
+        let hash = SubspaceModule::hash_weights(
+            netuid,
+            SubspaceModule::get_uid_for_key(netuid, &key),
+            &uids,
+            &values,
+            &salt,
+        );
+        assert_ok!(SubspaceModule::commit_weights(
+            get_origin(key),
+            netuid,
+            hash
+        ));
+
+        // weights can not be revealed in the epoch they were committed in
+        assert_err!(
+            SubspaceModule::reveal_weights(
+                get_origin(key),
+                netuid,
+                uids.clone(),
+                values.clone(),
+                salt.clone()
+            ),
+            Error::<Test>::RevealTooEarly
+        );
+
+        let (_, reveal_block) = WeightCommits::<Test>::get(netuid, key).unwrap();
+        run_to_block(reveal_block);
+
+        // the revealed weights have to match the commit
+        assert_err!(
+            SubspaceModule::reveal_weights(
+                get_origin(key),
+                netuid,
+                uids.clone(),
+                vec![10, 20],
+                salt.clone()
+            ),
+            Error::<Test>::InvalidReveal
+        );
+        assert_ok!(SubspaceModule::reveal_weights(
+            get_origin(key),
+            netuid,
+            uids.clone(),
+            values.clone(),
+            salt.clone()
+        ));
+        assert!(!Weights::<Test>::get(netuid, 0).is_empty());
+        assert!(WeightCommits::<Test>::get(netuid, key).is_none());
+
+        // commits that are not revealed in time expire
+        assert_ok!(SubspaceModule::commit_weights(
+            get_origin(key),
+            netuid,
+            hash
+        ));
+        let (_, reveal_block) = WeightCommits::<Test>::get(netuid, key).unwrap();
+        let window = SubspaceModule::subnet_params(netuid).weights_reveal_window as u64;
+        run_to_block(reveal_block + window);
+        assert_err!(
+            SubspaceModule::reveal_weights(get_origin(key), netuid, uids, values, salt),
+            Error::<Test>::RevealTooLate
+        );
+        step_epoch(netuid);
+        assert!(WeightCommits::<Test>::get(netuid, key).is_none());
+    });
+}
@@ -10,6 +10,7 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 use frame_support::{
     genesis_builder_helper::{build_config, create_default_config},
     pallet_prelude::Get,
+    traits::{fungible::HoldConsideration, LinearStoragePrice},
 };
 use pallet_aura::MinimumPeriodTimesTwo;
 use pallet_grandpa::{
@@ -26,11 +27,14 @@ use sp_runtime::{
         One, PostDispatchInfoOf, Verify,
     },
     transaction_validity::{TransactionSource, TransactionValidity, TransactionValidityError},
-    ApplyExtrinsicResult, MultiSignature,
+    ApplyExtrinsicResult, DispatchError, MultiSignature,
 };
 use sp_std::prelude::*;
 use sp_version::RuntimeVersion;
-use subspace_runtime_api::{ModuleInfo, ModuleParams, ModuleStats};
+use subspace_runtime_api::{
+    EmissionProjection, EpochOverrides, EpochRecord, EpochSimulation, ModuleInfo, ModuleParams,
+    ModuleStats, StakeLock, StreamSchedule, SubnetEmissionShare, TreasuryStream, UnlockChunk,
+};
 
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
@@ -111,6 +115,13 @@ pub mod opaque {
 pub type Migrations = (
     pallet_grandpa::migrations::MigrateV4ToV5<Runtime>,
     pallet_subspace::migrations::v10::MigrateToV10<Runtime>,
+    pallet_subspace::migrations::v11::MigrateToV11<Runtime>,
+    pallet_subspace::migrations::v12::MigrateToV12<Runtime>,
+    pallet_subspace::migrations::v13::MigrateToV13<Runtime>,
+    pallet_subspace::migrations::v14::MigrateToV14<Runtime>,
+    pallet_subspace::migrations::v15::MigrateToV15<Runtime>,
+    pallet_subspace::migrations::v16::MigrateToV16<Runtime>,
+    pallet_subspace::migrations::v17::MigrateToV17<Runtime>,
 );
 // To learn more about runtime versioning, see:
 // https://docs.substrate.io/main-docs/build/upgrade#runtime-versioning
@@ -124,7 +135,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     //   `spec_version`, and `authoring_version` are the same between Wasm and native.
     // This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
     //   the compatible custom types.
-    spec_version: 116,
+    spec_version: 117,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
     transaction_version: 1,
@@ -280,7 +291,7 @@ impl pallet_balances::Config for Runtime {
     type AccountStore = System;
     type FreezeIdentifier = ();
     type MaxFreezes = ();
-    type RuntimeHoldReason = ();
+    type RuntimeHoldReason = RuntimeHoldReason;
 
     type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
 
@@ -354,10 +365,32 @@ impl pallet_utility::Config for Runtime {
     type WeightInfo = pallet_utility::weights::SubstrateWeight<Runtime>;
 }
 
+parameter_types! {
+    pub const PreimageBaseDeposit: Balance = 1_000_000_000; // 1 $COMAI
+    pub const PreimageByteDeposit: Balance = 1_000_000;
+    pub const PreimageHoldReason: RuntimeHoldReason =
+        RuntimeHoldReason::Preimage(pallet_preimage::HoldReason::Preimage);
+}
+
+impl pallet_preimage::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type WeightInfo = pallet_preimage::weights::SubstrateWeight<Runtime>;
+    type Currency = Balances;
+    type ManagerOrigin = frame_system::EnsureRoot<AccountId>;
+    type Consideration = HoldConsideration<
+        AccountId,
+        Balances,
+        PreimageHoldReason,
+        LinearStoragePrice<PreimageBaseDeposit, PreimageByteDeposit, Balance>,
+    >;
+}
+
 impl pallet_subspace::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
     type Currency = Balances;
     type WeightInfo = pallet_subspace::autogen_weights::SubstrateWeight<Runtime>;
+    type RuntimeCall = RuntimeCall;
+    type Preimages = Preimage;
 }
 
 pub const WEIGHT_MILLISECS_PER_BLOCK: u64 = 2000;
@@ -418,6 +451,8 @@ construct_runtime!(
 
         // EVM Support
         BaseFee: pallet_base_fee,
+
+        Preimage: pallet_preimage,
     }
 );
 
@@ -681,6 +716,7 @@ impl_runtime_apis! {
         }
     }
 
+    #[api_version(2)]
     impl subspace_runtime_api::SubspaceRuntimeApi<Block> for Runtime {
 
         fn get_module_info(key: AccountId, netuid: u16) -> ModuleInfo {
@@ -690,6 +726,7 @@ impl_runtime_apis! {
             ModuleInfo {
                 stats: ModuleStats {
                     stake_from: stats.stake_from,
+                    unbonding: stats.unbonding,
                     emission: stats.emission,
                     incentive: stats.incentive,
                     dividends: stats.dividends,
@@ -705,6 +742,115 @@ impl_runtime_apis! {
                 }
             }
         }
+
+        fn get_unlocking_chunks(key: AccountId) -> Vec<UnlockChunk> {
+            SubspaceModule::get_unlocking_chunks(&key)
+                .into_iter()
+                .map(|chunk| UnlockChunk {
+                    netuid: chunk.netuid,
+                    module_key: chunk.module_key,
+                    amount: chunk.amount,
+                    unlock_block: chunk.unlock_block,
+                })
+                .collect()
+        }
+
+        fn get_stake_locks(key: AccountId) -> Vec<StakeLock> {
+            SubspaceModule::get_stake_locks(&key)
+                .into_iter()
+                .map(|lock| StakeLock {
+                    netuid: lock.netuid,
+                    amount: lock.amount,
+                    unlock_block: lock.unlock_block,
+                })
+                .collect()
+        }
+
+        fn get_treasury_streams() -> Vec<TreasuryStream> {
+            use pallet_subspace::treasury::StreamSchedule as Schedule;
+
+            SubspaceModule::get_treasury_streams()
+                .into_iter()
+                .map(|stream| TreasuryStream {
+                    id: stream.id,
+                    dest: stream.dest,
+                    value: stream.value,
+                    released: stream.released,
+                    start_block: stream.start_block,
+                    end_block: stream.start_block.saturating_add(stream.schedule.duration()),
+                    schedule: match stream.schedule {
+                        Schedule::Linear { duration } => StreamSchedule::Linear { duration },
+                        Schedule::Tranches(tranches) => {
+                            StreamSchedule::Tranches(tranches.into_inner())
+                        }
+                    },
+                })
+                .collect()
+        }
+
+        fn get_epoch_history(netuid: u16) -> Vec<EpochRecord> {
+            SubspaceModule::get_epoch_history(netuid)
+                .into_iter()
+                .map(|record| EpochRecord {
+                    block: record.block,
+                    keys: record.keys,
+                    incentive: record.incentive,
+                    dividends: record.dividends,
+                    emission: record.emission,
+                    trust: record.trust,
+                    rank: record.rank,
+                    consensus: record.consensus,
+                })
+                .collect()
+        }
+
+        fn simulate_epoch(
+            netuid: u16,
+            overrides: EpochOverrides,
+        ) -> Result<EpochSimulation, DispatchError> {
+            let overrides = pallet_subspace::EpochOverrides {
+                emission: overrides.emission,
+                kappa: overrides.kappa,
+                bonds_ma: overrides.bonds_ma,
+                max_allowed_validators: overrides.max_allowed_validators,
+                incentive_ratio: overrides.incentive_ratio,
+            };
+            let simulation = SubspaceModule::simulate_epoch(netuid, overrides)?;
+
+            Ok(EpochSimulation {
+                active_stake: simulation.active_stake,
+                consensus: simulation.consensus,
+                ranks: simulation.ranks,
+                trust: simulation.trust,
+                incentives: simulation.incentives,
+                dividends: simulation.dividends,
+                bonds: simulation.bonds,
+                pruning_scores: simulation.pruning_scores,
+                emissions: simulation.emissions,
+            })
+        }
+
+        fn get_emission_projection(blocks: u64, step: u64) -> Vec<EmissionProjection> {
+            SubspaceModule::project_emission(blocks, step)
+                .into_iter()
+                .map(|point| EmissionProjection {
+                    block: point.block,
+                    emission_per_block: point.emission_per_block,
+                    total_issued: point.total_issued,
+                })
+                .collect()
+        }
+
+        fn get_subnet_emission_shares() -> Vec<SubnetEmissionShare> {
+            SubspaceModule::project_subnet_emissions()
+                .into_iter()
+                .map(|share| SubnetEmissionShare {
+                    netuid: share.netuid,
+                    share: share.share,
+                    emission_per_block: share.emission_per_block,
+                })
+                .collect()
+        }
     }
 
 